@@ -0,0 +1,53 @@
+// Defines `window.myDrawer` for web blanket pages. Calls are posted to the app
+// as JSON and settled when it replies through `__myDrawerReply`.
+(function() {
+    const handler = window.webkit && window.webkit.messageHandlers && window.webkit.messageHandlers.myDrawer;
+    if (!handler || window.myDrawer) return;
+
+    const pending = new Map();
+    let nextId = 1;
+
+    function call(action, fields) {
+        return new Promise((resolve, reject) => {
+            const id = nextId++;
+            pending.set(id, { resolve, reject });
+            handler.postMessage(JSON.stringify(Object.assign({ id, action }, fields)));
+        });
+    }
+
+    function selectedText() {
+        const selection = window.getSelection();
+        return selection ? selection.toString() : '';
+    }
+
+    Object.defineProperty(window, '__myDrawerReply', {
+        value: function(id, error) {
+            const settle = pending.get(id);
+            if (!settle) return;
+            pending.delete(id);
+            if (error === null) {
+                settle.resolve();
+            } else {
+                settle.reject(new Error(error));
+            }
+        },
+    });
+
+    Object.defineProperty(window, 'myDrawer', {
+        value: Object.freeze({
+            /** Adds `text`, or the current selection, to the clipboard history. */
+            copyText(text) {
+                return call('copyText', { text: text === undefined ? selectedText() : String(text) });
+            },
+            /** Saves a note, by default the selection, or the page's address, titled after the page. */
+            saveToNotes(options) {
+                const { title = document.title, content = selectedText() || location.href } = options || {};
+                return call('saveToNotes', { title: String(title), content: String(content) });
+            },
+            /** Puts `text`, or the current selection, in the chat input. */
+            sendToChat(text) {
+                return call('sendToChat', { text: text === undefined ? selectedText() : String(text) });
+            },
+        }),
+    });
+})();
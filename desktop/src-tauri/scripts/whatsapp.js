@@ -0,0 +1,83 @@
+(function() {
+    const BTN_ID = 'gemini-wa-toggle';
+    
+    // Inject Styles
+    if (!document.getElementById('gemini-wa-style')) {
+         const style = document.createElement('style');
+         style.id = 'gemini-wa-style';
+         style.innerHTML = `
+            * { border-inline-start-width: 0px !important; }
+            
+            /* Custom Scrollbar */
+            ::-webkit-scrollbar {
+                width: 6px !important;
+                height: 6px !important;
+            }
+            ::-webkit-scrollbar-track {
+                background: transparent !important;
+            }
+            ::-webkit-scrollbar-thumb {
+                background-color: rgba(128, 128, 128, 0.4) !important;
+                border-radius: 3px !important;
+            }
+            ::-webkit-scrollbar-thumb:hover {
+                background-color: rgba(128, 128, 128, 0.6) !important;
+            }
+            
+            /* Hide sidebar wrapper when closed */
+            body.gemini-sidebar-closed :has(> #side) {
+                display: none !important;
+            }
+            
+            /* Fix main width when sidebar is closed */
+            body.gemini-sidebar-closed :has(> #main) {
+                max-width: calc(100vw - 64px) !important;
+            }
+
+            /* Icon Toggling */
+            /* Default (Sidebar Open): Show X (Close) */
+            .gemini-icon-close { display: block !important; }
+            .gemini-icon-menu { display: none !important; }
+
+            /* Sidebar Closed: Show Menu */
+            body.gemini-sidebar-closed .gemini-icon-close { display: none !important; }
+            body.gemini-sidebar-closed .gemini-icon-menu { display: block !important; }
+         `;
+         document.head.appendChild(style);
+    }
+
+    setInterval(() => {
+        if (document.getElementById(BTN_ID)) return;
+        
+        const firstNavItem = document.querySelector('[data-navbar-item]');
+        if (!firstNavItem) return;
+        
+        // Target: parent -> parent -> parent
+        const targetContainer = firstNavItem.parentElement?.parentElement?.parentElement;
+        if (!targetContainer) return;
+
+        const btn = document.createElement('div');
+        btn.id = BTN_ID;
+        
+        // X Icon (Close Sidebar)
+        const closeIcon = '<svg class="gemini-icon-close" viewBox="0 0 24 24" width="24" height="24" fill="currentColor"><path d="M19 6.41L17.59 5 12 10.59 6.41 5 5 6.41 10.59 12 5 17.59 6.41 19 12 13.41 17.59 19 19 17.59 13.41 12z"></path></svg>';
+        
+        // Menu Icon (Open Sidebar)
+        const menuIcon = '<svg class="gemini-icon-menu" viewBox="0 0 24 24" width="24" height="24" fill="currentColor"><path d="M3 18h18v-2H3v2zm0-5h18v-2H3v2zm0-7v2h18V6H3z"></path></svg>';
+
+        btn.innerHTML = `<div role="button" title="Toggle Sidebar" style="width: 100%; height: 100%; display: flex; align-items: center; justify-content: center;">${closeIcon}${menuIcon}</div>`;
+        
+        btn.style.cssText = 'height: 40px; width: 40px; display: flex; align-items: center; justify-content: center; cursor: pointer; border-radius: 50%; transition: background-color 0.2s; margin: 0 auto; margin-bottom: 8px; color: var(--icon, #54656f); z-index: 1000;';
+        
+        btn.onmouseenter = () => btn.style.backgroundColor = 'var(--background-default-hover, rgba(0,0,0,0.05))';
+        btn.onmouseleave = () => btn.style.backgroundColor = 'transparent';
+
+        btn.onclick = () => {
+            document.body.classList.toggle('gemini-sidebar-closed');
+            window.dispatchEvent(new Event('resize'));
+        };
+        
+        targetContainer.insertBefore(btn, targetContainer.firstChild);
+
+    }, 1000);
+})();
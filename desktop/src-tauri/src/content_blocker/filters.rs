@@ -0,0 +1,410 @@
+//! EasyList-style filters converted to WebKit content blocker rules.
+//!
+//! Only what the rule format can express faithfully is converted: URL
+//! filters with type, party and domain options, `@@` exceptions and element
+//! hiding. Anything else (regex filters, scriptlets, extended CSS, redirects)
+//! is counted as skipped rather than approximated.
+
+use serde::Serialize;
+
+/// WebKit refuses to compile lists with more rules than this.
+pub const MAX_RULES: usize = 150_000;
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct ContentRule {
+    pub trigger: Trigger,
+    pub action: Action,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub struct Trigger {
+    pub url_filter: String,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub url_filter_is_case_sensitive: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub resource_type: Vec<&'static str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub load_type: Vec<&'static str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub if_domain: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unless_domain: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum Action {
+    Block,
+    /// Cancels the rules before it for matching loads, which is why
+    /// exceptions are always placed after the blocking rules
+    IgnorePreviousRules,
+    CssDisplayNone { selector: String },
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParsedFilter {
+    Rule(ContentRule),
+    /// A filter WebKit can't express
+    Unsupported,
+    /// Blank lines, comments and list headers
+    Ignored,
+}
+
+// What a filter without type options covers. Top-level documents are left out
+// so a negated type never blocks whole pages.
+const RESOURCE_TYPES: [&str; 9] = ["image", "style-sheet", "script", "font", "raw", "media", "popup", "ping", "websocket"];
+
+fn resource_type(option: &str) -> Option<&'static str> {
+    Some(match option {
+        "script" => "script",
+        "image" => "image",
+        "stylesheet" | "css" => "style-sheet",
+        "font" => "font",
+        "media" => "media",
+        "xmlhttprequest" | "xhr" => "raw",
+        "websocket" => "websocket",
+        "ping" => "ping",
+        "popup" => "popup",
+        _ => return None,
+    })
+}
+
+// Extended CSS that only content scripts of adblockers understand
+const EXTENDED_SELECTORS: [&str; 8] = [
+    ":-abp-", ":has-text(", ":contains(", ":matches-css", ":xpath(", ":upward(", ":remove(", ":style(",
+];
+
+/// Lowercase host for `if-domain`/`unless-domain`, with a leading `*` so
+/// subdomains match too. WebKit only accepts ASCII (punycode) domains.
+fn rule_domain(domain: &str) -> Option<String> {
+    let domain = domain.trim().to_ascii_lowercase();
+    let valid = !domain.is_empty()
+        && domain.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+    valid.then(|| format!("*{}", domain))
+}
+
+/// Splits `a.com|~b.com` (or the comma separated form before `##`) into
+/// domains to match and domains to exclude. WebKit can't combine both in one
+/// trigger, so a mix is unsupported.
+fn domains(list: &str, separator: char) -> Option<(Vec<String>, Vec<String>)> {
+    let mut include = Vec::new();
+    let mut exclude = Vec::new();
+    for domain in list.split(separator).filter(|d| !d.is_empty()) {
+        match domain.strip_prefix('~') {
+            Some(domain) => exclude.push(rule_domain(domain)?),
+            None => include.push(rule_domain(domain)?),
+        }
+    }
+    if !include.is_empty() && !exclude.is_empty() {
+        return None;
+    }
+    Some((include, exclude))
+}
+
+/// WebKit's regex subset for an adblock URL pattern: `||` anchors to the
+/// domain, `|` to the start or end, `*` is a wildcard and `^` a separator.
+fn url_filter(pattern: &str) -> Option<String> {
+    let mut filter = String::new();
+    let mut rest = pattern;
+    if let Some(after) = rest.strip_prefix("||") {
+        filter.push_str("^[^:]+://+([^/]+\\.)?");
+        rest = after;
+    } else if let Some(after) = rest.strip_prefix('|') {
+        filter.push('^');
+        rest = after;
+    }
+    let (rest, anchored_end) = match rest.strip_suffix('|') {
+        Some(rest) => (rest, true),
+        None => (rest, false),
+    };
+
+    for c in rest.chars() {
+        match c {
+            '*' => filter.push_str(".*"),
+            // Loaded URLs always have a path, so a separator is never the end
+            '^' => filter.push_str("[/:?=&]"),
+            '.' | '+' | '?' | '(' | ')' | '[' | ']' | '{' | '}' | '\\' | '$' | '|' => {
+                filter.push('\\');
+                filter.push(c);
+            }
+            c if !c.is_ascii() => return None,
+            c => filter.push(c),
+        }
+    }
+    if anchored_end {
+        filter.push('$');
+    }
+    if filter.is_empty() {
+        filter.push_str(".*");
+    }
+    Some(filter)
+}
+
+fn parse_hiding(line: &str, at: usize) -> ParsedFilter {
+    let (domain_list, selector) = (&line[..at], &line[at + 2..]);
+    if selector.is_empty() || EXTENDED_SELECTORS.iter().any(|ext| selector.contains(ext)) {
+        return ParsedFilter::Unsupported;
+    }
+    let Some((if_domain, unless_domain)) = domains(domain_list, ',') else {
+        return ParsedFilter::Unsupported;
+    };
+
+    ParsedFilter::Rule(ContentRule {
+        trigger: Trigger {
+            url_filter: ".*".into(),
+            if_domain,
+            unless_domain,
+            ..Default::default()
+        },
+        action: Action::CssDisplayNone {
+            selector: selector.to_string(),
+        },
+    })
+}
+
+fn parse_network(line: &str) -> ParsedFilter {
+    let (exception, body) = match line.strip_prefix("@@") {
+        Some(body) => (true, body),
+        None => (false, line),
+    };
+    // Options follow the last `$`, unless that `$` is part of a URL
+    let (pattern, options) = match body.rsplit_once('$') {
+        Some((pattern, options)) if !options.contains('/') => (pattern, options),
+        _ => (body, ""),
+    };
+    if pattern.len() > 1 && pattern.starts_with('/') && pattern.ends_with('/') {
+        return ParsedFilter::Unsupported;
+    }
+    let Some(url_filter) = url_filter(pattern) else {
+        return ParsedFilter::Unsupported;
+    };
+
+    let mut trigger = Trigger {
+        url_filter,
+        ..Default::default()
+    };
+    let mut included = Vec::new();
+    let mut excluded = Vec::new();
+    for option in options.split(',').filter(|o| !o.is_empty()) {
+        let (negated, name) = match option.strip_prefix('~') {
+            Some(name) => (true, name),
+            None => (false, option),
+        };
+        match name {
+            "third-party" | "3p" => trigger.load_type = vec![if negated { "first-party" } else { "third-party" }],
+            "first-party" | "1p" => trigger.load_type = vec![if negated { "third-party" } else { "first-party" }],
+            "match-case" => trigger.url_filter_is_case_sensitive = true,
+            "important" => {}
+            _ if name.starts_with("domain=") => {
+                let Some((include, exclude)) = domains(&name["domain=".len()..], '|') else {
+                    return ParsedFilter::Unsupported;
+                };
+                trigger.if_domain = include;
+                trigger.unless_domain = exclude;
+            }
+            _ => match resource_type(name) {
+                Some(kind) if negated => excluded.push(kind),
+                Some(kind) => included.push(kind),
+                None => return ParsedFilter::Unsupported,
+            },
+        }
+    }
+    trigger.resource_type = if !included.is_empty() {
+        included
+    } else if !excluded.is_empty() {
+        RESOURCE_TYPES.into_iter().filter(|t| !excluded.contains(t)).collect()
+    } else {
+        Vec::new()
+    };
+
+    ParsedFilter::Rule(ContentRule {
+        trigger,
+        action: if exception { Action::IgnorePreviousRules } else { Action::Block },
+    })
+}
+
+/// Converts one line of a filter list.
+pub fn parse_filter(line: &str) -> ParsedFilter {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('!') || line.starts_with('[') {
+        return ParsedFilter::Ignored;
+    }
+    // Hiding exceptions, extended CSS, scriptlets and CSS injection
+    if ["#@#", "#?#", "#$#", "#%#", "#@$#", "#@?#"].iter().any(|marker| line.contains(marker)) {
+        return ParsedFilter::Unsupported;
+    }
+    match line.find("##") {
+        Some(at) => parse_hiding(line, at),
+        None => parse_network(line),
+    }
+}
+
+/// `https://www.Example.com/page` → `example.com`, or `None` if `input`
+/// doesn't look like a domain.
+pub fn normalize_domain(input: &str) -> Option<String> {
+    let input = input.trim().to_ascii_lowercase();
+    let host = input.split_once("://").map_or(input.as_str(), |(_, rest)| rest);
+    let host = host.split(['/', ':', '?', '#']).next().unwrap_or_default();
+    let host = host.strip_prefix("www.").unwrap_or(host);
+
+    let valid = host.contains('.')
+        && !host.starts_with('.')
+        && !host.ends_with('.')
+        && host.chars().all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-');
+    valid.then(|| host.to_string())
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct CompiledRules {
+    pub rules: Vec<ContentRule>,
+    /// Filters that couldn't be converted or didn't fit under `MAX_RULES`
+    pub skipped: usize,
+}
+
+/// Rules for every filter in `lists`, with blocking off on the `allowlist`
+/// sites. Blocking and hiding rules come first, then the exceptions that
+/// cancel them.
+pub fn compile<'a>(lists: impl IntoIterator<Item = &'a str>, allowlist: &[String]) -> CompiledRules {
+    let mut blocking = Vec::new();
+    let mut exceptions = Vec::new();
+    let mut skipped = 0;
+
+    for line in lists.into_iter().flat_map(str::lines) {
+        match parse_filter(line) {
+            ParsedFilter::Rule(rule) if rule.action == Action::IgnorePreviousRules => exceptions.push(rule),
+            ParsedFilter::Rule(rule) => blocking.push(rule),
+            ParsedFilter::Unsupported => skipped += 1,
+            ParsedFilter::Ignored => {}
+        }
+    }
+
+    let allowed: Vec<String> = allowlist.iter().filter_map(|d| rule_domain(d)).collect();
+    if !allowed.is_empty() {
+        exceptions.push(ContentRule {
+            trigger: Trigger {
+                url_filter: ".*".into(),
+                if_domain: allowed,
+                ..Default::default()
+            },
+            action: Action::IgnorePreviousRules,
+        });
+    }
+
+    // Dropping blocking rules only lets more through; exceptions must survive
+    exceptions.truncate(MAX_RULES);
+    let room = MAX_RULES - exceptions.len();
+    if blocking.len() > room {
+        skipped += blocking.len() - room;
+        blocking.truncate(room);
+    }
+
+    blocking.extend(exceptions);
+    CompiledRules {
+        rules: blocking,
+        skipped,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(line: &str) -> ContentRule {
+        match parse_filter(line) {
+            ParsedFilter::Rule(rule) => rule,
+            other => panic!("{} parsed as {:?}", line, other),
+        }
+    }
+
+    #[test]
+    fn converts_network_filters() {
+        let ads = rule("||ads.example.com^$third-party,script,domain=news.com|blog.com");
+        assert_eq!(ads.trigger.url_filter, "^[^:]+://+([^/]+\\.)?ads\\.example\\.com[/:?=&]");
+        assert_eq!(ads.trigger.load_type, vec!["third-party"]);
+        assert_eq!(ads.trigger.resource_type, vec!["script"]);
+        assert_eq!(ads.trigger.if_domain, vec!["*news.com", "*blog.com"]);
+        assert_eq!(ads.action, Action::Block);
+
+        let banner = rule("|https://cdn.example.com/banner*.gif|");
+        assert_eq!(banner.trigger.url_filter, "^https://cdn\\.example\\.com/banner.*\\.gif$");
+
+        let not_images = rule("/track/pixel?$~image,match-case");
+        assert!(not_images.trigger.url_filter_is_case_sensitive);
+        assert!(!not_images.trigger.resource_type.contains(&"image"));
+        assert!(not_images.trigger.resource_type.contains(&"script"));
+
+        let exception = rule("@@||example.com/ads.js$~third-party");
+        assert_eq!(exception.action, Action::IgnorePreviousRules);
+        assert_eq!(exception.trigger.load_type, vec!["first-party"]);
+    }
+
+    #[test]
+    fn converts_element_hiding() {
+        let generic = rule("##.ad-banner");
+        assert_eq!(generic.action, Action::CssDisplayNone { selector: ".ad-banner".into() });
+        assert!(generic.trigger.if_domain.is_empty());
+
+        let scoped = rule("~shop.example.com##div[id^=\"sponsor\"]");
+        assert_eq!(scoped.trigger.unless_domain, vec!["*shop.example.com"]);
+    }
+
+    #[test]
+    fn skips_what_webkit_cannot_express() {
+        for line in [
+            "/banner\\d+/",
+            "||example.com^$csp=script-src 'none'",
+            "||example.com^$redirect=noopjs",
+            "||example.com^$domain=a.com|~b.a.com",
+            "example.com#@#.ad",
+            "example.com##.ad:-abp-has(.sponsored)",
+            "example.com#$#abort-on-property-read ads",
+            "||bücher.de^",
+        ] {
+            assert_eq!(parse_filter(line), ParsedFilter::Unsupported, "{}", line);
+        }
+        for line in ["", "! Title: EasyList", "[Adblock Plus 2.0]"] {
+            assert_eq!(parse_filter(line), ParsedFilter::Ignored);
+        }
+    }
+
+    #[test]
+    fn exceptions_and_allowlist_come_last() {
+        let list = "@@||example.com/ok.js\n||tracker.com^\n##.ad\n/regex/\n";
+        let compiled = compile([list], &["news.com".to_string()]);
+
+        assert_eq!(compiled.skipped, 1);
+        let actions: Vec<_> = compiled.rules.iter().map(|r| &r.action).collect();
+        assert_eq!(actions[0], &Action::Block);
+        assert!(matches!(actions[1], Action::CssDisplayNone { .. }));
+        assert_eq!(actions[2], &Action::IgnorePreviousRules);
+        assert_eq!(compiled.rules[3].trigger.if_domain, vec!["*news.com"]);
+
+        let json = serde_json::to_value(&compiled.rules[3]).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "trigger": { "url-filter": ".*", "if-domain": ["*news.com"] },
+                "action": { "type": "ignore-previous-rules" }
+            })
+        );
+    }
+
+    #[test]
+    fn rules_are_capped_without_losing_exceptions() {
+        let blocking: String = (0..MAX_RULES).map(|i| format!("||ad{}.com^\n", i)).collect();
+        let compiled = compile([blocking.as_str(), "@@||ad1.com^"], &[]);
+
+        assert_eq!(compiled.rules.len(), MAX_RULES);
+        assert_eq!(compiled.skipped, 1);
+        assert_eq!(compiled.rules.last().unwrap().action, Action::IgnorePreviousRules);
+    }
+
+    #[test]
+    fn normalizes_allowlist_domains() {
+        assert_eq!(normalize_domain("https://www.Example.com/page?q=1"), Some("example.com".into()));
+        assert_eq!(normalize_domain("news.ycombinator.com:443"), Some("news.ycombinator.com".into()));
+        assert_eq!(normalize_domain("localhost"), None);
+        assert_eq!(normalize_domain("not a domain.com"), None);
+    }
+}
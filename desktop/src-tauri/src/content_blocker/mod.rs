@@ -0,0 +1,286 @@
+//! Ad and tracker blocking for web blanket tabs.
+//!
+//! Filter lists are downloaded on request and cached in the app data dir.
+//! Until one has been downloaded a small bundled list stands in. The compiled
+//! rules are handed to the web blanket, which attaches them to every tab.
+
+pub mod filters;
+
+use crate::error::AppError;
+use crate::settings::open_db;
+use filters::{normalize_domain, CompiledRules};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+const BUNDLED_LIST: &str = include_str!("../../filters/trackers.txt");
+const SETTINGS_KEY: &str = "content_blocker";
+
+const EASYLIST: &str = "https://easylist.to/easylist/easylist.txt";
+const EASYPRIVACY: &str = "https://easylist.to/easylist/easyprivacy.txt";
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentBlockerSettings {
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+    /// URLs of EasyList-style filter lists
+    #[serde(default = "default_lists")]
+    pub lists: Vec<String>,
+    /// Sites blocking is turned off for, e.g. "example.com", subdomains included
+    #[serde(default)]
+    pub allowlist: Vec<String>,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+fn default_lists() -> Vec<String> {
+    vec![EASYLIST.into(), EASYPRIVACY.into()]
+}
+
+impl Default for ContentBlockerSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            lists: default_lists(),
+            allowlist: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContentBlockerStatus {
+    pub enabled: bool,
+    pub rule_count: usize,
+    /// Filters that couldn't be converted
+    pub skipped: usize,
+    /// Configured lists that haven't been downloaded yet
+    pub missing_lists: Vec<String>,
+}
+
+pub struct ContentBlocker {
+    settings: Mutex<ContentBlockerSettings>,
+    status: Mutex<ContentBlockerStatus>,
+}
+
+impl Default for ContentBlocker {
+    fn default() -> Self {
+        Self {
+            settings: Mutex::new(ContentBlockerSettings::default()),
+            status: Mutex::new(ContentBlockerStatus::default()),
+        }
+    }
+}
+
+impl ContentBlocker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn settings(&self) -> ContentBlockerSettings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    pub fn status(&self) -> ContentBlockerStatus {
+        self.status.lock().unwrap().clone()
+    }
+}
+
+fn list_path(app: &AppHandle, url: &str) -> Result<PathBuf, AppError> {
+    let dir = app.path().app_data_dir().map_err(|e| e.to_string())?.join("filter_lists");
+    Ok(dir.join(format!("{:016x}.txt", fnv1a(url))))
+}
+
+/// 64-bit FNV-1a. Cached lists are named by it, so unlike `DefaultHasher` it
+/// must not change between builds.
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Compiles the rules for the current settings from the downloaded lists and
+/// hands them to the web blanket. Blocking, so run it off the async runtime.
+fn apply(app: &AppHandle) -> Result<ContentBlockerStatus, AppError> {
+    let blocker = app.state::<ContentBlocker>();
+    let settings = blocker.settings();
+
+    let mut lists = Vec::new();
+    let mut missing_lists = Vec::new();
+    for url in &settings.lists {
+        match std::fs::read_to_string(list_path(app, url)?) {
+            Ok(list) => lists.push(list),
+            Err(_) => missing_lists.push(url.clone()),
+        }
+    }
+    if lists.is_empty() {
+        lists.push(BUNDLED_LIST.to_string());
+    }
+
+    let compiled = if settings.enabled {
+        filters::compile(lists.iter().map(String::as_str), &settings.allowlist)
+    } else {
+        CompiledRules::default()
+    };
+    let encoded = if compiled.rules.is_empty() {
+        None
+    } else {
+        Some(serde_json::to_string(&compiled.rules).map_err(|e| e.to_string())?)
+    };
+    crate::web_blanket::set_content_rules(app, encoded);
+
+    let status = ContentBlockerStatus {
+        enabled: settings.enabled,
+        rule_count: compiled.rules.len(),
+        skipped: compiled.skipped,
+        missing_lists,
+    };
+    *blocker.status.lock().unwrap() = status.clone();
+    Ok(status)
+}
+
+async fn apply_in_background(app: &AppHandle) -> Result<ContentBlockerStatus, AppError> {
+    let app = app.clone();
+    tauri::async_runtime::spawn_blocking(move || apply(&app))
+        .await
+        .map_err(|e| AppError::Other(e.to_string()))?
+}
+
+async fn load_settings(app: &AppHandle) -> Result<ContentBlockerSettings, AppError> {
+    let pool = open_db(app).await?;
+    let row: Option<(String,)> = sqlx::query_as("SELECT value FROM settings WHERE key = ?")
+        .bind(SETTINGS_KEY)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    pool.close().await;
+
+    Ok(row.and_then(|(value,)| serde_json::from_str(&value).ok()).unwrap_or_default())
+}
+
+async fn save_settings(app: &AppHandle, settings: ContentBlockerSettings) -> Result<ContentBlockerStatus, AppError> {
+    let value = serde_json::to_string(&settings).map_err(|e| e.to_string())?;
+    let pool = open_db(app).await?;
+    sqlx::query("INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES (?, ?, ?)")
+        .bind(SETTINGS_KEY)
+        .bind(&value)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .execute(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    pool.close().await;
+
+    *app.state::<ContentBlocker>().settings.lock().unwrap() = settings;
+    apply_in_background(app).await
+}
+
+pub fn load(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        match load_settings(&app).await {
+            Ok(settings) => *app.state::<ContentBlocker>().settings.lock().unwrap() = settings,
+            Err(e) => eprintln!("Failed to load content blocker settings: {}", e),
+        }
+        if let Err(e) = apply_in_background(&app).await {
+            eprintln!("Failed to apply content blocker rules: {}", e);
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_content_blocker_settings(blocker: tauri::State<'_, ContentBlocker>) -> ContentBlockerSettings {
+    blocker.settings()
+}
+
+#[tauri::command]
+pub fn get_content_blocker_status(blocker: tauri::State<'_, ContentBlocker>) -> ContentBlockerStatus {
+    blocker.status()
+}
+
+#[tauri::command]
+pub async fn set_content_blocker_settings(
+    app: AppHandle,
+    mut settings: ContentBlockerSettings,
+) -> Result<ContentBlockerStatus, AppError> {
+    settings.lists = settings
+        .lists
+        .iter()
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty())
+        .collect();
+    let mut allowlist = Vec::new();
+    for domain in settings.allowlist.iter().filter_map(|d| normalize_domain(d)) {
+        if !allowlist.contains(&domain) {
+            allowlist.push(domain);
+        }
+    }
+    settings.allowlist = allowlist;
+
+    save_settings(&app, settings).await
+}
+
+/// Turns blocking off (`blocked: false`) or back on for the site of `url`.
+#[tauri::command]
+pub async fn set_site_blocking(app: AppHandle, url: String, blocked: bool) -> Result<ContentBlockerStatus, AppError> {
+    let domain = normalize_domain(&url).ok_or_else(|| AppError::InvalidUrl {
+        url: url.clone(),
+        reason: "no domain".into(),
+    })?;
+
+    let mut settings = app.state::<ContentBlocker>().settings();
+    settings.allowlist.retain(|d| d != &domain);
+    if !blocked {
+        settings.allowlist.push(domain);
+    }
+    save_settings(&app, settings).await
+}
+
+/// Downloads every configured list and recompiles. Lists that downloaded are
+/// used even when another one failed.
+#[tauri::command]
+pub async fn update_filter_lists(app: AppHandle) -> Result<ContentBlockerStatus, AppError> {
+    let settings = app.state::<ContentBlocker>().settings();
+    let client = reqwest::Client::new();
+    let mut failure = None;
+
+    for url in &settings.lists {
+        let fetch_error = |e: &dyn std::fmt::Display| AppError::Fetch {
+            url: url.clone(),
+            reason: e.to_string(),
+        };
+        let downloaded = async {
+            let response = client.get(url).send().await.and_then(|r| r.error_for_status());
+            let list = response.map_err(|e| fetch_error(&e))?.text().await.map_err(|e| fetch_error(&e))?;
+            let path = list_path(&app, url)?;
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir).map_err(|e| fetch_error(&e))?;
+            }
+            std::fs::write(&path, list).map_err(|e| fetch_error(&e))
+        };
+        if let Err(e) = downloaded.await {
+            eprintln!("{}", e);
+            failure.get_or_insert(e);
+        }
+    }
+
+    let status = apply_in_background(&app).await?;
+    match failure {
+        Some(e) => Err(e),
+        None => Ok(status),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn list_names_are_stable() {
+        assert_eq!(fnv1a(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a("foobar"), 0x8594_4171_f739_67e8);
+    }
+}
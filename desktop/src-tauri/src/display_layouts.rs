@@ -0,0 +1,151 @@
+use crate::layout_history::LayoutHistory;
+use crate::layout_manager::{restore_windows, IgnoredApps, RestoreOptions, WindowInfo};
+use crate::settings::open_db;
+use crate::window_backend::{DisplayInfo, SharedBackend};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+// Displays are polled rather than observed; docking takes a few seconds anyway
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Identifies a display arrangement: which displays are connected, their sizes
+/// and where they sit. Independent of the order the backend lists them in.
+pub fn arrangement_key(displays: &[DisplayInfo]) -> String {
+    let mut displays: Vec<&DisplayInfo> = displays.iter().collect();
+    displays.sort_by(|a, b| {
+        (a.frame.x, a.frame.y)
+            .partial_cmp(&(b.frame.x, b.frame.y))
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(a.id.cmp(&b.id))
+    });
+    displays
+        .iter()
+        .map(|d| {
+            format!(
+                "{}:{}x{}@{},{}",
+                d.id, d.frame.width as i64, d.frame.height as i64, d.frame.x as i64, d.frame.y as i64
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// Reports an arrangement change once it has been seen on two polls in a row,
+/// so the intermediate states while a dock connects are skipped.
+#[derive(Debug, Default)]
+pub struct ArrangementWatch {
+    current: Option<String>,
+    candidate: Option<String>,
+}
+
+impl ArrangementWatch {
+    pub fn observe(&mut self, key: String) -> Option<String> {
+        if self.current.is_none() {
+            self.current = Some(key);
+            return None;
+        }
+        if self.current.as_ref() == Some(&key) {
+            self.candidate = None;
+            return None;
+        }
+        if self.candidate.as_ref() == Some(&key) {
+            self.candidate = None;
+            self.current = Some(key.clone());
+            return Some(key);
+        }
+        self.candidate = Some(key);
+        None
+    }
+}
+
+/// The most recently saved layout marked for auto-apply under `key`.
+async fn auto_apply_layout(app: &AppHandle, key: &str) -> Result<Option<Vec<WindowInfo>>, String> {
+    let pool = open_db(app).await?;
+    let row: Option<(String,)> = sqlx::query_as(
+        "SELECT layout_data FROM window_layouts WHERE display_key = ? AND auto_apply = 1
+         ORDER BY updated_at DESC LIMIT 1",
+    )
+    .bind(key)
+    .fetch_optional(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    pool.close().await;
+
+    row.map(|(data,)| serde_json::from_str(&data).map_err(|e| e.to_string()))
+        .transpose()
+}
+
+/// Emits `display-arrangement-changed` when monitors are connected, removed or
+/// rearranged, and restores the layout saved for the new arrangement if the
+/// user marked one for auto-apply.
+pub fn spawn_watcher(app: AppHandle) {
+    std::thread::spawn(move || {
+        let my_pid = std::process::id() as i32;
+        let mut watch = ArrangementWatch::default();
+        loop {
+            let backend = app.state::<SharedBackend>();
+            if let Some(key) = watch.observe(arrangement_key(&backend.displays())) {
+                let _ = app.emit("display-arrangement-changed", &key);
+
+                match tauri::async_runtime::block_on(auto_apply_layout(&app, &key)) {
+                    Ok(Some(windows)) => {
+                        let ignored = app.state::<IgnoredApps>().get();
+                        app.state::<LayoutHistory>().record(backend.as_ref(), |backend| {
+                            restore_windows(
+                                backend,
+                                windows,
+                                &RestoreOptions::default(),
+                                &ignored,
+                                my_pid,
+                                &|_| Ok(()),
+                                &|progress| {
+                                    let _ = app.emit("layout-restore-progress", progress);
+                                },
+                            )
+                        });
+                    }
+                    Ok(None) => {}
+                    Err(e) => eprintln!("Failed to load layout for displays: {}", e),
+                }
+            }
+            std::thread::sleep(POLL_INTERVAL);
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_display_arrangement(backend: tauri::State<'_, SharedBackend>) -> String {
+    arrangement_key(&backend.displays())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_backend::fake::display;
+
+    #[test]
+    fn arrangement_key_ignores_listing_order() {
+        let docked = [display(2, 1000.0, 0.0), display(1, 0.0, 0.0)];
+        assert_eq!(arrangement_key(&docked), "1:1000x800@0,0;2:1000x800@1000,0");
+
+        let reversed = [display(1, 0.0, 0.0), display(2, 1000.0, 0.0)];
+        assert_eq!(arrangement_key(&docked), arrangement_key(&reversed));
+        assert_ne!(arrangement_key(&docked), arrangement_key(&[display(1, 0.0, 0.0)]));
+    }
+
+    #[test]
+    fn watch_reports_a_change_once_it_settles() {
+        let mut watch = ArrangementWatch::default();
+        assert_eq!(watch.observe("laptop".into()), None);
+        assert_eq!(watch.observe("laptop".into()), None);
+
+        // A one-poll blip is ignored
+        assert_eq!(watch.observe("mirrored".into()), None);
+        assert_eq!(watch.observe("docked".into()), None);
+        assert_eq!(watch.observe("docked".into()), Some("docked".into()));
+        assert_eq!(watch.observe("docked".into()), None);
+
+        assert_eq!(watch.observe("laptop".into()), None);
+        assert_eq!(watch.observe("laptop".into()), Some("laptop".into()));
+    }
+}
@@ -0,0 +1,549 @@
+//! Drag-to-snap: while a window is being dragged, an overlay shows the zones of
+//! a layout preset on the display under the pointer; dropping the window in a
+//! zone resizes it to fill that zone.
+//!
+//! A press alone doesn't start a drag. Only once the pointer has moved and the
+//! window under it has moved along (same size, new position) is it treated as
+//! the user dragging that window, so clicks and text selections are ignored.
+
+use crate::error::AppError;
+use crate::layout_history::LayoutHistory;
+use crate::layout_manager::{display_for, get_open_windows, IgnoredApps, WindowInfo, WindowRect};
+use crate::layout_presets::{builtin_preset, find_preset, PresetKind};
+use crate::platform::{PointerEvent, PointerEventKind};
+use crate::snap::SnapConfig;
+use crate::window_backend::{frames_match, DisplayInfo, SharedBackend, WindowBackend, WindowError};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+
+pub const OVERLAY_LABEL: &str = "snap-overlay";
+const SETTINGS_KEY: &str = "drag_snap";
+const DEFAULT_PRESET: &str = "grid_4";
+// Pointer travel between checks of whether the window follows it
+const DRAG_STEP: f64 = 8.0;
+// Past this the press was a click or a selection, not a window drag
+const MAX_DRAG_CHECK: f64 = 64.0;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DragSnapSettings {
+    pub enabled: bool,
+    /// Layout preset whose slots are the zones
+    pub preset: String,
+}
+
+impl Default for DragSnapSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            preset: DEFAULT_PRESET.to_string(),
+        }
+    }
+}
+
+/// What the overlay shows: the display's frame, the zones relative to it and
+/// the zone under the pointer.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ZoneOverlay {
+    pub display: WindowRect,
+    pub zones: Vec<WindowRect>,
+    pub active: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DragUpdate {
+    Show(ZoneOverlay),
+    Hide,
+    /// Hide; the window was dropped in a zone but would not move there
+    DropFailed {
+        app_name: String,
+        title: String,
+        error: WindowError,
+    },
+}
+
+#[derive(Debug, Default)]
+enum DragState {
+    #[default]
+    Idle,
+    Pressed {
+        window: WindowInfo,
+        start: (f64, f64),
+        next_check: f64,
+    },
+    /// Button held, but not dragging a window
+    Ignored,
+    Dragging {
+        window: WindowInfo,
+        displays: Vec<DisplayInfo>,
+        display_id: u32,
+        zones: Vec<WindowRect>,
+        active: Option<usize>,
+    },
+}
+
+#[derive(Debug, Default)]
+pub struct DragTracker {
+    state: DragState,
+}
+
+impl DragTracker {
+    /// Advances the drag with one pointer event. `zones_for` gives the zones
+    /// for a display's work area. Drops the window into the active zone on
+    /// release.
+    pub fn handle(
+        &mut self,
+        backend: &dyn WindowBackend,
+        zones_for: &dyn Fn(&WindowRect) -> Vec<WindowRect>,
+        kind: PointerEventKind,
+        (x, y): (f64, f64),
+        ignored: &[String],
+        my_pid: i32,
+    ) -> Option<DragUpdate> {
+        match kind {
+            PointerEventKind::Press => {
+                let hidden = self.cancel();
+                self.state = get_open_windows(backend, ignored, my_pid)
+                    .into_iter()
+                    .find(|w| w.frame.contains(x, y))
+                    .map_or(DragState::Ignored, |window| DragState::Pressed {
+                        window,
+                        start: (x, y),
+                        next_check: DRAG_STEP,
+                    });
+                hidden
+            }
+            PointerEventKind::Drag => self.drag(backend, zones_for, x, y),
+            PointerEventKind::Release => {
+                let state = std::mem::take(&mut self.state);
+                let DragState::Dragging { window, zones, active, .. } = state else {
+                    return None;
+                };
+                let Some(zone) = active.and_then(|i| zones.get(i)) else {
+                    return Some(DragUpdate::Hide);
+                };
+                match backend.set_frame(&window, zone) {
+                    Ok(()) => Some(DragUpdate::Hide),
+                    Err(error) => Some(DragUpdate::DropFailed {
+                        app_name: window.app_name,
+                        title: window.title,
+                        error,
+                    }),
+                }
+            }
+            // A move without the button means the release was missed
+            PointerEventKind::Move => self.cancel(),
+        }
+    }
+
+    fn cancel(&mut self) -> Option<DragUpdate> {
+        let was_dragging = matches!(self.state, DragState::Dragging { .. });
+        self.state = DragState::Idle;
+        was_dragging.then_some(DragUpdate::Hide)
+    }
+
+    fn drag(
+        &mut self,
+        backend: &dyn WindowBackend,
+        zones_for: &dyn Fn(&WindowRect) -> Vec<WindowRect>,
+        x: f64,
+        y: f64,
+    ) -> Option<DragUpdate> {
+        match &mut self.state {
+            DragState::Pressed {
+                window,
+                start,
+                next_check,
+            } => {
+                let distance = (x - start.0).hypot(y - start.1);
+                if distance < *next_check {
+                    return None;
+                }
+                let live = backend.list_windows().into_iter().find(|w| w.id == window.id);
+                if !live.is_some_and(|live| is_moved(&window.frame, &live.frame)) {
+                    if distance > MAX_DRAG_CHECK {
+                        self.state = DragState::Ignored;
+                    } else {
+                        *next_check = distance + DRAG_STEP;
+                    }
+                    return None;
+                }
+
+                let window = window.clone();
+                let displays = backend.displays();
+                let display = display_for(&displays, &point(x, y))?.clone();
+                let zones = zones_for(&display.work_area);
+                let active = zone_at(&zones, x, y);
+                let overlay = overlay(&display, &zones, active);
+                self.state = DragState::Dragging {
+                    window,
+                    displays,
+                    display_id: display.id,
+                    zones,
+                    active,
+                };
+                Some(DragUpdate::Show(overlay))
+            }
+            DragState::Dragging {
+                displays,
+                display_id,
+                zones,
+                active,
+                ..
+            } => {
+                let display = display_for(displays, &point(x, y))?;
+                let moved_display = display.id != *display_id;
+                if moved_display {
+                    *display_id = display.id;
+                    *zones = zones_for(&display.work_area);
+                }
+                let now_active = zone_at(zones, x, y);
+                if !moved_display && now_active == *active {
+                    return None;
+                }
+                *active = now_active;
+                Some(DragUpdate::Show(overlay(display, zones, now_active)))
+            }
+            DragState::Idle | DragState::Ignored => None,
+        }
+    }
+}
+
+/// Same size at a new position: the window is being moved, not resized.
+fn is_moved(before: &WindowRect, after: &WindowRect) -> bool {
+    let same_size = WindowRect {
+        x: before.x,
+        y: before.y,
+        ..after.clone()
+    };
+    !frames_match(before, after) && frames_match(before, &same_size)
+}
+
+fn point(x: f64, y: f64) -> WindowRect {
+    WindowRect {
+        x,
+        y,
+        width: 0.0,
+        height: 0.0,
+    }
+}
+
+fn zone_at(zones: &[WindowRect], x: f64, y: f64) -> Option<usize> {
+    zones.iter().position(|z| z.contains(x, y))
+}
+
+fn overlay(display: &DisplayInfo, zones: &[WindowRect], active: Option<usize>) -> ZoneOverlay {
+    let origin = &display.frame;
+    ZoneOverlay {
+        display: origin.clone(),
+        zones: zones
+            .iter()
+            .map(|z| WindowRect {
+                x: z.x - origin.x,
+                y: z.y - origin.y,
+                ..z.clone()
+            })
+            .collect(),
+        active,
+    }
+}
+
+pub struct DragSnap {
+    tracker: Mutex<DragTracker>,
+    settings: Mutex<DragSnapSettings>,
+    layout: Mutex<PresetKind>,
+}
+
+impl Default for DragSnap {
+    fn default() -> Self {
+        Self {
+            tracker: Mutex::new(DragTracker::default()),
+            settings: Mutex::new(DragSnapSettings::default()),
+            layout: Mutex::new(builtin_preset(DEFAULT_PRESET).expect("default preset").layout),
+        }
+    }
+}
+
+impl DragSnap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn settings(&self) -> DragSnapSettings {
+        self.settings.lock().unwrap().clone()
+    }
+}
+
+/// Restores the saved settings. Falls back to the default zones when the saved
+/// preset has since been deleted.
+pub fn load(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let settings = match crate::settings::load::<DragSnapSettings>(&app, SETTINGS_KEY).await {
+            Ok(Some(settings)) => settings,
+            Ok(None) => return,
+            Err(e) => {
+                eprintln!("Failed to load drag-to-snap settings: {}", e);
+                return;
+            }
+        };
+        let drag = app.state::<DragSnap>();
+        match find_preset(&app, &settings.preset).await {
+            Ok(preset) => *drag.layout.lock().unwrap() = preset.layout,
+            Err(e) => {
+                eprintln!("Drag-to-snap preset {}: {}", settings.preset, e);
+                drag.settings.lock().unwrap().enabled = settings.enabled;
+                return;
+            }
+        }
+        *drag.settings.lock().unwrap() = settings;
+    });
+}
+
+/// Creates the hidden, click-through overlay window the zones are drawn in.
+pub fn build_overlay_window(app: &AppHandle) -> Result<WebviewWindow, String> {
+    let url = format!("index.html?overlay={}", OVERLAY_LABEL);
+    let window = WebviewWindowBuilder::new(app, OVERLAY_LABEL, WebviewUrl::App(url.into()))
+        .title("Snap Zones")
+        .decorations(false)
+        .transparent(true)
+        .shadow(false)
+        .resizable(false)
+        .focused(false)
+        .visible(false)
+        .always_on_top(true)
+        .visible_on_all_workspaces(true)
+        .skip_taskbar(true)
+        .build()
+        .map_err(|e| e.to_string())?;
+    window.set_ignore_cursor_events(true).unwrap_or(());
+    Ok(window)
+}
+
+/// Called for every pointer event from the platform's event source.
+pub fn handle_pointer(app: &AppHandle, event: PointerEvent) {
+    let drag = app.state::<DragSnap>();
+    if !drag.settings().enabled {
+        return;
+    }
+    let backend = app.state::<SharedBackend>();
+    let snap = app.state::<SnapConfig>().get();
+    let layout = drag.layout.lock().unwrap().clone();
+    let ignored = app.state::<IgnoredApps>().get();
+    let my_pid = std::process::id() as i32;
+
+    let update = app.state::<LayoutHistory>().record(backend.as_ref(), |backend| {
+        drag.tracker.lock().unwrap().handle(
+            backend,
+            &|area| layout.zones(area, &snap),
+            event.kind,
+            (event.location.x, event.location.y),
+            &ignored,
+            my_pid,
+        )
+    });
+
+    let Some(window) = app.get_webview_window(OVERLAY_LABEL) else {
+        return;
+    };
+    match update {
+        Some(DragUpdate::Show(overlay)) => {
+            place_overlay(&window, &overlay.display);
+            let _ = app.emit_to(OVERLAY_LABEL, "snap-zones", &overlay);
+            window.show().unwrap_or(());
+        }
+        Some(DragUpdate::Hide) => window.hide().unwrap_or(()),
+        Some(DragUpdate::DropFailed { app_name, title, error }) => {
+            window.hide().unwrap_or(());
+            eprintln!("Failed to snap \"{}\" ({}): {}", title, app_name, error);
+        }
+        None => {}
+    }
+}
+
+/// Display frames are in points on macOS and pixels on X11.
+fn place_overlay(window: &WebviewWindow, frame: &WindowRect) {
+    #[cfg(target_os = "macos")]
+    {
+        use tauri::{LogicalPosition, LogicalSize};
+        window.set_position(LogicalPosition::new(frame.x, frame.y)).unwrap_or(());
+        window.set_size(LogicalSize::new(frame.width, frame.height)).unwrap_or(());
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        use tauri::{PhysicalPosition, PhysicalSize};
+        window
+            .set_position(PhysicalPosition::new(frame.x as i32, frame.y as i32))
+            .unwrap_or(());
+        window
+            .set_size(PhysicalSize::new(frame.width as u32, frame.height as u32))
+            .unwrap_or(());
+    }
+}
+
+#[tauri::command]
+pub fn get_drag_snap_settings(drag: tauri::State<'_, DragSnap>) -> DragSnapSettings {
+    drag.settings()
+}
+
+#[tauri::command]
+pub async fn set_drag_snap_settings(
+    app: AppHandle,
+    drag: tauri::State<'_, DragSnap>,
+    settings: DragSnapSettings,
+) -> Result<(), AppError> {
+    let preset = find_preset(&app, &settings.preset).await?;
+    crate::settings::save(&app, SETTINGS_KEY, &settings).await?;
+    *drag.layout.lock().unwrap() = preset.layout;
+    *drag.settings.lock().unwrap() = settings;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_backend::fake::{display, rect, window, FakeBackend};
+    use PointerEventKind::*;
+
+    const MY_PID: i32 = 1;
+
+    fn backend() -> FakeBackend {
+        FakeBackend::new(
+            vec![window(10, 100, "Safari", "Home", rect(100.0, 100.0, 400.0, 300.0))],
+            vec![display(1, 0.0, 0.0), display(2, 1000.0, 0.0)],
+        )
+    }
+
+    fn halves(area: &WindowRect) -> Vec<WindowRect> {
+        builtin_preset("columns_2")
+            .unwrap()
+            .layout
+            .zones(area, &Default::default())
+    }
+
+    /// The window manager moving the window along with the pointer.
+    fn follow(fake: &FakeBackend, dx: f64, dy: f64) {
+        let mut windows = fake.windows.lock().unwrap();
+        windows[0].frame.x = 100.0 + dx;
+        windows[0].frame.y = 100.0 + dy;
+    }
+
+    fn send(tracker: &mut DragTracker, fake: &FakeBackend, kind: PointerEventKind, x: f64, y: f64) -> Option<DragUpdate> {
+        tracker.handle(fake, &halves, kind, (x, y), &[], MY_PID)
+    }
+
+    #[test]
+    fn dropping_a_dragged_window_fills_the_zone() {
+        let fake = backend();
+        let mut tracker = DragTracker::default();
+
+        assert_eq!(send(&mut tracker, &fake, Press, 200.0, 110.0), None);
+        // Below the first check distance nothing is looked at
+        assert_eq!(send(&mut tracker, &fake, Drag, 203.0, 110.0), None);
+
+        follow(&fake, 500.0, 0.0);
+        let Some(DragUpdate::Show(shown)) = send(&mut tracker, &fake, Drag, 700.0, 110.0) else {
+            panic!("overlay not shown");
+        };
+        assert_eq!(shown.display, rect(0.0, 0.0, 1000.0, 800.0));
+        assert_eq!(shown.zones, vec![rect(0.0, 25.0, 500.0, 775.0), rect(500.0, 25.0, 500.0, 775.0)]);
+        assert_eq!(shown.active, Some(1));
+
+        // Same zone: nothing to redraw
+        assert_eq!(send(&mut tracker, &fake, Drag, 720.0, 130.0), None);
+
+        assert_eq!(send(&mut tracker, &fake, Release, 720.0, 130.0), Some(DragUpdate::Hide));
+        assert_eq!(*fake.moves.lock().unwrap(), vec![(10, rect(500.0, 25.0, 500.0, 775.0))]);
+    }
+
+    #[test]
+    fn a_failed_drop_is_reported() {
+        let fake = backend();
+        let mut tracker = DragTracker::default();
+
+        send(&mut tracker, &fake, Press, 200.0, 110.0);
+        follow(&fake, 500.0, 0.0);
+        send(&mut tracker, &fake, Drag, 700.0, 110.0);
+
+        let refusal = WindowError::AppNotScriptable { app_name: "Safari".into() };
+        *fake.refuse.lock().unwrap() = Some(refusal.clone());
+        assert_eq!(
+            send(&mut tracker, &fake, Release, 700.0, 110.0),
+            Some(DragUpdate::DropFailed {
+                app_name: "Safari".into(),
+                title: "Home".into(),
+                error: refusal,
+            })
+        );
+    }
+
+    #[test]
+    fn zones_follow_the_pointer_across_displays() {
+        let fake = backend();
+        let mut tracker = DragTracker::default();
+
+        send(&mut tracker, &fake, Press, 200.0, 110.0);
+        follow(&fake, 20.0, 0.0);
+        send(&mut tracker, &fake, Drag, 220.0, 110.0);
+
+        let Some(DragUpdate::Show(shown)) = send(&mut tracker, &fake, Drag, 1200.0, 110.0) else {
+            panic!("overlay not updated");
+        };
+        assert_eq!(shown.display.x, 1000.0);
+        assert_eq!(shown.zones[0], rect(0.0, 25.0, 500.0, 775.0));
+        assert_eq!(shown.active, Some(0));
+
+        send(&mut tracker, &fake, Release, 1200.0, 110.0);
+        assert_eq!(fake.moves.lock().unwrap()[0].1, rect(1000.0, 25.0, 500.0, 775.0));
+    }
+
+    #[test]
+    fn presses_that_dont_move_a_window_are_ignored() {
+        let fake = backend();
+        let mut tracker = DragTracker::default();
+
+        // Selecting text: the pointer moves, the window doesn't
+        send(&mut tracker, &fake, Press, 200.0, 110.0);
+        for x in (210..400).step_by(10) {
+            assert_eq!(send(&mut tracker, &fake, Drag, x as f64, 110.0), None);
+        }
+        // Even if the window moves later, this press is no longer a drag
+        follow(&fake, 300.0, 0.0);
+        assert_eq!(send(&mut tracker, &fake, Drag, 500.0, 110.0), None);
+        assert_eq!(send(&mut tracker, &fake, Release, 500.0, 110.0), None);
+
+        // Pressing on the desktop
+        send(&mut tracker, &fake, Press, 900.0, 700.0);
+        assert_eq!(send(&mut tracker, &fake, Drag, 950.0, 700.0), None);
+        assert!(fake.moves.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn resizing_is_not_a_drag() {
+        let fake = backend();
+        let mut tracker = DragTracker::default();
+
+        send(&mut tracker, &fake, Press, 499.0, 200.0);
+        fake.windows.lock().unwrap()[0].frame.width = 450.0;
+        assert_eq!(send(&mut tracker, &fake, Drag, 549.0, 200.0), None);
+    }
+
+    #[test]
+    fn dropping_outside_zones_or_missing_the_release_just_hides() {
+        let fake = backend();
+        let mut tracker = DragTracker::default();
+
+        send(&mut tracker, &fake, Press, 200.0, 110.0);
+        follow(&fake, 0.0, -100.0);
+        let shown = send(&mut tracker, &fake, Drag, 200.0, 10.0);
+        assert!(matches!(shown, Some(DragUpdate::Show(ZoneOverlay { active: None, .. }))));
+        assert_eq!(send(&mut tracker, &fake, Release, 200.0, 10.0), Some(DragUpdate::Hide));
+
+        send(&mut tracker, &fake, Press, 200.0, 10.0);
+        follow(&fake, 50.0, -100.0);
+        send(&mut tracker, &fake, Drag, 250.0, 10.0);
+        assert_eq!(send(&mut tracker, &fake, Move, 250.0, 10.0), Some(DragUpdate::Hide));
+        assert!(fake.moves.lock().unwrap().is_empty());
+    }
+}
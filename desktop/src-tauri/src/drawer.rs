@@ -0,0 +1,674 @@
+use crate::error::AppError;
+use crate::layout_manager::{FocusTracker, IgnoredApps};
+use crate::window_backend::SharedBackend;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, PhysicalPosition, WebviewUrl, WebviewWindow, WebviewWindowBuilder};
+
+pub const MAIN_DRAWER_ID: &str = "main";
+// Secondary drawer ids (and window labels) start with this; the capability
+// granting drawer windows their permissions matches on it
+pub const DRAWER_ID_PREFIX: &str = "drawer-";
+// Settings key of every drawer's spec
+const SETTINGS_KEY: &str = "drawers";
+// Where the frontend kept the main drawer's edge before specs were saved
+const LEGACY_POSITION_KEY: &str = "drawer_position";
+
+// Distance (in px) from the screen edge that counts as "touching" it
+const EDGE_THRESHOLD: f64 = 5.0;
+// Height of the hot corner zones
+const CORNER_SIZE: f64 = 50.0;
+// Gap between the open drawer and the screen edge (logical px)
+const EDGE_MARGIN: f64 = 20.0;
+
+const SIDE_LEFT: u8 = 0;
+const SIDE_RIGHT: u8 = 1;
+
+// Parts of the screen edges a drawer can be triggered from
+const ZONE_LEFT_TOP: u8 = 1 << 0;
+const ZONE_LEFT_MIDDLE: u8 = 1 << 1;
+const ZONE_LEFT_BOTTOM: u8 = 1 << 2;
+const ZONE_RIGHT_TOP: u8 = 1 << 3;
+const ZONE_RIGHT_MIDDLE: u8 = 1 << 4;
+const ZONE_RIGHT_BOTTOM: u8 = 1 << 5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DrawerEdge {
+    Left,
+    Right,
+    HotCorners,
+    TopLeft,
+    BottomLeft,
+    TopRight,
+    BottomRight,
+}
+
+impl FromStr for DrawerEdge {
+    type Err = String;
+
+    fn from_str(config: &str) -> Result<Self, Self::Err> {
+        match config {
+            "left" => Ok(Self::Left),
+            "right" => Ok(Self::Right),
+            "hot-corners" => Ok(Self::HotCorners),
+            "top-left" => Ok(Self::TopLeft),
+            "bottom-left" => Ok(Self::BottomLeft),
+            "top-right" => Ok(Self::TopRight),
+            "bottom-right" => Ok(Self::BottomRight),
+            _ => Err(format!("Unknown drawer edge \"{}\"", config)),
+        }
+    }
+}
+
+impl DrawerEdge {
+    /// Reads a saved edge, falling back to the left edge for anything unknown.
+    pub fn from_config(config: &str) -> Self {
+        config.parse().unwrap_or(Self::Left)
+    }
+
+    pub fn as_config(&self) -> &'static str {
+        match self {
+            Self::Left => "left",
+            Self::Right => "right",
+            Self::HotCorners => "hot-corners",
+            Self::TopLeft => "top-left",
+            Self::BottomLeft => "bottom-left",
+            Self::TopRight => "top-right",
+            Self::BottomRight => "bottom-right",
+        }
+    }
+
+    fn as_u8(&self) -> u8 {
+        match self {
+            Self::Left => 0,
+            Self::Right => 1,
+            Self::HotCorners => 2,
+            Self::TopLeft => 3,
+            Self::BottomLeft => 4,
+            Self::TopRight => 5,
+            Self::BottomRight => 6,
+        }
+    }
+
+    fn from_u8(val: u8) -> Self {
+        match val {
+            1 => Self::Right,
+            2 => Self::HotCorners,
+            3 => Self::TopLeft,
+            4 => Self::BottomLeft,
+            5 => Self::TopRight,
+            6 => Self::BottomRight,
+            _ => Self::Left,
+        }
+    }
+
+    /// Side the drawer opens on when it is shown without a pointer trigger.
+    fn default_side(&self) -> u8 {
+        match self {
+            Self::Right | Self::TopRight | Self::BottomRight => SIDE_RIGHT,
+            _ => SIDE_LEFT,
+        }
+    }
+
+    /// The edge zones `hit_test` accepts, as a mask of `ZONE_*` bits.
+    fn zones(&self) -> u8 {
+        match self {
+            Self::Left => ZONE_LEFT_TOP | ZONE_LEFT_MIDDLE | ZONE_LEFT_BOTTOM,
+            Self::Right => ZONE_RIGHT_TOP | ZONE_RIGHT_MIDDLE | ZONE_RIGHT_BOTTOM,
+            Self::HotCorners => ZONE_LEFT_TOP | ZONE_LEFT_BOTTOM | ZONE_RIGHT_TOP | ZONE_RIGHT_BOTTOM,
+            Self::TopLeft => ZONE_LEFT_TOP,
+            Self::BottomLeft => ZONE_LEFT_BOTTOM,
+            Self::TopRight => ZONE_RIGHT_TOP,
+            Self::BottomRight => ZONE_RIGHT_BOTTOM,
+        }
+    }
+
+    /// Whether some pointer position would trigger both edges.
+    pub fn overlaps(&self, other: DrawerEdge) -> bool {
+        self.zones() & other.zones() != 0
+    }
+
+    /// Returns the side the drawer should slide in from when the pointer is at (x, y),
+    /// or `None` if the pointer is not in this edge's trigger zone.
+    pub fn hit_test(&self, x: f64, y: f64, screen_width: f64, screen_height: f64) -> Option<u8> {
+        let at_left = x < EDGE_THRESHOLD;
+        let at_right = x > screen_width - EDGE_THRESHOLD;
+        let at_top = y < CORNER_SIZE;
+        let at_bottom = y > screen_height - CORNER_SIZE;
+
+        match self {
+            Self::Left if at_left => Some(SIDE_LEFT),
+            Self::Right if at_right => Some(SIDE_RIGHT),
+            Self::HotCorners if at_left && (at_top || at_bottom) => Some(SIDE_LEFT),
+            Self::HotCorners if at_right && (at_top || at_bottom) => Some(SIDE_RIGHT),
+            Self::TopLeft if at_left && at_top => Some(SIDE_LEFT),
+            Self::BottomLeft if at_left && at_bottom => Some(SIDE_LEFT),
+            Self::TopRight if at_right && at_top => Some(SIDE_RIGHT),
+            Self::BottomRight if at_right && at_bottom => Some(SIDE_RIGHT),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DrawerSpec {
+    pub id: String,
+    pub edge: String,
+    pub default_view: String,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl DrawerSpec {
+    pub fn main() -> Self {
+        Self {
+            id: MAIN_DRAWER_ID.to_string(),
+            edge: "left".to_string(),
+            default_view: "chat".to_string(),
+            width: 400.0,
+            height: 800.0,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct DrawerEventPayload {
+    drawer_id: String,
+}
+
+/// Owns the open/animating state and slide animation of a single drawer window.
+/// The drawer id doubles as the label of its webview window.
+pub struct DrawerController {
+    spec: Mutex<DrawerSpec>,
+    edge: AtomicU8,
+    is_open: AtomicBool,
+    is_animating: AtomicBool,
+    last_active_side: AtomicU8,
+}
+
+impl DrawerController {
+    pub fn new(spec: DrawerSpec) -> Self {
+        let edge = DrawerEdge::from_config(&spec.edge);
+
+        Self {
+            spec: Mutex::new(spec),
+            edge: AtomicU8::new(edge.as_u8()),
+            is_open: AtomicBool::new(false),
+            is_animating: AtomicBool::new(false),
+            last_active_side: AtomicU8::new(edge.default_side()),
+        }
+    }
+
+    pub fn id(&self) -> String {
+        self.spec.lock().unwrap().id.clone()
+    }
+
+    pub fn spec(&self) -> DrawerSpec {
+        self.spec.lock().unwrap().clone()
+    }
+
+    pub fn edge(&self) -> DrawerEdge {
+        DrawerEdge::from_u8(self.edge.load(Ordering::Relaxed))
+    }
+
+    pub fn set_edge(&self, edge: DrawerEdge) {
+        self.edge.store(edge.as_u8(), Ordering::Relaxed);
+        self.spec.lock().unwrap().edge = edge.as_config().to_string();
+        if !self.is_open() {
+            self.last_active_side.store(edge.default_side(), Ordering::Relaxed);
+        }
+    }
+
+    pub fn last_side(&self) -> u8 {
+        self.last_active_side.load(Ordering::Relaxed)
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.is_open.load(Ordering::Relaxed)
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.is_animating.load(Ordering::Relaxed)
+    }
+
+    fn window(&self, app: &AppHandle) -> Option<WebviewWindow> {
+        app.get_webview_window(&self.id())
+    }
+
+    /// Slides the drawer in from `side`. No-op while open or animating.
+    pub fn show(self: &Arc<Self>, app: &AppHandle, side: u8) {
+        if self.is_open() || self.is_animating() {
+            return;
+        }
+        let Some(window) = self.window(app) else {
+            return;
+        };
+
+        self.is_animating.store(true, Ordering::Relaxed);
+        self.last_active_side.store(side, Ordering::Relaxed);
+
+        let this = Arc::clone(self);
+        let app = app.clone();
+        std::thread::spawn(move || {
+            // Remember what the user was working in before the drawer takes focus
+            if let (Some(focus), Some(backend), Some(ignored)) = (
+                app.try_state::<FocusTracker>(),
+                app.try_state::<SharedBackend>(),
+                app.try_state::<IgnoredApps>(),
+            ) {
+                focus.record(backend.as_ref(), &ignored.get(), std::process::id() as i32);
+            }
+
+            // Disable click-through before showing
+            window.set_ignore_cursor_events(false).unwrap_or(());
+
+            // Show window before animating
+            window.show().unwrap_or(());
+            window.set_focus().unwrap_or(());
+
+            let (open_x, closed_x, center_y) = this.positions(&window, side);
+            this.animate(&window, closed_x, open_x, center_y);
+
+            this.is_open.store(true, Ordering::Relaxed);
+            this.is_animating.store(false, Ordering::Relaxed);
+            let _ = app.emit("drawer-opened", DrawerEventPayload { drawer_id: this.id() });
+        });
+    }
+
+    /// Slides the drawer back out to the side it came from. No-op while closed or animating.
+    pub fn hide(self: &Arc<Self>, app: &AppHandle) {
+        if !self.is_open() || self.is_animating() {
+            return;
+        }
+        let Some(window) = self.window(app) else {
+            return;
+        };
+
+        self.is_animating.store(true, Ordering::Relaxed);
+
+        let this = Arc::clone(self);
+        let app = app.clone();
+        std::thread::spawn(move || {
+            let side = this.last_active_side.load(Ordering::Relaxed);
+            let (open_x, closed_x, center_y) = this.positions(&window, side);
+            this.animate(&window, open_x, closed_x, center_y);
+
+            // Hide window to prevent ghosting on space switch
+            window.hide().unwrap_or(());
+
+            // Enable click-through when hidden
+            window.set_ignore_cursor_events(true).unwrap_or(());
+
+            this.is_open.store(false, Ordering::Relaxed);
+            this.is_animating.store(false, Ordering::Relaxed);
+            let _ = app.emit("drawer-closed", DrawerEventPayload { drawer_id: this.id() });
+        });
+    }
+
+    /// Returns (open_x, closed_x, center_y) in physical pixels for the window's monitor.
+    fn positions(&self, window: &WebviewWindow, side: u8) -> (i32, i32, i32) {
+        let monitor = window
+            .current_monitor()
+            .ok()
+            .flatten()
+            .or_else(|| window.primary_monitor().ok().flatten());
+        let scale_factor = monitor.as_ref().map(|m| m.scale_factor()).unwrap_or(2.0);
+        let screen_height = monitor.as_ref().map(|m| m.size().height).unwrap_or(2160) as f64; // Default to a common Retina height
+        let screen_width = monitor.as_ref().map(|m| m.size().width).unwrap_or(3840) as f64; // Approx 4k width
+
+        let spec = self.spec();
+        let width = spec.width * scale_factor;
+        let height = spec.height * scale_factor;
+
+        let (open_x, closed_x) = if side == SIDE_RIGHT {
+            ((screen_width - width - EDGE_MARGIN * scale_factor) as i32, screen_width as i32)
+        } else {
+            ((EDGE_MARGIN * scale_factor) as i32, -width as i32)
+        };
+
+        let center_y = ((screen_height - height) / 2.0) as i32;
+        (open_x, closed_x, center_y)
+    }
+
+    fn animate(&self, window: &WebviewWindow, start_x: i32, end_x: i32, y: i32) {
+        let duration_ms = 200;
+        let steps = 20;
+        let step_delay = duration_ms / steps;
+
+        for i in 0..=steps {
+            let t = i as f64 / steps as f64;
+            // Ease out cubic
+            let eased_t = 1.0 - (1.0 - t).powi(3);
+            let current_x = (start_x as f64 + (end_x as f64 - start_x as f64) * eased_t) as i32;
+
+            window
+                .set_position(PhysicalPosition::new(current_x, y))
+                .unwrap_or(());
+            std::thread::sleep(Duration::from_millis(step_delay));
+        }
+
+        // Ensure final position
+        window.set_position(PhysicalPosition::new(end_x, y)).unwrap_or(());
+    }
+}
+
+/// Registry of all drawer controllers, keyed by drawer id.
+pub struct DrawerManager {
+    drawers: Mutex<HashMap<String, Arc<DrawerController>>>,
+}
+
+impl DrawerManager {
+    pub fn new() -> Self {
+        Self {
+            drawers: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn register(&self, spec: DrawerSpec) -> Arc<DrawerController> {
+        let controller = Arc::new(DrawerController::new(spec));
+        self.drawers
+            .lock()
+            .unwrap()
+            .insert(controller.id(), Arc::clone(&controller));
+        controller
+    }
+
+    pub fn get(&self, drawer_id: &str) -> Option<Arc<DrawerController>> {
+        self.drawers.lock().unwrap().get(drawer_id).cloned()
+    }
+
+    /// Like `get`, for commands: a missing drawer is a `DrawerNotFound` error.
+    pub fn drawer(&self, drawer_id: &str) -> Result<Arc<DrawerController>, AppError> {
+        self.get(drawer_id).ok_or_else(|| AppError::DrawerNotFound {
+            drawer_id: drawer_id.to_string(),
+        })
+    }
+
+    /// Moves a drawer to `edge`, unless another drawer already opens from part
+    /// of it: a pointer position must never trigger two drawers.
+    pub fn set_edge(&self, drawer_id: &str, edge: DrawerEdge) -> Result<(), AppError> {
+        let drawer = self.drawer(drawer_id)?;
+        self.check_edge(drawer_id, edge)?;
+        drawer.set_edge(edge);
+        Ok(())
+    }
+
+    /// Fails with `DrawerEdgeTaken` if a drawer other than `drawer_id` overlaps `edge`.
+    fn check_edge(&self, drawer_id: &str, edge: DrawerEdge) -> Result<(), AppError> {
+        match self.all().into_iter().find(|d| d.id() != drawer_id && d.edge().overlaps(edge)) {
+            Some(other) => Err(AppError::DrawerEdgeTaken { drawer_id: other.id() }),
+            None => Ok(()),
+        }
+    }
+
+    pub fn remove(&self, drawer_id: &str) -> Option<Arc<DrawerController>> {
+        self.drawers.lock().unwrap().remove(drawer_id)
+    }
+
+    pub fn all(&self) -> Vec<Arc<DrawerController>> {
+        let mut drawers: Vec<_> = self.drawers.lock().unwrap().values().cloned().collect();
+        drawers.sort_by_key(|d| d.id());
+        drawers
+    }
+
+    /// Called from the pointer polling loop. Opens the closed drawer whose
+    /// trigger zone contains the pointer; `set_edge` keeps zones from overlapping.
+    pub fn handle_pointer(&self, app: &AppHandle, x: f64, y: f64, screen_width: f64, screen_height: f64) {
+        for drawer in self.all() {
+            if drawer.is_open() || drawer.is_animating() {
+                continue;
+            }
+            if let Some(side) = drawer.edge().hit_test(x, y, screen_width, screen_height) {
+                drawer.show(app, side);
+                return;
+            }
+        }
+    }
+}
+
+/// Creates the webview window for a secondary drawer. The main drawer's window
+/// comes from `tauri.conf.json`.
+pub fn build_drawer_window(app: &AppHandle, spec: &DrawerSpec) -> Result<WebviewWindow, AppError> {
+    let url = format!("index.html?drawer={}&view={}", spec.id, spec.default_view);
+
+    WebviewWindowBuilder::new(app, &spec.id, WebviewUrl::App(url.into()))
+        .title("My Drawer")
+        .inner_size(spec.width, spec.height)
+        .position(-(spec.width + 30.0), 0.0)
+        .decorations(false)
+        .transparent(true)
+        .shadow(false)
+        .resizable(false)
+        .disable_drag_drop_handler()
+        .visible(false)
+        .always_on_top(true)
+        .skip_taskbar(true)
+        .build()
+        .map_err(AppError::from)
+}
+
+// -----------------------------------------------------------------------------
+// Persistence
+// -----------------------------------------------------------------------------
+
+/// Builds the window of a secondary drawer and starts tracking it.
+fn open_drawer(app: &AppHandle, spec: DrawerSpec) -> Result<(), AppError> {
+    let window = build_drawer_window(app, &spec)?;
+    let configured = window.clone();
+    window.run_on_main_thread(move || crate::platform::configure_drawer_window(&configured))?;
+    app.state::<DrawerManager>().register(spec);
+    Ok(())
+}
+
+/// Saves every drawer's spec, so edges and secondary drawers survive a restart.
+pub async fn save_specs(app: &AppHandle) -> Result<(), AppError> {
+    let specs: Vec<DrawerSpec> = app.state::<DrawerManager>().all().iter().map(|d| d.spec()).collect();
+    crate::settings::save(app, SETTINGS_KEY, &specs).await
+}
+
+/// Puts back the main drawer's edge and reopens the secondary drawers saved
+/// by `save_specs`.
+pub fn load(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let loaded = match crate::settings::load::<Vec<DrawerSpec>>(&app, SETTINGS_KEY).await {
+            Ok(None) => crate::settings::load::<String>(&app, LEGACY_POSITION_KEY)
+                .await
+                .map(|edge| edge.map(|edge| vec![DrawerSpec { edge, ..DrawerSpec::main() }])),
+            loaded => loaded,
+        };
+        let specs = match loaded {
+            Ok(specs) => specs.unwrap_or_default(),
+            Err(e) => {
+                eprintln!("Failed to load drawers: {}", e);
+                return;
+            }
+        };
+
+        let drawers = app.state::<DrawerManager>();
+        for spec in specs {
+            if let Some(drawer) = drawers.get(&spec.id) {
+                drawer.set_edge(DrawerEdge::from_config(&spec.edge));
+            } else if let Err(e) = open_drawer(&app, spec) {
+                eprintln!("Failed to open drawer: {}", e);
+            }
+        }
+    });
+}
+
+// -----------------------------------------------------------------------------
+// Commands
+// -----------------------------------------------------------------------------
+
+#[tauri::command]
+pub async fn set_drawer_config(
+    app: AppHandle,
+    state: tauri::State<'_, DrawerManager>,
+    drawer_id: String,
+    config: String,
+) -> Result<(), AppError> {
+    let edge = config.parse().map_err(AppError::InvalidInput)?;
+    state.set_edge(&drawer_id, edge)?;
+    save_specs(&app).await
+}
+
+#[tauri::command]
+pub fn show_drawer(
+    app: AppHandle,
+    state: tauri::State<DrawerManager>,
+    drawer_id: String,
+) -> Result<(), AppError> {
+    let drawer = state.drawer(&drawer_id)?;
+    drawer.show(&app, drawer.last_side());
+    Ok(())
+}
+
+#[tauri::command]
+pub fn hide_drawer(
+    app: AppHandle,
+    state: tauri::State<DrawerManager>,
+    drawer_id: String,
+) -> Result<(), AppError> {
+    let drawer = state.drawer(&drawer_id)?;
+    drawer.hide(&app);
+    Ok(())
+}
+
+#[tauri::command]
+pub fn list_drawers(state: tauri::State<DrawerManager>) -> Vec<DrawerSpec> {
+    state.all().iter().map(|d| d.spec()).collect()
+}
+
+#[tauri::command]
+pub async fn create_drawer(
+    app: AppHandle,
+    state: tauri::State<'_, DrawerManager>,
+    spec: DrawerSpec,
+) -> Result<(), AppError> {
+    if !spec.id.starts_with(DRAWER_ID_PREFIX) {
+        return Err(AppError::InvalidInput(format!("Drawer ids must start with \"{}\"", DRAWER_ID_PREFIX)));
+    }
+    if state.get(&spec.id).is_some() {
+        return Err(AppError::InvalidInput(format!("Drawer {} already exists", spec.id)));
+    }
+    let edge = spec.edge.parse().map_err(AppError::InvalidInput)?;
+    state.check_edge(&spec.id, edge)?;
+
+    open_drawer(&app, spec)?;
+    save_specs(&app).await
+}
+
+#[tauri::command]
+pub async fn remove_drawer(
+    app: AppHandle,
+    state: tauri::State<'_, DrawerManager>,
+    drawer_id: String,
+) -> Result<(), AppError> {
+    if drawer_id == MAIN_DRAWER_ID {
+        return Err(AppError::InvalidInput("The main drawer cannot be removed".into()));
+    }
+
+    state
+        .remove(&drawer_id)
+        .ok_or_else(|| AppError::DrawerNotFound { drawer_id: drawer_id.clone() })?;
+    if let Some(window) = app.get_webview_window(&drawer_id) {
+        window.close()?;
+    }
+    save_specs(&app).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WIDTH: f64 = 1440.0;
+    const HEIGHT: f64 = 900.0;
+
+    fn hit(edge: DrawerEdge, x: f64, y: f64) -> Option<u8> {
+        edge.hit_test(x, y, WIDTH, HEIGHT)
+    }
+
+    #[test]
+    fn side_edges_trigger_along_their_whole_height() {
+        for y in [0.0, 450.0, 899.0] {
+            assert_eq!(hit(DrawerEdge::Left, 0.0, y), Some(SIDE_LEFT));
+            assert_eq!(hit(DrawerEdge::Right, 1439.0, y), Some(SIDE_RIGHT));
+        }
+        assert_eq!(hit(DrawerEdge::Left, 1439.0, 450.0), None);
+        assert_eq!(hit(DrawerEdge::Right, 0.0, 450.0), None);
+    }
+
+    #[test]
+    fn corners_trigger_only_near_their_corner() {
+        assert_eq!(hit(DrawerEdge::TopLeft, 0.0, 10.0), Some(SIDE_LEFT));
+        assert_eq!(hit(DrawerEdge::TopLeft, 0.0, 450.0), None);
+        assert_eq!(hit(DrawerEdge::BottomLeft, 0.0, 890.0), Some(SIDE_LEFT));
+        assert_eq!(hit(DrawerEdge::BottomLeft, 0.0, 10.0), None);
+        assert_eq!(hit(DrawerEdge::TopRight, 1439.0, 10.0), Some(SIDE_RIGHT));
+        assert_eq!(hit(DrawerEdge::TopRight, 0.0, 10.0), None);
+        assert_eq!(hit(DrawerEdge::BottomRight, 1439.0, 890.0), Some(SIDE_RIGHT));
+        assert_eq!(hit(DrawerEdge::BottomRight, 1439.0, 450.0), None);
+    }
+
+    #[test]
+    fn hot_corners_trigger_in_all_four_corners() {
+        assert_eq!(hit(DrawerEdge::HotCorners, 0.0, 10.0), Some(SIDE_LEFT));
+        assert_eq!(hit(DrawerEdge::HotCorners, 0.0, 890.0), Some(SIDE_LEFT));
+        assert_eq!(hit(DrawerEdge::HotCorners, 1439.0, 10.0), Some(SIDE_RIGHT));
+        assert_eq!(hit(DrawerEdge::HotCorners, 1439.0, 890.0), Some(SIDE_RIGHT));
+        assert_eq!(hit(DrawerEdge::HotCorners, 0.0, 450.0), None);
+    }
+
+    #[test]
+    fn points_away_from_the_edges_trigger_nothing() {
+        let edges = [
+            DrawerEdge::Left,
+            DrawerEdge::Right,
+            DrawerEdge::HotCorners,
+            DrawerEdge::TopLeft,
+            DrawerEdge::BottomLeft,
+            DrawerEdge::TopRight,
+            DrawerEdge::BottomRight,
+        ];
+        for edge in edges {
+            assert_eq!(hit(edge, 720.0, 450.0), None);
+            assert_eq!(hit(edge, 720.0, 0.0), None);
+            // Just past the threshold
+            assert_eq!(hit(edge, EDGE_THRESHOLD, 10.0), None);
+            assert_eq!(hit(edge, WIDTH - EDGE_THRESHOLD, 10.0), None);
+        }
+    }
+
+    #[test]
+    fn edges_overlap_when_a_point_triggers_both() {
+        assert!(DrawerEdge::Left.overlaps(DrawerEdge::TopLeft));
+        assert!(DrawerEdge::HotCorners.overlaps(DrawerEdge::BottomRight));
+        assert!(DrawerEdge::Right.overlaps(DrawerEdge::Right));
+        assert!(!DrawerEdge::Left.overlaps(DrawerEdge::Right));
+        assert!(!DrawerEdge::TopLeft.overlaps(DrawerEdge::BottomLeft));
+        assert!(!DrawerEdge::Left.overlaps(DrawerEdge::TopRight));
+    }
+
+    #[test]
+    fn edges_round_trip_through_their_config_names() {
+        let edges = [
+            DrawerEdge::Left,
+            DrawerEdge::Right,
+            DrawerEdge::HotCorners,
+            DrawerEdge::TopLeft,
+            DrawerEdge::BottomLeft,
+            DrawerEdge::TopRight,
+            DrawerEdge::BottomRight,
+        ];
+        for edge in edges {
+            assert_eq!(edge.as_config().parse(), Ok(edge));
+        }
+        assert!("middle".parse::<DrawerEdge>().is_err());
+        assert_eq!(DrawerEdge::from_config("middle"), DrawerEdge::Left);
+    }
+}
@@ -0,0 +1,188 @@
+//! The error every command returns. The frontend receives it as
+//! `{ code, message, details }`: `code` is stable and meant to be matched on,
+//! `message` is for display and `details` carries the code-specific fields.
+
+use crate::window_backend::WindowError;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+use serde_json::{json, Value};
+use std::sync::PoisonError;
+
+#[derive(Debug, thiserror::Error)]
+pub enum AppError {
+    #[error(transparent)]
+    Window(#[from] WindowError),
+    #[error("Drawer {drawer_id} not found")]
+    DrawerNotFound { drawer_id: String },
+    /// Another drawer already opens from part of the same screen edge
+    #[error("Drawer {drawer_id} already opens from that edge")]
+    DrawerEdgeTaken { drawer_id: String },
+    #[error("Layout preset {preset_id} not found")]
+    PresetNotFound { preset_id: String },
+    #[error("Workspace {workspace_id} not found")]
+    WorkspaceNotFound { workspace_id: String },
+    /// A tiling command was used while auto-tiling is off
+    #[error("Tiling is off")]
+    TilingOff,
+    #[error("Tab {tab_id} not found")]
+    TabNotFound { tab_id: String },
+    /// The native view hosting the web blanket's tabs hasn't been created
+    #[error("The web blanket is not ready yet")]
+    WebBlanketUnavailable,
+    #[error("Invalid URL {url}: {reason}")]
+    InvalidUrl { url: String, reason: String },
+    #[error("Invalid match pattern {pattern}: {reason}")]
+    InvalidMatchPattern { pattern: String, reason: String },
+    /// The page's call was already answered, or its tab closed
+    #[error("Bridge request {request_id} not found")]
+    BridgeRequestNotFound { request_id: u64 },
+    /// An argument the command can't use, e.g. an empty name
+    #[error("{0}")]
+    InvalidInput(String),
+    #[error("Could not launch {path}: {reason}")]
+    Launch { path: String, reason: String },
+    #[error("Could not bind {shortcut}: {reason}")]
+    Shortcut { shortcut: String, reason: String },
+    #[error("Webview error: {0}")]
+    Webview(String),
+    #[error("Clipboard unavailable: {0}")]
+    Clipboard(String),
+    #[error("Could not fetch {url}: {reason}")]
+    Fetch { url: String, reason: String },
+    #[error("{feature} is not supported on this OS")]
+    Unsupported { feature: &'static str },
+    #[error("{0}")]
+    Other(String),
+}
+
+impl AppError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            AppError::Window(error) => error.kind(),
+            AppError::DrawerNotFound { .. } => "drawerNotFound",
+            AppError::DrawerEdgeTaken { .. } => "drawerEdgeTaken",
+            AppError::PresetNotFound { .. } => "presetNotFound",
+            AppError::WorkspaceNotFound { .. } => "workspaceNotFound",
+            AppError::TilingOff => "tilingOff",
+            AppError::TabNotFound { .. } => "tabNotFound",
+            AppError::WebBlanketUnavailable => "webBlanketUnavailable",
+            AppError::InvalidUrl { .. } => "invalidUrl",
+            AppError::InvalidMatchPattern { .. } => "invalidMatchPattern",
+            AppError::BridgeRequestNotFound { .. } => "bridgeRequestNotFound",
+            AppError::InvalidInput(_) => "invalidInput",
+            AppError::Launch { .. } => "launch",
+            AppError::Shortcut { .. } => "shortcut",
+            AppError::Webview(_) => "webview",
+            AppError::Clipboard(_) => "clipboard",
+            AppError::Fetch { .. } => "fetch",
+            AppError::Unsupported { .. } => "unsupported",
+            AppError::Other(_) => "other",
+        }
+    }
+
+    fn details(&self) -> Value {
+        match self {
+            AppError::Window(error) => {
+                let mut fields = serde_json::to_value(error).unwrap_or_default();
+                if let Some(fields) = fields.as_object_mut() {
+                    fields.remove("kind");
+                }
+                fields
+            }
+            AppError::DrawerNotFound { drawer_id } | AppError::DrawerEdgeTaken { drawer_id } => {
+                json!({ "drawerId": drawer_id })
+            }
+            AppError::PresetNotFound { preset_id } => json!({ "presetId": preset_id }),
+            AppError::WorkspaceNotFound { workspace_id } => json!({ "workspaceId": workspace_id }),
+            AppError::TabNotFound { tab_id } => json!({ "tabId": tab_id }),
+            AppError::InvalidUrl { url, .. } | AppError::Fetch { url, .. } => json!({ "url": url }),
+            AppError::InvalidMatchPattern { pattern, .. } => json!({ "pattern": pattern }),
+            AppError::Launch { path, .. } => json!({ "path": path }),
+            AppError::Shortcut { shortcut, .. } => json!({ "shortcut": shortcut }),
+            AppError::BridgeRequestNotFound { request_id } => json!({ "requestId": request_id }),
+            AppError::Unsupported { feature } => json!({ "feature": feature }),
+            _ => json!({}),
+        }
+    }
+}
+
+impl Serialize for AppError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut error = serializer.serialize_struct("AppError", 3)?;
+        error.serialize_field("code", self.code())?;
+        error.serialize_field("message", &self.to_string())?;
+        error.serialize_field("details", &self.details())?;
+        error.end()
+    }
+}
+
+impl From<String> for AppError {
+    fn from(message: String) -> Self {
+        AppError::Other(message)
+    }
+}
+
+impl From<&str> for AppError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+impl<T> From<PoisonError<T>> for AppError {
+    fn from(error: PoisonError<T>) -> Self {
+        AppError::Other(error.to_string())
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(error: tauri::Error) -> Self {
+        AppError::Webview(error.to_string())
+    }
+}
+
+impl From<AppError> for String {
+    fn from(error: AppError) -> Self {
+        error.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_serialize_with_code_message_and_details() {
+        let json = |e: AppError| serde_json::to_value(e).unwrap();
+
+        assert_eq!(
+            json(AppError::TabNotFound { tab_id: "t1".into() }),
+            json!({ "code": "tabNotFound", "message": "Tab t1 not found", "details": { "tabId": "t1" } })
+        );
+        assert_eq!(
+            json(AppError::Unsupported { feature: "App icons" }),
+            json!({
+                "code": "unsupported",
+                "message": "App icons is not supported on this OS",
+                "details": { "feature": "App icons" }
+            })
+        );
+        assert_eq!(
+            json(AppError::DrawerNotFound { drawer_id: "right".into() }),
+            json!({ "code": "drawerNotFound", "message": "Drawer right not found", "details": { "drawerId": "right" } })
+        );
+        assert_eq!(json(AppError::from("boom"))["code"], "other");
+    }
+
+    #[test]
+    fn window_errors_keep_their_kind_as_code() {
+        let json = serde_json::to_value(AppError::from(WindowError::AppNotScriptable { app_name: "Finder".into() })).unwrap();
+
+        assert_eq!(
+            json,
+            json!({
+                "code": "appNotScriptable",
+                "message": "Finder doesn't allow its windows to be moved",
+                "details": { "appName": "Finder" }
+            })
+        );
+    }
+}
@@ -0,0 +1,63 @@
+use crate::layout_manager::WindowInfo;
+use std::process::Command;
+
+/// Opens an application bundle, executable or document at `path`.
+pub fn launch_path(path: &str) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    {
+        Command::new("open").arg(path).spawn().map_err(|e| e.to_string())?;
+        Ok(())
+    }
+    #[cfg(target_os = "linux")]
+    {
+        let is_executable = {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::metadata(path)
+                .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+                .unwrap_or(false)
+        };
+        if is_executable {
+            Command::new(path).spawn().map_err(|e| e.to_string())?;
+        } else {
+            Command::new("xdg-open").arg(path).spawn().map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    {
+        let _ = path;
+        Err("Not supported".to_string())
+    }
+}
+
+/// Starts the app that owned a saved window: by its recorded path when there is
+/// one, otherwise by name.
+pub fn launch_window_app(window: &WindowInfo) -> Result<(), String> {
+    if let Some(path) = window.app_path.as_deref().filter(|p| !p.is_empty()) {
+        return launch_path(path);
+    }
+    if window.app_name.is_empty() {
+        return Err("No app to launch".into());
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        Command::new("open")
+            .args(["-a", &window.app_name])
+            .spawn()
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+    #[cfg(target_os = "linux")]
+    {
+        // WM_CLASS usually matches the binary name, modulo case ("Code" -> "code")
+        Command::new(window.app_name.to_lowercase())
+            .spawn()
+            .map_err(|e| format!("Could not start {}: {}", window.app_name, e))?;
+        Ok(())
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    {
+        Err("Not supported".to_string())
+    }
+}
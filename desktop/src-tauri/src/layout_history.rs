@@ -0,0 +1,272 @@
+//! Undo/redo for layout operations.
+//!
+//! An operation runs against a `Recorder`, which passes every call through to
+//! the real backend and notes the frame each window had before it was moved.
+//! The operations themselves don't know they are being recorded.
+
+use crate::layout_manager::{WindowInfo, WindowRect};
+use crate::window_backend::{DisplayInfo, WindowBackend, WindowError};
+use std::sync::Mutex;
+
+// Operations kept for undo
+const HISTORY_LIMIT: usize = 50;
+
+/// One window moved by an operation. `window.frame` is where it is now.
+#[derive(Debug, Clone)]
+struct FrameChange {
+    window: WindowInfo,
+    before: WindowRect,
+    after: WindowRect,
+}
+
+/// A backend that records the frames it changes.
+struct Recorder<'a> {
+    backend: &'a dyn WindowBackend,
+    changes: Mutex<Vec<FrameChange>>,
+}
+
+impl WindowBackend for Recorder<'_> {
+    fn list_windows(&self) -> Vec<WindowInfo> {
+        self.backend.list_windows()
+    }
+
+    fn list_all_windows(&self) -> Vec<WindowInfo> {
+        self.backend.list_all_windows()
+    }
+
+    fn focused_window(&self) -> Option<WindowInfo> {
+        self.backend.focused_window()
+    }
+
+    fn set_frame(&self, window: &WindowInfo, frame: &WindowRect) -> Result<(), WindowError> {
+        self.backend.set_frame(window, frame)?;
+
+        let mut changes = self.changes.lock().unwrap();
+        // A window moved twice keeps its first "before"
+        match changes.iter_mut().find(|c| c.window.id == window.id) {
+            Some(change) => change.after = frame.clone(),
+            None => changes.push(FrameChange {
+                window: window.clone(),
+                before: window.frame.clone(),
+                after: frame.clone(),
+            }),
+        }
+        Ok(())
+    }
+
+    fn focus(&self, window: &WindowInfo) -> Result<(), WindowError> {
+        self.backend.focus(window)
+    }
+
+    fn minimize(&self, window: &WindowInfo) -> Result<(), WindowError> {
+        self.backend.minimize(window)
+    }
+
+    fn displays(&self) -> Vec<DisplayInfo> {
+        self.backend.displays()
+    }
+
+    fn app_path(&self, pid: i32) -> Option<String> {
+        self.backend.app_path(pid)
+    }
+}
+
+#[derive(Default)]
+pub struct LayoutHistory {
+    undo: Mutex<Vec<Vec<FrameChange>>>,
+    redo: Mutex<Vec<Vec<FrameChange>>>,
+}
+
+impl LayoutHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Runs `operation` against `backend`, remembering the frames it changes as
+    /// one undo step. A new step clears the redo stack.
+    pub fn record<T>(&self, backend: &dyn WindowBackend, operation: impl FnOnce(&dyn WindowBackend) -> T) -> T {
+        let recorder = Recorder {
+            backend,
+            changes: Mutex::new(Vec::new()),
+        };
+        let result = operation(&recorder);
+
+        let mut changes = recorder.changes.into_inner().unwrap();
+        changes.retain(|c| c.before != c.after);
+        if !changes.is_empty() {
+            for change in &mut changes {
+                change.window.frame = change.after.clone();
+            }
+            let mut undo = self.undo.lock().unwrap();
+            if undo.len() == HISTORY_LIMIT {
+                undo.remove(0);
+            }
+            undo.push(changes);
+            self.redo.lock().unwrap().clear();
+        }
+        result
+    }
+
+    /// Puts the windows of the last operation back. False if there was nothing
+    /// to undo. Windows closed since are skipped; any other failure leaves the
+    /// step on the undo stack so it can be retried.
+    pub fn undo(&self, backend: &dyn WindowBackend) -> Result<bool, WindowError> {
+        let Some(mut changes) = self.undo.lock().unwrap().pop() else {
+            return Ok(false);
+        };
+        if let Err(e) = apply(backend, &mut changes, |c| &c.before) {
+            self.undo.lock().unwrap().push(changes);
+            return Err(e);
+        }
+        self.redo.lock().unwrap().push(changes);
+        Ok(true)
+    }
+
+    /// Re-applies the last undone operation. False if there was nothing to redo.
+    pub fn redo(&self, backend: &dyn WindowBackend) -> Result<bool, WindowError> {
+        let Some(mut changes) = self.redo.lock().unwrap().pop() else {
+            return Ok(false);
+        };
+        if let Err(e) = apply(backend, &mut changes, |c| &c.after) {
+            self.redo.lock().unwrap().push(changes);
+            return Err(e);
+        }
+        self.undo.lock().unwrap().push(changes);
+        Ok(true)
+    }
+}
+
+/// Moves every window to `target(change)` and notes that as its frame. Stops at
+/// the first failure other than a window that has been closed.
+fn apply(
+    backend: &dyn WindowBackend,
+    changes: &mut [FrameChange],
+    target: impl Fn(&FrameChange) -> &WindowRect,
+) -> Result<(), WindowError> {
+    for change in changes {
+        let frame = target(change).clone();
+        match backend.set_frame(&change.window, &frame) {
+            Ok(()) => change.window.frame = frame,
+            Err(WindowError::WindowNotFound { .. }) => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_backend::fake::{display, rect, window, FakeBackend};
+
+    fn backend() -> FakeBackend {
+        FakeBackend::new(
+            vec![
+                window(10, 100, "Safari", "Home", rect(0.0, 0.0, 500.0, 500.0)),
+                window(11, 200, "Notes", "Todo", rect(500.0, 0.0, 500.0, 500.0)),
+            ],
+            vec![display(1, 0.0, 0.0)],
+        )
+    }
+
+    fn frames(fake: &FakeBackend) -> Vec<WindowRect> {
+        fake.windows.lock().unwrap().iter().map(|w| w.frame.clone()).collect()
+    }
+
+    fn move_all(backend: &dyn WindowBackend, x: f64) {
+        for w in backend.list_windows() {
+            backend.set_frame(&w, &rect(x, 0.0, 300.0, 300.0)).unwrap();
+        }
+    }
+
+    #[test]
+    fn undo_and_redo_a_whole_operation() {
+        let fake = backend();
+        let history = LayoutHistory::new();
+        let start = frames(&fake);
+
+        history.record(&fake, |b| move_all(b, 100.0));
+        let moved = frames(&fake);
+        assert_ne!(moved, start);
+
+        assert!(history.undo(&fake).unwrap());
+        assert_eq!(frames(&fake), start);
+        assert!(!history.undo(&fake).unwrap());
+
+        assert!(history.redo(&fake).unwrap());
+        assert_eq!(frames(&fake), moved);
+        assert!(!history.redo(&fake).unwrap());
+    }
+
+    #[test]
+    fn a_window_moved_twice_returns_to_its_first_frame() {
+        let fake = backend();
+        let history = LayoutHistory::new();
+        let start = frames(&fake);
+
+        history.record(&fake, |b| {
+            move_all(b, 100.0);
+            move_all(b, 200.0);
+        });
+        history.undo(&fake).unwrap();
+        assert_eq!(frames(&fake), start);
+    }
+
+    #[test]
+    fn new_operations_clear_redo_and_no_ops_are_not_recorded() {
+        let fake = backend();
+        let history = LayoutHistory::new();
+
+        history.record(&fake, |b| move_all(b, 100.0));
+        history.undo(&fake).unwrap();
+        history.record(&fake, |b| move_all(b, 200.0));
+        assert!(!history.redo(&fake).unwrap());
+
+        // Moving windows to where they already are leaves nothing to undo
+        history.record(&fake, |b| move_all(b, 200.0));
+        history.undo(&fake).unwrap();
+        assert!(frames(&fake).iter().all(|f| f.x != 200.0));
+        assert!(!history.undo(&fake).unwrap());
+    }
+
+    #[test]
+    fn history_is_bounded() {
+        let fake = backend();
+        let history = LayoutHistory::new();
+        for i in 0..HISTORY_LIMIT + 3 {
+            history.record(&fake, |b| move_all(b, i as f64 + 1.0));
+        }
+
+        let undone = std::iter::from_fn(|| history.undo(&fake).unwrap().then_some(())).count();
+        assert_eq!(undone, HISTORY_LIMIT);
+        assert_eq!(frames(&fake)[0].x, 3.0);
+    }
+
+    #[test]
+    fn a_failed_undo_can_be_retried() {
+        let fake = backend();
+        let history = LayoutHistory::new();
+        let start = frames(&fake);
+
+        history.record(&fake, |b| move_all(b, 100.0));
+        *fake.refuse.lock().unwrap() = Some(WindowError::PermissionDenied);
+        assert_eq!(history.undo(&fake), Err(WindowError::PermissionDenied));
+        assert!(!history.redo(&fake).unwrap());
+
+        *fake.refuse.lock().unwrap() = None;
+        assert!(history.undo(&fake).unwrap());
+        assert_eq!(frames(&fake), start);
+    }
+
+    #[test]
+    fn closed_windows_are_skipped() {
+        let fake = backend();
+        let history = LayoutHistory::new();
+        let start = frames(&fake);
+
+        history.record(&fake, |b| move_all(b, 100.0));
+        fake.windows.lock().unwrap().remove(1);
+        assert!(history.undo(&fake).unwrap());
+        assert_eq!(frames(&fake), vec![start[0].clone()]);
+    }
+}
@@ -1,24 +1,18 @@
-use accessibility_sys::{
-    kAXErrorSuccess, AXUIElementCopyAttributeValue, AXUIElementCreateApplication,
-    AXUIElementSetAttributeValue,
-    AXUIElementRef, AXValueRef, AXValueType, AXValueCreate,
-};
-use core_foundation::base::{TCFType, FromVoid};
-use core_foundation::string::{CFString, CFStringRef};
-use core_foundation::array::{CFArray, CFArrayRef, CFArrayGetValueAtIndex};
-use core_foundation::dictionary::{CFDictionary, CFDictionaryRef};
-use core_foundation::number::{CFNumber, CFNumberRef};
-use core_graphics::display::{CGDisplay};
-use core_graphics::geometry::{CGPoint, CGSize};
-use core_graphics::window::{
-    CGWindowListCopyWindowInfo, kCGWindowListOptionOnScreenOnly,
-    kCGWindowListExcludeDesktopElements, kCGNullWindowID,
-};
+use crate::layout_presets::PresetKind;
+use crate::snap::{SnapSettings, SnapTarget};
+use crate::window_backend::{DisplayInfo, WindowBackend, WindowError};
+use crate::window_match::{self, MatchScore};
 use serde::{Deserialize, Serialize};
-use std::ffi::c_void;
-use std::ptr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+// System UI processes that own "normal" windows but must never be laid out
+const DEFAULT_IGNORED_APPS: [&str; 5] = ["Dock", "Window Server", "Control Center", "Screenshot", "Wallpaper"];
+
+// Anything smaller is a palette, popover or helper window
+pub(crate) const MIN_WINDOW_SIZE: f64 = 100.0;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct WindowRect {
     pub x: f64,
     pub y: f64,
@@ -26,6 +20,37 @@ pub struct WindowRect {
     pub height: f64,
 }
 
+impl WindowRect {
+    pub fn center(&self) -> (f64, f64) {
+        (self.x + self.width / 2.0, self.y + self.height / 2.0)
+    }
+
+    pub fn contains(&self, x: f64, y: f64) -> bool {
+        x >= self.x && x < self.x + self.width && y >= self.y && y < self.y + self.height
+    }
+
+    /// This rect as fractions of `area`.
+    pub fn relative_to(&self, area: &WindowRect) -> WindowRect {
+        let fraction = |v: f64, size: f64| if size > 0.0 { v / size } else { 0.0 };
+        WindowRect {
+            x: fraction(self.x - area.x, area.width),
+            y: fraction(self.y - area.y, area.height),
+            width: fraction(self.width, area.width),
+            height: fraction(self.height, area.height),
+        }
+    }
+
+    /// Inverse of `relative_to`: fractions of `area` back to a frame.
+    pub fn scaled_to(&self, area: &WindowRect) -> WindowRect {
+        WindowRect {
+            x: (area.x + self.x * area.width).round(),
+            y: (area.y + self.y * area.height).round(),
+            width: (self.width * area.width).round(),
+            height: (self.height * area.height).round(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct WindowInfo {
     pub id: u32,
@@ -33,440 +58,1108 @@ pub struct WindowInfo {
     pub title: String,
     pub app_name: String,
     pub frame: WindowRect,
+    /// Bundle or executable path, used to relaunch the app on restore
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app_path: Option<String>,
+    /// Display the window was on when captured
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_id: Option<u32>,
+    /// Frame as fractions of that display's work area, so a layout survives a
+    /// resolution or arrangement change
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relative_frame: Option<WindowRect>,
+    /// Bundle id on macOS, the WM_CLASS instance on X11
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bundle_id: Option<String>,
+    /// AX subrole on macOS ("AXStandardWindow", "AXDialog", ...), window type
+    /// on X11 ("normal", "dialog", ...). Only filled by `list_all_windows`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+    #[serde(default)]
+    pub state: WindowState,
 }
 
-pub fn get_open_windows(my_pid: i32) -> Vec<WindowInfo> {
-    let mut windows = Vec::new();
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct WindowState {
+    pub minimized: bool,
+    pub fullscreen: bool,
+    pub focused: bool,
+    /// False for minimized windows and windows on another Space or desktop
+    pub on_screen: bool,
+    /// CoreGraphics window level; 0 for normal windows
+    pub layer: i32,
+}
 
-    unsafe {
-        let options =
-            kCGWindowListOptionOnScreenOnly | kCGWindowListExcludeDesktopElements;
-        let window_list_ref = CGWindowListCopyWindowInfo(options, kCGNullWindowID);
+/// Which windows `query_windows` returns. The default matches what layouts
+/// work with: normal, visible windows of other apps.
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default, rename_all = "camelCase")]
+pub struct WindowQuery {
+    pub include_minimized: bool,
+    /// Windows on other Spaces or desktops
+    pub include_offscreen: bool,
+    /// Windows above or below the normal layer (panels, overlays)
+    pub include_all_layers: bool,
+    /// Only these apps, by name; empty for all
+    pub apps: Vec<String>,
+    pub bundle_ids: Vec<String>,
+    pub roles: Vec<String>,
+    /// Apps never returned, by name or bundle id. `None` for the configured
+    /// `IgnoredApps`
+    pub ignore_apps: Option<Vec<String>>,
+    pub min_size: f64,
+}
 
-        if window_list_ref.is_null() {
-            return windows;
+impl Default for WindowQuery {
+    fn default() -> Self {
+        Self {
+            include_minimized: false,
+            include_offscreen: false,
+            include_all_layers: false,
+            apps: Vec::new(),
+            bundle_ids: Vec::new(),
+            roles: Vec::new(),
+            ignore_apps: None,
+            min_size: MIN_WINDOW_SIZE,
         }
+    }
+}
 
-        let window_list: CFArray<*const c_void> = CFArray::wrap_under_create_rule(window_list_ref);
-        let count = window_list.len();
-
-        for i in 0..count {
-            let dict_ref =
-                CFArrayGetValueAtIndex(window_list.as_concrete_TypeRef(), i) as CFDictionaryRef;
-            let dict = CFDictionary::wrap_under_get_rule(dict_ref);
+impl WindowQuery {
+    /// Whether the backend's on-screen listing is enough to answer this query.
+    fn needs_all_windows(&self) -> bool {
+        self.include_minimized
+            || self.include_offscreen
+            || self.include_all_layers
+            || !self.bundle_ids.is_empty()
+            || !self.roles.is_empty()
+    }
 
-            let pid = get_number_from_dict(&dict, "kCGWindowOwnerPID").unwrap_or(0) as i32;
+    /// `ignored` is the configured list, used unless the query has its own.
+    pub fn matches(&self, window: &WindowInfo, ignored: &[String], my_pid: i32) -> bool {
+        let listed = |list: &[String], value: Option<&str>| {
+            list.is_empty() || value.is_some_and(|v| list.iter().any(|l| l == v))
+        };
+        let state = &window.state;
 
-            if pid == my_pid {
-                continue;
-            }
+        window.pid != my_pid
+            && !is_ignored(self.ignore_apps.as_deref().unwrap_or(ignored), window)
+            && window.frame.width >= self.min_size
+            && window.frame.height >= self.min_size
+            && (self.include_minimized || !state.minimized)
+            && (self.include_offscreen || state.on_screen || state.minimized)
+            && (self.include_all_layers || state.layer == 0)
+            && listed(&self.apps, Some(&window.app_name))
+            && listed(&self.bundle_ids, window.bundle_id.as_deref())
+            && listed(&self.roles, window.role.as_deref())
+    }
+}
 
-            let layer = get_number_from_dict(&dict, "kCGWindowLayer").unwrap_or(0);
-            if layer != 0 {
-                continue;
-            }
+fn is_ignored(apps: &[String], window: &WindowInfo) -> bool {
+    apps.iter()
+        .any(|a| *a == window.app_name || Some(a.as_str()) == window.bundle_id.as_deref())
+}
 
-            let app_name = get_string_from_dict(&dict, "kCGWindowOwnerName").unwrap_or_default();
-            let title = get_string_from_dict(&dict, "kCGWindowName").unwrap_or_default();
+fn is_manageable(window: &WindowInfo, ignored: &[String], my_pid: i32) -> bool {
+    window.pid != my_pid
+        && !is_ignored(ignored, window)
+        && window.frame.width >= MIN_WINDOW_SIZE
+        && window.frame.height >= MIN_WINDOW_SIZE
+}
 
-            if app_name == "Dock"
-                || app_name == "Window Server"
-                || app_name == "Control Center"
-                || app_name == "Screenshot"
-                || app_name == "Wallpaper"
-            {
-                continue;
-            }
+/// Apps no layout, snap, tiling or window action touches, by name or bundle id.
+pub struct IgnoredApps {
+    apps: Mutex<Vec<String>>,
+}
 
-            let id = get_number_from_dict(&dict, "kCGWindowNumber").unwrap_or(0) as u32;
-
-            let bounds_key = CFString::new("kCGWindowBounds");
-            let bounds_key_ptr = bounds_key.as_concrete_TypeRef() as *const c_void;
-            
-            if let Some(bounds_ptr_ref) = dict.find(bounds_key_ptr) {
-                let bounds_dict_ref = *bounds_ptr_ref as CFDictionaryRef;
-                let bounds_dict = CFDictionary::wrap_under_get_rule(bounds_dict_ref);
-                
-                let x = get_number_from_dict_float(&bounds_dict, "X").unwrap_or(0.0);
-                let y = get_number_from_dict_float(&bounds_dict, "Y").unwrap_or(0.0);
-                let width = get_number_from_dict_float(&bounds_dict, "Width").unwrap_or(0.0);
-                let height = get_number_from_dict_float(&bounds_dict, "Height").unwrap_or(0.0);
-
-                if width < 100.0 || height < 100.0 {
-                    continue;
-                }
-
-                windows.push(WindowInfo {
-                    id,
-                    pid,
-                    app_name,
-                    title,
-                    frame: WindowRect {
-                        x,
-                        y,
-                        width,
-                        height,
-                    },
-                });
-            }
+impl Default for IgnoredApps {
+    fn default() -> Self {
+        Self {
+            apps: Mutex::new(DEFAULT_IGNORED_APPS.iter().map(|a| a.to_string()).collect()),
         }
     }
+}
+
+impl IgnoredApps {
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-    windows
+    pub fn get(&self) -> Vec<String> {
+        self.apps.lock().unwrap().clone()
+    }
+
+    pub fn set(&self, apps: Vec<String>) {
+        *self.apps.lock().unwrap() = apps;
+    }
 }
 
-pub fn restore_windows(windows: Vec<WindowInfo>, my_pid: i32) {
-    let current_windows = get_open_windows(my_pid);
-
-    for saved_win in windows {
-        let mut target_pid = saved_win.pid;
-        let mut found = false;
-
-        if let Some(curr) = current_windows.iter().find(|w| w.id == saved_win.id) {
-            target_pid = curr.pid;
-            found = true;
-        } else if let Some(curr) = current_windows
-            .iter()
-            .find(|w| w.app_name == saved_win.app_name && w.title == saved_win.title)
-        {
-            target_pid = curr.pid;
-            found = true;
-        } else if let Some(curr) = current_windows
-            .iter()
-            .find(|w| w.app_name == saved_win.app_name)
-        {
-            target_pid = curr.pid;
-            found = true;
-        }
+/// Remembers the external window that had focus before one of our drawers
+/// took it, so drawer actions can target what the user was working in.
+#[derive(Default)]
+pub struct FocusTracker {
+    last: Mutex<Option<WindowInfo>>,
+}
 
-        if found {
-            unsafe {
-                move_window(target_pid, saved_win.frame);
+impl FocusTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Stores the currently focused window unless it is one of ours.
+    pub fn record(&self, backend: &dyn WindowBackend, ignored: &[String], my_pid: i32) {
+        if let Some(window) = backend.focused_window() {
+            if is_manageable(&window, ignored, my_pid) {
+                *self.last.lock().unwrap() = Some(window);
             }
         }
     }
-}
 
-pub fn snap_active_window(direction: String, my_pid: i32) {
-    let windows = get_open_windows(my_pid);
-    if windows.is_empty() {
-        return;
+    pub fn last(&self) -> Option<WindowInfo> {
+        self.last.lock().unwrap().clone()
     }
+}
 
-    let target = &windows[0];
+/// The windows actions may touch: visible, normal-sized, not ours and not of
+/// an `ignored` app.
+pub fn get_open_windows(backend: &dyn WindowBackend, ignored: &[String], my_pid: i32) -> Vec<WindowInfo> {
+    backend
+        .list_windows()
+        .into_iter()
+        .filter(|w| is_manageable(w, ignored, my_pid))
+        .collect()
+}
 
-    let center = CGPoint {
-        x: target.frame.x + target.frame.width / 2.0,
-        y: target.frame.y + target.frame.height / 2.0,
+/// Windows matching `query`, front-most first, with `state.focused` set.
+pub fn query_windows(backend: &dyn WindowBackend, query: &WindowQuery, ignored: &[String], my_pid: i32) -> Vec<WindowInfo> {
+    let windows = if query.needs_all_windows() {
+        backend.list_all_windows()
+    } else {
+        backend.list_windows()
     };
+    let focused = backend.focused_window().map(|w| w.id);
 
-    let display_id = unsafe {
-        let displays = CGDisplay::active_displays().unwrap_or(vec![CGDisplay::main().id]);
-        let mut best_display = displays[0];
+    windows
+        .into_iter()
+        .filter(|w| query.matches(w, ignored, my_pid))
+        .map(|mut w| {
+            w.state.focused = Some(w.id) == focused;
+            w
+        })
+        .collect()
+}
 
-        for d_id in displays {
-            let d = CGDisplay::new(d_id);
-            let b = d.bounds();
-            if b.contains(&center) {
-                best_display = d_id;
-                break;
-            }
+#[derive(Debug, Clone)]
+pub struct RestoreOptions {
+    /// Launch apps that have no windows open, then wait for their windows
+    pub launch_missing: bool,
+    /// How long to wait for launched apps' windows
+    pub timeout: Duration,
+    pub poll_interval: Duration,
+}
+
+impl Default for RestoreOptions {
+    fn default() -> Self {
+        Self {
+            launch_missing: false,
+            timeout: Duration::from_secs(15),
+            poll_interval: Duration::from_millis(500),
         }
-        best_display
-    };
+    }
+}
 
-    let display = CGDisplay::new(display_id);
-    let bounds = display.bounds();
-
-    let menu_bar_height = 24.0;
-    let working_y = bounds.origin.y + menu_bar_height;
-    let working_h = bounds.size.height - menu_bar_height;
-    let working_w = bounds.size.width;
-    let working_x = bounds.origin.x;
-
-    let new_frame = match direction.as_str() {
-        "left" => WindowRect {
-            x: working_x,
-            y: working_y,
-            width: working_w / 2.0,
-            height: working_h,
-        },
-        "right" => WindowRect {
-            x: working_x + working_w / 2.0,
-            y: working_y,
-            width: working_w / 2.0,
-            height: working_h,
-        },
-        "top" => WindowRect {
-            x: working_x,
-            y: working_y,
-            width: working_w,
-            height: working_h / 2.0,
-        },
-        "bottom" => WindowRect {
-            x: working_x,
-            y: working_y + working_h / 2.0,
-            width: working_w,
-            height: working_h / 2.0,
-        },
-        "maximize" => WindowRect {
-            x: working_x,
-            y: working_y,
-            width: working_w,
-            height: working_h,
-        },
-        "center" => WindowRect {
-            x: working_x + working_w * 0.1,
-            y: working_y + working_h * 0.1,
-            width: working_w * 0.8,
-            height: working_h * 0.8,
-        },
-        _ => target.frame.clone(),
-    };
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum RestoreStatus {
+    Placed,
+    LaunchedAndPlaced,
+    NotFound,
+    /// A matching window was found but could not be moved
+    PlacementFailed { error: WindowError },
+}
 
-    unsafe {
-        move_window(target.pid, new_frame);
-    }
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RestoreResult {
+    pub saved: WindowInfo,
+    /// The live window the saved frame was applied to
+    pub window_id: Option<u32>,
+    #[serde(flatten)]
+    pub status: RestoreStatus,
+    /// Why that window was picked
+    pub score: Option<MatchScore>,
 }
 
-pub fn apply_preset_layout(layout_type: String, my_pid: i32) {
-    let windows = get_open_windows(my_pid);
-    if windows.is_empty() { return; }
+/// Emitted as `layout-restore-progress` while a restore runs.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(tag = "phase", rename_all = "snake_case", rename_all_fields = "camelCase")]
+pub enum RestoreProgress {
+    Launching { app_name: String },
+    LaunchFailed { app_name: String, error: String },
+    Waiting { remaining: usize },
+    Placed { app_name: String, title: String, launched: bool },
+    PlacementFailed { app_name: String, title: String, error: String },
+    Finished { placed: usize, not_found: usize, failed: usize },
+}
 
-    let target = &windows[0];
-    let center = CGPoint {
-        x: target.frame.x + target.frame.width / 2.0,
-        y: target.frame.y + target.frame.height / 2.0,
-    };
+/// Places every pending result that now matches a live, unclaimed window.
+/// With `only_apps`, results for other apps are left alone.
+fn place_pending(
+    backend: &dyn WindowBackend,
+    results: &mut [RestoreResult],
+    claimed: &mut Vec<u32>,
+    only_apps: Option<&[String]>,
+    ignored: &[String],
+    my_pid: i32,
+    progress: &dyn Fn(RestoreProgress),
+) {
+    let live: Vec<WindowInfo> = get_open_windows(backend, ignored, my_pid)
+        .into_iter()
+        .filter(|w| !claimed.contains(&w.id))
+        .collect();
+    let pending: Vec<usize> = (0..results.len())
+        .filter(|i| results[*i].window_id.is_none())
+        .filter(|i| only_apps.is_none_or(|apps| apps.contains(&results[*i].saved.app_name)))
+        .collect();
+    let saved: Vec<WindowInfo> = pending.iter().map(|i| results[*i].saved.clone()).collect();
+    let launched = only_apps.is_some();
 
-    let display_id = unsafe {
-        let displays = CGDisplay::active_displays().unwrap_or(vec![CGDisplay::main().id]);
-        let mut best_display = displays[0];
-        
-        for d_id in displays {
-            let d = CGDisplay::new(d_id);
-            let b = d.bounds();
-            if b.contains(&center) {
-                best_display = d_id;
-                break;
-            }
-        }
-        best_display
-    };
+    let displays = backend.displays();
 
-    let display = CGDisplay::new(display_id);
-    let bounds = display.bounds();
-    
-    let menu_bar_height = 24.0; 
-    let working_y = bounds.origin.y + menu_bar_height;
-    let working_h = bounds.size.height - menu_bar_height; 
-    let working_w = bounds.size.width;
-    let working_x = bounds.origin.x;
-
-    match layout_type.as_str() {
-        "rows_2" => {
-            if windows.len() >= 2 {
-                unsafe {
-                    move_window(windows[0].pid, WindowRect { x: working_x, y: working_y, width: working_w, height: working_h / 2.0 });
-                    move_window(windows[1].pid, WindowRect { x: working_x, y: working_y + working_h / 2.0, width: working_w, height: working_h / 2.0 });
-                }
-            }
-        },
-        "rows_3" => {
-            if windows.len() >= 3 {
-                let h_third = working_h / 3.0;
-                unsafe {
-                    move_window(windows[0].pid, WindowRect { x: working_x, y: working_y, width: working_w, height: h_third });
-                    move_window(windows[1].pid, WindowRect { x: working_x, y: working_y + h_third, width: working_w, height: h_third });
-                    move_window(windows[2].pid, WindowRect { x: working_x, y: working_y + h_third * 2.0, width: working_w, height: h_third });
-                }
-            }
-        },
-        "columns_2" => {
-            if windows.len() >= 2 {
-                let w1 = &windows[0];
-                let w2 = &windows[1];
-                unsafe {
-                    move_window(w1.pid, WindowRect { x: working_x, y: working_y, width: working_w / 2.0, height: working_h });
-                    move_window(w2.pid, WindowRect { x: working_x + working_w / 2.0, y: working_y, width: working_w / 2.0, height: working_h });
-                }
+    for (k, assigned) in window_match::assign(&saved, &live, &displays).into_iter().enumerate() {
+        let Some((j, score)) = assigned else {
+            continue;
+        };
+        let window = &live[j];
+        let result = &mut results[pending[k]];
+        claimed.push(window.id);
+        result.window_id = Some(window.id);
+        result.score = Some(score);
+
+        match backend.set_frame(window, &saved_frame(&result.saved, &displays)) {
+            Ok(()) => {
+                result.status = if launched { RestoreStatus::LaunchedAndPlaced } else { RestoreStatus::Placed };
+                progress(RestoreProgress::Placed {
+                    app_name: window.app_name.clone(),
+                    title: window.title.clone(),
+                    launched,
+                });
             }
-        },
-        "columns_3" => {
-            if windows.len() >= 3 {
-                let w_third = working_w / 3.0;
-                unsafe {
-                    move_window(windows[0].pid, WindowRect { x: working_x, y: working_y, width: w_third, height: working_h });
-                    move_window(windows[1].pid, WindowRect { x: working_x + w_third, y: working_y, width: w_third, height: working_h });
-                    move_window(windows[2].pid, WindowRect { x: working_x + w_third * 2.0, y: working_y, width: w_third, height: working_h });
-                }
+            Err(error) => {
+                progress(RestoreProgress::PlacementFailed {
+                    app_name: window.app_name.clone(),
+                    title: window.title.clone(),
+                    error: error.to_string(),
+                });
+                result.status = RestoreStatus::PlacementFailed { error };
             }
-        },
-        "grid_4" => {
-            if windows.len() >= 4 {
-                let half_w = working_w / 2.0;
-                let half_h = working_h / 2.0;
-                unsafe {
-                    move_window(windows[0].pid, WindowRect { x: working_x, y: working_y, width: half_w, height: half_h }); // Top Left
-                    move_window(windows[1].pid, WindowRect { x: working_x + half_w, y: working_y, width: half_w, height: half_h }); // Top Right
-                    move_window(windows[2].pid, WindowRect { x: working_x, y: working_y + half_h, width: half_w, height: half_h }); // Bottom Left
-                    move_window(windows[3].pid, WindowRect { x: working_x + half_w, y: working_y + half_h, width: half_w, height: half_h }); // Bottom Right
-                }
+        }
+    }
+}
+
+/// Moves live windows back to their saved frames. With `launch_missing`, apps
+/// that have no window open are launched and their windows placed as they
+/// appear, until `timeout`.
+pub fn restore_windows(
+    backend: &dyn WindowBackend,
+    windows: Vec<WindowInfo>,
+    options: &RestoreOptions,
+    ignored: &[String],
+    my_pid: i32,
+    launch: &dyn Fn(&WindowInfo) -> Result<(), String>,
+    progress: &dyn Fn(RestoreProgress),
+) -> Vec<RestoreResult> {
+    let mut results: Vec<RestoreResult> = windows
+        .into_iter()
+        .map(|saved| RestoreResult {
+            saved,
+            window_id: None,
+            status: RestoreStatus::NotFound,
+            score: None,
+        })
+        .collect();
+    let mut claimed = Vec::new();
+
+    place_pending(backend, &mut results, &mut claimed, None, ignored, my_pid, progress);
+
+    if options.launch_missing {
+        let running: Vec<String> = get_open_windows(backend, ignored, my_pid)
+            .into_iter()
+            .map(|w| w.app_name)
+            .collect();
+
+        let mut launched: Vec<String> = Vec::new();
+        for result in results.iter().filter(|r| r.window_id.is_none()) {
+            let app_name = &result.saved.app_name;
+            if running.contains(app_name) || launched.contains(app_name) {
+                continue;
             }
-        },
-        "main_left" => {
-            if windows.len() >= 2 {
-                let main_w = working_w * 0.6;
-                let side_w = working_w * 0.4;
-                unsafe {
-                    move_window(windows[0].pid, WindowRect { x: working_x, y: working_y, width: main_w, height: working_h });
-                    
-                    let others = &windows[1..];
-                    let stack_count = others.len().min(3);
-                    if stack_count > 0 {
-                        let stack_h = working_h / stack_count as f64;
-                        for (i, w) in others.iter().take(stack_count).enumerate() {
-                            move_window(w.pid, WindowRect { 
-                                x: working_x + main_w, 
-                                y: working_y + (i as f64 * stack_h), 
-                                width: side_w, 
-                                height: stack_h 
-                            });
-                        }
-                    }
-                }
+            progress(RestoreProgress::Launching { app_name: app_name.clone() });
+            match launch(&result.saved) {
+                Ok(()) => launched.push(app_name.clone()),
+                Err(error) => progress(RestoreProgress::LaunchFailed {
+                    app_name: app_name.clone(),
+                    error,
+                }),
             }
-        },
-        "cascade" => {
-            let offset = 30.0;
-            let start_x = working_x + 50.0;
-            let start_y = working_y + 50.0;
-            let w_width = working_w * 0.6;
-            let w_height = working_h * 0.7;
-            
-            for (i, w) in windows.iter().take(10).enumerate() {
-                unsafe {
-                    move_window(w.pid, WindowRect {
-                        x: start_x + (i as f64 * offset),
-                        y: start_y + (i as f64 * offset),
-                        width: w_width,
-                        height: w_height
-                    });
-                }
+        }
+
+        let deadline = Instant::now() + options.timeout;
+        while !launched.is_empty() {
+            let remaining = results
+                .iter()
+                .filter(|r| r.window_id.is_none() && launched.contains(&r.saved.app_name))
+                .count();
+            if remaining == 0 || Instant::now() >= deadline {
+                break;
             }
-        },
-        _ => {}
-    }
-}
-
-unsafe fn move_window(pid: i32, frame: WindowRect) {
-    let app_ref = AXUIElementCreateApplication(pid);
-    if app_ref.is_null() {
-        return;
-    }
-
-    let mut windows_ref: *const c_void = ptr::null();
-    let windows_attr = CFString::new("AXWindows");
-
-    let result = AXUIElementCopyAttributeValue(
-        app_ref,
-        windows_attr.as_concrete_TypeRef(),
-        &mut windows_ref,
-    );
-
-    if result == kAXErrorSuccess && !windows_ref.is_null() {
-        let windows: CFArray<AXUIElementRef> =
-            CFArray::wrap_under_create_rule(windows_ref as CFArrayRef);
-        let count = windows.len();
-
-        for i in 0..count {
-            let window_ref =
-                CFArrayGetValueAtIndex(windows.as_concrete_TypeRef(), i) as AXUIElementRef;
-
-            let pos = CGPoint {
-                x: frame.x,
-                y: frame.y,
-            };
-            let size = CGSize {
-                width: frame.width,
-                height: frame.height,
-            };
-
-            let pos_val =
-                AXValueCreate(1, &pos as *const _ as *const c_void);
-            let size_val =
-                AXValueCreate(2, &size as *const _ as *const c_void);
-
-            let pos_attr = CFString::new("AXPosition");
-            let size_attr = CFString::new("AXSize");
-
-            AXUIElementSetAttributeValue(
-                window_ref,
-                pos_attr.as_concrete_TypeRef(),
-                pos_val as *const c_void,
-            );
-            AXUIElementSetAttributeValue(
-                window_ref,
-                size_attr.as_concrete_TypeRef(),
-                size_val as *const c_void,
-            );
-
-            break;
+            progress(RestoreProgress::Waiting { remaining });
+            std::thread::sleep(options.poll_interval);
+            place_pending(backend, &mut results, &mut claimed, Some(&launched), ignored, my_pid, progress);
         }
     }
+
+    let not_found = results.iter().filter(|r| r.window_id.is_none()).count();
+    let failed = results
+        .iter()
+        .filter(|r| matches!(r.status, RestoreStatus::PlacementFailed { .. }))
+        .count();
+    progress(RestoreProgress::Finished {
+        placed: results.len() - not_found - failed,
+        not_found,
+        failed,
+    });
+    results
 }
 
-unsafe fn get_number_from_dict(
-    dict: &CFDictionary<*const c_void, *const c_void>,
-    key: &str,
-) -> Option<i64> {
-    let key_cf = CFString::new(key);
-    let key_ptr = key_cf.as_concrete_TypeRef() as *const c_void;
-    
-    if let Some(val_ref) = dict.find(key_ptr) {
-        let val_ptr: *const c_void = *val_ref;
-        if val_ptr.is_null() { return None; }
-        let num = CFNumber::wrap_under_get_rule(val_ptr as CFNumberRef);
-        num.to_i64()
-    } else {
-        None
+/// The display containing the centre of `frame`, falling back to the main display.
+pub fn display_for<'a>(displays: &'a [DisplayInfo], frame: &WindowRect) -> Option<&'a DisplayInfo> {
+    let (cx, cy) = frame.center();
+    displays
+        .iter()
+        .find(|d| d.frame.contains(cx, cy))
+        .or_else(|| displays.iter().find(|d| d.is_main))
+        .or_else(|| displays.first())
+}
+
+/// Displays ordered left to right, then top to bottom.
+pub fn displays_left_to_right(displays: &[DisplayInfo]) -> Vec<&DisplayInfo> {
+    let mut ordered: Vec<&DisplayInfo> = displays.iter().collect();
+    ordered.sort_by(|a, b| {
+        (a.frame.x, a.frame.y)
+            .partial_cmp(&(b.frame.x, b.frame.y))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+    ordered
+}
+
+/// Records which display each window is on and its frame relative to that
+/// display's work area.
+pub fn annotate_displays(windows: &mut [WindowInfo], displays: &[DisplayInfo]) {
+    for window in windows {
+        if let Some(display) = display_for(displays, &window.frame) {
+            window.display_id = Some(display.id);
+            window.relative_frame = Some(window.frame.relative_to(&display.work_area));
+        }
     }
 }
 
-unsafe fn get_number_from_dict_float(
-    dict: &CFDictionary<*const c_void, *const c_void>,
-    key: &str,
-) -> Option<f64> {
-    let key_cf = CFString::new(key);
-    let key_ptr = key_cf.as_concrete_TypeRef() as *const c_void;
+/// The manageable windows with everything a later restore needs: the app's
+/// path for relaunching and the display each window is on.
+pub fn capture_windows(backend: &dyn WindowBackend, ignored: &[String], my_pid: i32) -> Vec<WindowInfo> {
+    let mut windows = get_open_windows(backend, ignored, my_pid);
+    for window in &mut windows {
+        window.app_path = backend.app_path(window.pid);
+    }
+    annotate_displays(&mut windows, &backend.displays());
+    windows
+}
+
+/// Where a saved window should go on the current displays. Frames are rebuilt
+/// from the relative frame on the same display, or on the main display when
+/// that one is gone; layouts captured without display information keep their
+/// absolute frame.
+pub fn saved_frame(saved: &WindowInfo, displays: &[DisplayInfo]) -> WindowRect {
+    let Some(relative) = &saved.relative_frame else {
+        return saved.frame.clone();
+    };
+    let display = displays
+        .iter()
+        .find(|d| Some(d.id) == saved.display_id)
+        .or_else(|| displays.iter().find(|d| d.is_main))
+        .or_else(|| displays.first());
+    match display {
+        Some(display) => relative.scaled_to(&display.work_area),
+        None => saved.frame.clone(),
+    }
+}
+
+/// The window layout actions apply to: the focused window, or the one that was
+/// focused before our drawer opened, or failing both the front-most window.
+pub fn target_window(
+    backend: &dyn WindowBackend,
+    windows: &[WindowInfo],
+    remembered: Option<&WindowInfo>,
+    ignored: &[String],
+    my_pid: i32,
+) -> Option<WindowInfo> {
+    let focused = backend.focused_window().filter(|w| is_manageable(w, ignored, my_pid));
+
+    focused
+        .iter()
+        .chain(remembered)
+        .find_map(|wanted| windows.iter().find(|w| w.id == wanted.id))
+        .or_else(|| windows.first())
+        .cloned()
+}
+
+pub fn snap_active_window(
+    backend: &dyn WindowBackend,
+    target: SnapTarget,
+    settings: &SnapSettings,
+    remembered: Option<&WindowInfo>,
+    ignored: &[String],
+    my_pid: i32,
+) -> Result<(), WindowError> {
+    let windows = get_open_windows(backend, ignored, my_pid);
+    let window = target_window(backend, &windows, remembered, ignored, my_pid).ok_or(WindowError::WindowNotFound { id: None })?;
+
+    let displays = backend.displays();
+    let display = display_for(&displays, &window.frame).ok_or("No display found")?;
+
+    let new_frame = target.next_frame(&window.frame, &display.work_area, settings);
+    backend.set_frame(&window, &new_frame)
+}
+
+pub fn apply_preset_layout(
+    backend: &dyn WindowBackend,
+    preset: &PresetKind,
+    settings: &SnapSettings,
+    remembered: Option<&WindowInfo>,
+    ignored: &[String],
+    my_pid: i32,
+) -> Result<(), WindowError> {
+    let mut windows = get_open_windows(backend, ignored, my_pid);
+    let target = target_window(backend, &windows, remembered, ignored, my_pid).ok_or(WindowError::WindowNotFound { id: None })?;
+
+    // The target takes the first (main) slot, the rest keep their stacking order
+    windows.retain(|w| w.id != target.id);
+    windows.insert(0, target);
 
-    if let Some(val_ref) = dict.find(key_ptr) {
-        let val_ptr: *const c_void = *val_ref;
-        if val_ptr.is_null() {
-            return None;
+    let displays = backend.displays();
+    let display = display_for(&displays, &windows[0].frame).ok_or("No display found")?;
+
+    // One window refusing to move doesn't stop the others; the first error is reported
+    let frames = preset.frames(&display.work_area, settings, windows.len());
+    let mut result = Ok(());
+    for (window, frame) in windows.iter().zip(frames.iter()) {
+        if let Err(e) = backend.set_frame(window, frame) {
+            result = result.and(Err(e));
         }
-        let num = CFNumber::wrap_under_get_rule(val_ptr as CFNumberRef);
-        num.to_f64()
-    } else {
-        None
     }
+    result
 }
 
-unsafe fn get_string_from_dict(
-    dict: &CFDictionary<*const c_void, *const c_void>,
-    key: &str,
-) -> Option<String> {
-    let key_cf = CFString::new(key);
-    let key_ptr = key_cf.as_concrete_TypeRef() as *const c_void;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_backend::fake::{display, rect, window, FakeBackend};
+
+    const MY_PID: i32 = 1;
+
+    fn ignored() -> Vec<String> {
+        IgnoredApps::new().get()
+    }
+
+    fn restore(fake: &FakeBackend, saved: Vec<WindowInfo>, options: &RestoreOptions) -> Vec<RestoreResult> {
+        restore_windows(fake, saved, options, &ignored(), MY_PID, &|_| Err("no launcher".into()), &|_| {})
+    }
 
-    if let Some(val_ref) = dict.find(key_ptr) {
-        let val_ptr: *const c_void = *val_ref;
-        if val_ptr.is_null() {
-            return None;
+    fn launching(timeout_ms: u64) -> RestoreOptions {
+        RestoreOptions {
+            launch_missing: true,
+            timeout: Duration::from_millis(timeout_ms),
+            poll_interval: Duration::from_millis(1),
         }
-        let str_ref = CFString::wrap_under_get_rule(val_ptr as CFStringRef);
-        Some(str_ref.to_string())
-    } else {
-        None
     }
-}
\ No newline at end of file
+
+    fn preset(id: &str) -> PresetKind {
+        crate::layout_presets::builtin_preset(id).unwrap().layout
+    }
+
+    fn backend(windows: Vec<WindowInfo>) -> FakeBackend {
+        FakeBackend::new(windows, vec![display(1, 0.0, 0.0), display(2, 1000.0, 0.0)])
+    }
+
+    #[test]
+    fn open_windows_skip_own_process_system_apps_and_tiny_windows() {
+        let fake = backend(vec![
+            window(10, 100, "Safari", "Home", rect(0.0, 0.0, 500.0, 500.0)),
+            window(11, MY_PID, "My Drawer", "", rect(0.0, 0.0, 400.0, 800.0)),
+            window(12, 200, "Dock", "", rect(0.0, 700.0, 1000.0, 100.0)),
+            window(13, 300, "Terminal", "zsh", rect(0.0, 0.0, 80.0, 500.0)),
+            window(14, 400, "Notes", "Todo", rect(10.0, 10.0, 300.0, 300.0)),
+        ]);
+
+        let ids: Vec<u32> = get_open_windows(&fake, &ignored(), MY_PID).iter().map(|w| w.id).collect();
+        assert_eq!(ids, vec![10, 14]);
+    }
+
+    #[test]
+    fn configured_ignore_list_applies_to_open_windows() {
+        let mut stickies = window(10, 100, "Stickies", "", rect(0.0, 0.0, 500.0, 500.0));
+        stickies.bundle_id = Some("com.apple.Stickies".into());
+        let fake = backend(vec![stickies, window(11, 200, "Notes", "", rect(0.0, 0.0, 500.0, 500.0))]);
+
+        let ignored = IgnoredApps::new();
+        ignored.set([ignored.get(), vec!["com.apple.Stickies".into()]].concat());
+        let open: Vec<u32> = get_open_windows(&fake, &ignored.get(), MY_PID).iter().map(|w| w.id).collect();
+        assert_eq!(open, vec![11]);
+        let queried = query_windows(&fake, &WindowQuery::default(), &ignored.get(), MY_PID);
+        assert_eq!(queried.len(), 1);
+        assert_eq!(queried[0].id, 11);
+    }
+
+    fn query_backend() -> FakeBackend {
+        let mut minimized = window(11, 200, "Notes", "Todo", rect(0.0, 0.0, 500.0, 500.0));
+        minimized.state = WindowState {
+            minimized: true,
+            ..Default::default()
+        };
+        let mut other_space = window(12, 100, "Safari", "Docs", rect(0.0, 0.0, 500.0, 500.0));
+        other_space.state.on_screen = false;
+        let mut panel = window(13, 300, "Music", "Mini Player", rect(0.0, 0.0, 300.0, 300.0));
+        panel.state.layer = 3;
+        panel.role = Some("utility".into());
+        let mut terminal = window(14, 400, "Terminal", "zsh", rect(0.0, 0.0, 500.0, 500.0));
+        terminal.bundle_id = Some("com.apple.Terminal".into());
+        terminal.role = Some("normal".into());
+
+        let mut safari = window(10, 100, "Safari", "Home", rect(0.0, 0.0, 500.0, 500.0));
+        safari.bundle_id = Some("com.apple.Safari".into());
+        safari.role = Some("normal".into());
+        backend(vec![safari, minimized, other_space, panel, terminal])
+    }
+
+    fn query_ids(fake: &FakeBackend, query: WindowQuery) -> Vec<u32> {
+        query_windows(fake, &query, &ignored(), MY_PID).iter().map(|w| w.id).collect()
+    }
+
+    #[test]
+    fn query_defaults_to_visible_normal_windows() {
+        let fake = query_backend();
+        let windows = query_windows(&fake, &WindowQuery::default(), &ignored(), MY_PID);
+
+        let ids: Vec<u32> = windows.iter().map(|w| w.id).collect();
+        assert_eq!(ids, vec![10, 14]);
+        assert!(windows[0].state.focused);
+        assert!(!windows[1].state.focused);
+    }
+
+    #[test]
+    fn query_includes_hidden_windows_when_asked() {
+        let fake = query_backend();
+        let minimized = WindowQuery {
+            include_minimized: true,
+            ..Default::default()
+        };
+        assert_eq!(query_ids(&fake, minimized), vec![10, 11, 14]);
+
+        let everything = WindowQuery {
+            include_minimized: true,
+            include_offscreen: true,
+            include_all_layers: true,
+            ..Default::default()
+        };
+        let windows = query_windows(&fake, &everything, &ignored(), MY_PID);
+        assert_eq!(windows.iter().map(|w| w.id).collect::<Vec<_>>(), vec![10, 11, 12, 13, 14]);
+        assert!(windows[1].state.minimized);
+        assert_eq!(windows[3].state.layer, 3);
+    }
+
+    #[test]
+    fn query_filters_by_app_bundle_id_and_role() {
+        let fake = query_backend();
+        let apps = WindowQuery {
+            apps: vec!["Safari".into()],
+            include_offscreen: true,
+            ..Default::default()
+        };
+        assert_eq!(query_ids(&fake, apps), vec![10, 12]);
+
+        let bundle_ids = WindowQuery {
+            bundle_ids: vec!["com.apple.Terminal".into()],
+            ..Default::default()
+        };
+        assert_eq!(query_ids(&fake, bundle_ids), vec![14]);
+
+        let roles = WindowQuery {
+            roles: vec!["utility".into()],
+            include_all_layers: true,
+            ..Default::default()
+        };
+        assert_eq!(query_ids(&fake, roles), vec![13]);
+    }
+
+    #[test]
+    fn query_ignore_list_replaces_the_built_in_one() {
+        let fake = query_backend();
+        let ignore_terminal = WindowQuery {
+            ignore_apps: Some(vec!["com.apple.Terminal".into()]),
+            ..Default::default()
+        };
+        assert_eq!(query_ids(&fake, ignore_terminal), vec![10]);
+
+        let dock = backend(vec![window(20, 500, "Dock", "", rect(0.0, 0.0, 1000.0, 150.0))]);
+        assert!(query_ids(&dock, WindowQuery::default()).is_empty());
+        let nothing_ignored = WindowQuery {
+            ignore_apps: Some(Vec::new()),
+            ..Default::default()
+        };
+        assert_eq!(query_ids(&dock, nothing_ignored), vec![20]);
+    }
+
+    #[test]
+    fn display_for_uses_window_center() {
+        let displays = vec![display(1, 0.0, 0.0), display(2, 1000.0, 0.0)];
+        // Mostly on the second display
+        let frame = rect(900.0, 100.0, 400.0, 300.0);
+        assert_eq!(display_for(&displays, &frame).unwrap().id, 2);
+    }
+
+    #[test]
+    fn display_for_falls_back_to_main_display() {
+        let displays = vec![display(2, 1000.0, 0.0), display(1, 0.0, 0.0)];
+        let offscreen = rect(-5000.0, -5000.0, 400.0, 300.0);
+        assert_eq!(display_for(&displays, &offscreen).unwrap().id, 1);
+        assert!(display_for(&[], &offscreen).is_none());
+    }
+
+    #[test]
+    fn snap_moves_front_window_within_its_display_work_area() {
+        let fake = backend(vec![
+            window(10, 100, "Safari", "Home", rect(1100.0, 100.0, 500.0, 500.0)),
+            window(11, 200, "Notes", "Todo", rect(0.0, 0.0, 500.0, 500.0)),
+        ]);
+
+        snap_active_window(&fake, SnapTarget::LeftHalf, &SnapSettings::default(), None, &ignored(), MY_PID).unwrap();
+
+        assert_eq!(fake.moved_ids(), vec![10]);
+        assert_eq!(fake.frame_of(10), Some(rect(1000.0, 25.0, 500.0, 775.0)));
+    }
+
+    #[test]
+    fn repeated_snap_cycles_with_spacing() {
+        let fake = backend(vec![window(10, 100, "Safari", "", rect(0.0, 0.0, 500.0, 500.0))]);
+        let settings = SnapSettings { padding: 10.0, gap: 0.0 };
+
+        snap_active_window(&fake, SnapTarget::RightHalf, &settings, None, &ignored(), MY_PID).unwrap();
+        assert_eq!(fake.frame_of(10), Some(rect(500.0, 35.0, 490.0, 755.0)));
+
+        snap_active_window(&fake, SnapTarget::RightHalf, &settings, None, &ignored(), MY_PID).unwrap();
+        let third = fake.frame_of(10).unwrap();
+        assert!((third.width - 980.0 / 3.0).abs() < 1e-9);
+        assert!((third.x + third.width - 990.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn snap_without_windows_reports_it() {
+        let fake = backend(vec![]);
+        let result = snap_active_window(&fake, SnapTarget::LeftHalf, &SnapSettings::default(), None, &ignored(), MY_PID);
+        assert_eq!(result, Err(WindowError::WindowNotFound { id: None }));
+        assert!(fake.moved_ids().is_empty());
+    }
+
+    #[test]
+    fn backend_errors_reach_the_caller() {
+        let fake = backend(vec![
+            window(10, 100, "Safari", "", rect(0.0, 0.0, 500.0, 500.0)),
+            window(11, 200, "Notes", "", rect(0.0, 0.0, 500.0, 500.0)),
+        ]);
+        *fake.refuse.lock().unwrap() = Some(WindowError::PermissionDenied);
+
+        let snapped = snap_active_window(&fake, SnapTarget::LeftHalf, &SnapSettings::default(), None, &ignored(), MY_PID);
+        assert_eq!(snapped, Err(WindowError::PermissionDenied));
+        let applied = apply_preset_layout(&fake, &preset("columns_2"), &SnapSettings::default(), None, &ignored(), MY_PID);
+        assert_eq!(applied, Err(WindowError::PermissionDenied));
+    }
+
+    #[test]
+    fn snap_targets_focused_window_not_front_most() {
+        let fake = backend(vec![
+            window(10, 100, "Safari", "", rect(0.0, 0.0, 500.0, 500.0)),
+            window(11, 200, "Notes", "", rect(0.0, 0.0, 500.0, 500.0)),
+        ]);
+        *fake.focused.lock().unwrap() = Some(11);
+
+        snap_active_window(&fake, SnapTarget::RightHalf, &SnapSettings::default(), None, &ignored(), MY_PID).unwrap();
+
+        assert_eq!(fake.moved_ids(), vec![11]);
+    }
+
+    #[test]
+    fn snap_from_drawer_targets_remembered_window() {
+        let fake = backend(vec![
+            window(1, MY_PID, "My Drawer", "", rect(0.0, 0.0, 400.0, 800.0)),
+            window(10, 100, "Safari", "", rect(0.0, 0.0, 500.0, 500.0)),
+            window(11, 200, "Notes", "", rect(0.0, 0.0, 500.0, 500.0)),
+        ]);
+        let tracker = FocusTracker::new();
+
+        // The user was in Notes, then the drawer opened and took focus
+        *fake.focused.lock().unwrap() = Some(11);
+        tracker.record(&fake, &ignored(), MY_PID);
+        *fake.focused.lock().unwrap() = Some(1);
+        tracker.record(&fake, &ignored(), MY_PID);
+
+        assert_eq!(tracker.last().map(|w| w.id), Some(11));
+        snap_active_window(&fake, SnapTarget::LeftHalf, &SnapSettings::default(), tracker.last().as_ref(), &ignored(), MY_PID).unwrap();
+        assert_eq!(fake.moved_ids(), vec![11]);
+    }
+
+    #[test]
+    fn closed_remembered_window_falls_back_to_front_most() {
+        let fake = backend(vec![
+            window(1, MY_PID, "My Drawer", "", rect(0.0, 0.0, 400.0, 800.0)),
+            window(10, 100, "Safari", "", rect(0.0, 0.0, 500.0, 500.0)),
+        ]);
+        let gone = window(99, 300, "Mail", "", rect(0.0, 0.0, 500.0, 500.0));
+
+        snap_active_window(&fake, SnapTarget::LeftHalf, &SnapSettings::default(), Some(&gone), &ignored(), MY_PID).unwrap();
+
+        assert_eq!(fake.moved_ids(), vec![10]);
+    }
+
+    #[test]
+    fn apply_preset_moves_windows_in_order() {
+        let fake = backend(vec![
+            window(10, 100, "Safari", "", rect(0.0, 0.0, 500.0, 500.0)),
+            window(11, 200, "Notes", "", rect(0.0, 0.0, 500.0, 500.0)),
+            window(12, 300, "Mail", "", rect(0.0, 0.0, 500.0, 500.0)),
+        ]);
+
+        apply_preset_layout(&fake, &preset("columns_2"), &SnapSettings::default(), None, &ignored(), MY_PID).unwrap();
+
+        assert_eq!(fake.moved_ids(), vec![10, 11]);
+        assert_eq!(fake.frame_of(10), Some(rect(0.0, 25.0, 500.0, 775.0)));
+        assert_eq!(fake.frame_of(11), Some(rect(500.0, 25.0, 500.0, 775.0)));
+        assert_eq!(fake.frame_of(12), Some(rect(0.0, 0.0, 500.0, 500.0)));
+    }
+
+    #[test]
+    fn apply_preset_gives_focused_window_the_main_slot() {
+        let fake = backend(vec![
+            window(10, 100, "Safari", "", rect(0.0, 0.0, 500.0, 500.0)),
+            window(11, 200, "Notes", "", rect(0.0, 0.0, 500.0, 500.0)),
+            window(12, 300, "Mail", "", rect(0.0, 0.0, 500.0, 500.0)),
+        ]);
+        *fake.focused.lock().unwrap() = Some(12);
+
+        apply_preset_layout(&fake, &preset("main_left"), &SnapSettings::default(), None, &ignored(), MY_PID).unwrap();
+
+        assert_eq!(fake.moved_ids(), vec![12, 10, 11]);
+        assert_eq!(fake.frame_of(12), Some(rect(0.0, 25.0, 600.0, 775.0)));
+    }
+
+    #[test]
+    fn apply_preset_with_too_few_windows_does_nothing() {
+        let fake = backend(vec![window(10, 100, "Safari", "", rect(0.0, 0.0, 500.0, 500.0))]);
+        apply_preset_layout(&fake, &preset("grid_4"), &SnapSettings::default(), None, &ignored(), MY_PID).unwrap();
+        assert!(fake.moved_ids().is_empty());
+    }
+
+    #[test]
+    fn restore_matches_by_id_title_and_app() {
+        let fake = backend(vec![
+            window(20, 100, "Safari", "Docs", rect(0.0, 0.0, 500.0, 500.0)),
+            window(21, 200, "Notes", "Todo", rect(0.0, 0.0, 500.0, 500.0)),
+            window(22, 300, "Mail", "Inbox", rect(0.0, 0.0, 500.0, 500.0)),
+        ]);
+
+        let saved = vec![
+            // Same id
+            window(20, 999, "Safari", "Old title", rect(1.0, 1.0, 200.0, 200.0)),
+            // New id, same app and title
+            window(91, 999, "Notes", "Todo", rect(2.0, 2.0, 200.0, 200.0)),
+            // New id and title, same app
+            window(92, 999, "Mail", "Drafts", rect(3.0, 3.0, 200.0, 200.0)),
+            // App no longer running
+            window(93, 999, "Music", "", rect(4.0, 4.0, 200.0, 200.0)),
+        ];
+
+        let results = restore(&fake, saved, &RestoreOptions::default());
+
+        assert_eq!(fake.moved_ids(), vec![20, 21, 22]);
+        let statuses: Vec<_> = results.iter().map(|r| (r.window_id, r.status.clone())).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                (Some(20), RestoreStatus::Placed),
+                (Some(21), RestoreStatus::Placed),
+                (Some(22), RestoreStatus::Placed),
+                (None, RestoreStatus::NotFound),
+            ]
+        );
+        assert!(results[0].score.as_ref().unwrap().same_id);
+        assert_eq!(results[1].score.as_ref().unwrap().title, 1.0);
+        assert!(results[3].score.is_none());
+        assert_eq!(fake.frame_of(20), Some(rect(1.0, 1.0, 200.0, 200.0)));
+        assert_eq!(fake.frame_of(21), Some(rect(2.0, 2.0, 200.0, 200.0)));
+        assert_eq!(fake.frame_of(22), Some(rect(3.0, 3.0, 200.0, 200.0)));
+    }
+
+    #[test]
+    fn restore_positions_each_window_of_an_app_separately() {
+        let fake = backend(vec![
+            window(30, 100, "Safari", "News", rect(0.0, 0.0, 500.0, 500.0)),
+            window(31, 100, "Safari", "Mail", rect(0.0, 0.0, 500.0, 500.0)),
+            window(32, 100, "Safari", "Docs", rect(0.0, 0.0, 500.0, 500.0)),
+        ]);
+
+        let saved = vec![
+            // Loose app-only match listed before the exact ones
+            window(80, 100, "Safari", "Gone", rect(1.0, 1.0, 200.0, 200.0)),
+            window(81, 100, "Safari", "Docs", rect(2.0, 2.0, 200.0, 200.0)),
+            window(30, 100, "Safari", "News", rect(3.0, 3.0, 200.0, 200.0)),
+        ];
+
+        restore(&fake, saved, &RestoreOptions::default());
+
+        assert_eq!(fake.frame_of(30), Some(rect(3.0, 3.0, 200.0, 200.0)));
+        assert_eq!(fake.frame_of(32), Some(rect(2.0, 2.0, 200.0, 200.0)));
+        assert_eq!(fake.frame_of(31), Some(rect(1.0, 1.0, 200.0, 200.0)));
+        assert_eq!(fake.moved_ids().len(), 3);
+    }
+
+    #[test]
+    fn restore_ignores_extra_saved_windows() {
+        let fake = backend(vec![window(30, 100, "Safari", "News", rect(0.0, 0.0, 500.0, 500.0))]);
+
+        let saved = vec![
+            window(80, 100, "Safari", "One", rect(1.0, 1.0, 200.0, 200.0)),
+            window(81, 100, "Safari", "Two", rect(2.0, 2.0, 200.0, 200.0)),
+        ];
+
+        restore(&fake, saved, &RestoreOptions::default());
+
+        assert_eq!(fake.moved_ids(), vec![30]);
+        assert_eq!(fake.frame_of(30), Some(rect(1.0, 1.0, 200.0, 200.0)));
+    }
+
+    #[test]
+    fn restore_never_moves_own_windows() {
+        let fake = backend(vec![window(20, MY_PID, "My Drawer", "", rect(0.0, 0.0, 400.0, 800.0))]);
+        restore(
+            &fake,
+            vec![window(20, MY_PID, "My Drawer", "", rect(5.0, 5.0, 400.0, 800.0))],
+            &RestoreOptions::default(),
+        );
+        assert!(fake.moved_ids().is_empty());
+    }
+
+    #[test]
+    fn restore_launches_missing_apps_and_places_their_windows() {
+        let fake = backend(vec![window(20, 100, "Safari", "Docs", rect(0.0, 0.0, 500.0, 500.0))]);
+        let launched = Mutex::new(Vec::new());
+        let events = Mutex::new(Vec::new());
+
+        let saved = vec![
+            window(20, 100, "Safari", "Docs", rect(1.0, 1.0, 200.0, 200.0)),
+            window(90, 200, "Notes", "Todo", rect(2.0, 2.0, 200.0, 200.0)),
+            window(91, 200, "Notes", "Ideas", rect(3.0, 3.0, 200.0, 200.0)),
+        ];
+        let results = restore_windows(
+            &fake,
+            saved,
+            &launching(1000),
+            &ignored(),
+            MY_PID,
+            &|w| {
+                launched.lock().unwrap().push(w.app_name.clone());
+                let mut windows = fake.windows.lock().unwrap();
+                windows.push(window(40, 300, "Notes", "Ideas", rect(0.0, 0.0, 500.0, 500.0)));
+                windows.push(window(41, 300, "Notes", "Todo", rect(0.0, 0.0, 500.0, 500.0)));
+                Ok(())
+            },
+            &|event| events.lock().unwrap().push(event),
+        );
+
+        // Launched once for both of its windows
+        assert_eq!(*launched.lock().unwrap(), vec!["Notes".to_string()]);
+        assert_eq!(fake.frame_of(41), Some(rect(2.0, 2.0, 200.0, 200.0)));
+        assert_eq!(fake.frame_of(40), Some(rect(3.0, 3.0, 200.0, 200.0)));
+
+        let statuses: Vec<_> = results.iter().map(|r| (r.window_id, r.status.clone())).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                (Some(20), RestoreStatus::Placed),
+                (Some(41), RestoreStatus::LaunchedAndPlaced),
+                (Some(40), RestoreStatus::LaunchedAndPlaced),
+            ]
+        );
+        assert_eq!(
+            events.lock().unwrap().last(),
+            Some(&RestoreProgress::Finished { placed: 3, not_found: 0, failed: 0 })
+        );
+    }
+
+    #[test]
+    fn restore_does_not_launch_apps_that_are_already_running() {
+        let fake = backend(vec![window(20, 100, "Safari", "Docs", rect(0.0, 0.0, 500.0, 500.0))]);
+        let saved = vec![
+            window(20, 100, "Safari", "Docs", rect(1.0, 1.0, 200.0, 200.0)),
+            window(21, 100, "Safari", "News", rect(2.0, 2.0, 200.0, 200.0)),
+        ];
+
+        let results = restore_windows(
+            &fake,
+            saved,
+            &launching(1000),
+            &ignored(),
+            MY_PID,
+            &|_| panic!("should not launch"),
+            &|_| {},
+        );
+
+        assert_eq!(results[1].status, RestoreStatus::NotFound);
+    }
+
+    #[test]
+    fn restore_gives_up_on_launched_apps_after_timeout() {
+        let fake = backend(Vec::new());
+        let events = Mutex::new(Vec::new());
+
+        let results = restore_windows(
+            &fake,
+            vec![window(90, 200, "Notes", "Todo", rect(2.0, 2.0, 200.0, 200.0))],
+            &launching(20),
+            &ignored(),
+            MY_PID,
+            &|_| Ok(()),
+            &|event| events.lock().unwrap().push(event),
+        );
+
+        assert_eq!(results[0].status, RestoreStatus::NotFound);
+        let events = events.lock().unwrap();
+        assert_eq!(events[0], RestoreProgress::Launching { app_name: "Notes".into() });
+        assert!(events.contains(&RestoreProgress::Waiting { remaining: 1 }));
+        assert_eq!(events.last(), Some(&RestoreProgress::Finished { placed: 0, not_found: 1, failed: 0 }));
+    }
+
+    #[test]
+    fn restore_reports_launch_failures() {
+        let fake = backend(Vec::new());
+        let events = Mutex::new(Vec::new());
+
+        restore_windows(
+            &fake,
+            vec![window(90, 200, "Notes", "Todo", rect(2.0, 2.0, 200.0, 200.0))],
+            &launching(1000),
+            &ignored(),
+            MY_PID,
+            &|_| Err("missing".into()),
+            &|event| events.lock().unwrap().push(event),
+        );
+
+        let events = events.lock().unwrap();
+        assert_eq!(
+            events[1],
+            RestoreProgress::LaunchFailed { app_name: "Notes".into(), error: "missing".into() }
+        );
+        assert_eq!(events.len(), 3);
+    }
+
+    #[test]
+    fn restore_reports_windows_that_could_not_be_moved() {
+        let fake = backend(vec![window(20, 100, "Safari", "Docs", rect(0.0, 0.0, 500.0, 500.0))]);
+        *fake.refuse.lock().unwrap() = Some(WindowError::AppNotScriptable { app_name: "Safari".into() });
+        let events = Mutex::new(Vec::new());
+
+        let results = restore_windows(
+            &fake,
+            vec![window(20, 100, "Safari", "Docs", rect(1.0, 1.0, 200.0, 200.0))],
+            &RestoreOptions::default(),
+            &ignored(),
+            MY_PID,
+            &|_| panic!("should not launch"),
+            &|event| events.lock().unwrap().push(event),
+        );
+
+        assert_eq!(results[0].window_id, Some(20));
+        assert_eq!(
+            results[0].status,
+            RestoreStatus::PlacementFailed {
+                error: WindowError::AppNotScriptable { app_name: "Safari".into() }
+            }
+        );
+        assert_eq!(
+            events.lock().unwrap().last(),
+            Some(&RestoreProgress::Finished { placed: 0, not_found: 0, failed: 1 })
+        );
+        let json = serde_json::to_value(&results[0]).unwrap();
+        assert_eq!(json["status"], "placementFailed");
+        assert_eq!(json["error"], serde_json::json!({ "kind": "appNotScriptable", "appName": "Safari" }));
+    }
+
+    #[test]
+    fn annotate_records_display_and_relative_frame() {
+        let displays = [display(1, 0.0, 0.0), display(2, 1000.0, 0.0)];
+        let mut windows = vec![window(10, 100, "Safari", "", rect(1250.0, 25.0, 500.0, 310.0))];
+
+        annotate_displays(&mut windows, &displays);
+
+        assert_eq!(windows[0].display_id, Some(2));
+        assert_eq!(windows[0].relative_frame, Some(rect(0.25, 0.0, 0.5, 0.4)));
+        assert_eq!(saved_frame(&windows[0], &displays), windows[0].frame);
+    }
+
+    #[test]
+    fn saved_frame_follows_a_resized_or_missing_display() {
+        let mut saved = window(10, 100, "Safari", "", rect(1250.0, 25.0, 500.0, 310.0));
+        saved.display_id = Some(2);
+        saved.relative_frame = Some(rect(0.25, 0.0, 0.5, 0.4));
+
+        // Display 2 came back at double the size, to the left of the laptop
+        let mut bigger = display(2, -2000.0, 0.0);
+        bigger.frame = rect(-2000.0, 0.0, 2000.0, 1600.0);
+        bigger.work_area = rect(-2000.0, 0.0, 2000.0, 1600.0);
+        assert_eq!(
+            saved_frame(&saved, &[display(1, 0.0, 0.0), bigger]),
+            rect(-1500.0, 0.0, 1000.0, 640.0)
+        );
+
+        // Undocked: falls back to the main display
+        assert_eq!(saved_frame(&saved, &[display(1, 0.0, 0.0)]), rect(250.0, 25.0, 500.0, 310.0));
+
+        // Captured before display information was recorded
+        saved.relative_frame = None;
+        assert_eq!(saved_frame(&saved, &[display(1, 0.0, 0.0)]), saved.frame);
+    }
+
+    #[test]
+    fn restore_places_windows_relative_to_their_display() {
+        let fake = backend(vec![window(20, 100, "Safari", "Docs", rect(0.0, 0.0, 500.0, 500.0))]);
+        let mut saved = window(20, 100, "Safari", "Docs", rect(1250.0, 25.0, 500.0, 310.0));
+        saved.display_id = Some(2);
+        saved.relative_frame = Some(rect(0.0, 0.0, 1.0, 1.0));
+
+        restore(&fake, vec![saved], &RestoreOptions::default());
+
+        assert_eq!(fake.frame_of(20), Some(rect(1000.0, 25.0, 1000.0, 775.0)));
+    }
+
+    #[test]
+    fn capture_skips_own_windows_and_records_displays() {
+        let fake = backend(vec![
+            window(10, 100, "Safari", "", rect(1250.0, 25.0, 500.0, 310.0)),
+            window(11, MY_PID, "My Drawer", "", rect(0.0, 0.0, 400.0, 800.0)),
+        ]);
+
+        let windows = capture_windows(&fake, &ignored(), MY_PID);
+
+        assert_eq!(windows.len(), 1);
+        assert_eq!(windows[0].display_id, Some(2));
+        assert!(windows[0].relative_frame.is_some());
+    }
+}
@@ -0,0 +1,543 @@
+use crate::error::AppError;
+use crate::layout_manager::WindowRect;
+use crate::settings::open_db;
+use crate::snap::{Cell, SnapSettings};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+// Keeps user presets to something a screen can actually show
+const MAX_SLOTS: usize = 32;
+const MAX_SPLIT_DEPTH: usize = 8;
+
+/// How a preset divides a display's working area. Slots are filled in order:
+/// the focused window takes the first one, the rest follow stacking order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PresetKind {
+    /// Equal cells, filled row by row
+    Grid { columns: usize, rows: usize },
+    /// Recursive splits; every `window` leaf is a slot
+    Split { root: SplitNode },
+    /// Explicit rectangles as fractions of the working area
+    Cells { cells: Vec<Cell> },
+    /// One main window on the left, the others stacked on the right
+    MainStack { main_ratio: f64, max_stack: usize },
+    /// Overlapping windows shifted by `offset` points each
+    Cascade {
+        offset: f64,
+        start: f64,
+        width: f64,
+        height: f64,
+        max_windows: usize,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SplitNode {
+    Window,
+    /// Children side by side, sized by `ratios` (relative weights)
+    Horizontal { ratios: Vec<f64>, children: Vec<SplitNode> },
+    /// Children stacked top to bottom, sized by `ratios` (relative weights)
+    Vertical { ratios: Vec<f64>, children: Vec<SplitNode> },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LayoutPreset {
+    pub id: String,
+    pub name: String,
+    pub builtin: bool,
+    pub layout: PresetKind,
+}
+
+impl SplitNode {
+    fn validate(&self, depth: usize) -> Result<usize, String> {
+        if depth > MAX_SPLIT_DEPTH {
+            return Err(format!("Splits can be nested at most {} deep", MAX_SPLIT_DEPTH));
+        }
+        match self {
+            SplitNode::Window => Ok(1),
+            SplitNode::Horizontal { ratios, children } | SplitNode::Vertical { ratios, children } => {
+                if children.is_empty() {
+                    return Err("A split needs at least one child".into());
+                }
+                if ratios.len() != children.len() {
+                    return Err(format!(
+                        "A split has {} ratios for {} children",
+                        ratios.len(),
+                        children.len()
+                    ));
+                }
+                if ratios.iter().any(|r| !r.is_finite() || *r <= 0.0) {
+                    return Err("Split ratios must be positive numbers".into());
+                }
+                children.iter().map(|c| c.validate(depth + 1)).sum()
+            }
+        }
+    }
+
+    fn collect_cells(&self, cell: Cell, out: &mut Vec<Cell>) {
+        match self {
+            SplitNode::Window => out.push(cell),
+            SplitNode::Horizontal { ratios, children } => {
+                let total: f64 = ratios.iter().sum();
+                let mut x = cell.x;
+                for (ratio, child) in ratios.iter().zip(children) {
+                    let width = cell.width * ratio / total;
+                    child.collect_cells(Cell::new(x, cell.y, width, cell.height), out);
+                    x += width;
+                }
+            }
+            SplitNode::Vertical { ratios, children } => {
+                let total: f64 = ratios.iter().sum();
+                let mut y = cell.y;
+                for (ratio, child) in ratios.iter().zip(children) {
+                    let height = cell.height * ratio / total;
+                    child.collect_cells(Cell::new(cell.x, y, cell.width, height), out);
+                    y += height;
+                }
+            }
+        }
+    }
+}
+
+impl PresetKind {
+    /// Parses a definition written as JSON or TOML.
+    pub fn parse(definition: &str) -> Result<Self, String> {
+        let trimmed = definition.trim_start();
+        let kind: PresetKind = if trimmed.starts_with('{') {
+            serde_json::from_str(trimmed).map_err(|e| format!("Invalid JSON preset: {}", e))?
+        } else {
+            toml::from_str(trimmed).map_err(|e| format!("Invalid TOML preset: {}", e))?
+        };
+        kind.validate()?;
+        Ok(kind)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        let ratio = |v: f64| v.is_finite() && v > 0.0 && v <= 1.0;
+
+        match self {
+            PresetKind::Grid { columns, rows } => {
+                if *columns == 0 || *rows == 0 || columns * rows > MAX_SLOTS {
+                    return Err(format!("A grid needs between 1 and {} cells", MAX_SLOTS));
+                }
+            }
+            PresetKind::Split { root } => {
+                if root.validate(0)? > MAX_SLOTS {
+                    return Err(format!("A split can hold at most {} windows", MAX_SLOTS));
+                }
+            }
+            PresetKind::Cells { cells } => {
+                if cells.is_empty() || cells.len() > MAX_SLOTS {
+                    return Err(format!("A preset needs between 1 and {} cells", MAX_SLOTS));
+                }
+                for (i, cell) in cells.iter().enumerate() {
+                    cell.validate().map_err(|e| format!("Cell {}: {}", i + 1, e))?;
+                }
+            }
+            PresetKind::MainStack { main_ratio, max_stack } => {
+                if !ratio(*main_ratio) || *main_ratio >= 1.0 {
+                    return Err("main_ratio must be between 0 and 1".into());
+                }
+                if *max_stack == 0 || *max_stack >= MAX_SLOTS {
+                    return Err(format!("max_stack must be between 1 and {}", MAX_SLOTS - 1));
+                }
+            }
+            PresetKind::Cascade { offset, start, width, height, max_windows } => {
+                if !offset.is_finite() || *offset < 0.0 || !start.is_finite() || *start < 0.0 {
+                    return Err("offset and start must be non-negative numbers".into());
+                }
+                if !ratio(*width) || !ratio(*height) {
+                    return Err("width and height must be ratios between 0 and 1".into());
+                }
+                if *max_windows == 0 || *max_windows > MAX_SLOTS {
+                    return Err(format!("max_windows must be between 1 and {}", MAX_SLOTS));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Fewest windows the preset needs before it is applied.
+    pub fn min_windows(&self) -> usize {
+        match self {
+            PresetKind::Grid { columns, rows } => columns * rows,
+            PresetKind::Split { root } => root.validate(0).unwrap_or(usize::MAX),
+            PresetKind::Cells { cells } => cells.len(),
+            PresetKind::MainStack { .. } => 2,
+            PresetKind::Cascade { .. } => 1,
+        }
+    }
+
+    /// Frames for the first `count` windows. Empty when there are fewer
+    /// windows than the preset needs.
+    pub fn frames(&self, area: &WindowRect, settings: &SnapSettings, count: usize) -> Vec<WindowRect> {
+        if count < self.min_windows() {
+            return Vec::new();
+        }
+
+        let cells = match self {
+            PresetKind::Grid { columns, rows } => {
+                let (w, h) = (1.0 / *columns as f64, 1.0 / *rows as f64);
+                (0..rows * columns)
+                    .map(|i| Cell::new((i % columns) as f64 * w, (i / columns) as f64 * h, w, h))
+                    .collect()
+            }
+            PresetKind::Split { root } => {
+                let mut cells = Vec::new();
+                root.collect_cells(Cell::new(0.0, 0.0, 1.0, 1.0), &mut cells);
+                cells
+            }
+            PresetKind::Cells { cells } => cells.clone(),
+            PresetKind::MainStack { main_ratio, max_stack } => {
+                let stack_count = (count - 1).min(*max_stack);
+                let stack_h = 1.0 / stack_count as f64;
+                let mut cells = vec![Cell::new(0.0, 0.0, *main_ratio, 1.0)];
+                cells.extend(
+                    (0..stack_count).map(|i| Cell::new(*main_ratio, i as f64 * stack_h, 1.0 - main_ratio, stack_h)),
+                );
+                cells
+            }
+            PresetKind::Cascade { offset, start, width, height, max_windows } => {
+                let inner = Cell::new(0.0, 0.0, 1.0, 1.0).frame_in(area, settings);
+                return (0..count.min(*max_windows))
+                    .map(|i| WindowRect {
+                        x: inner.x + start + i as f64 * offset,
+                        y: inner.y + start + i as f64 * offset,
+                        width: inner.width * width,
+                        height: inner.height * height,
+                    })
+                    .collect();
+            }
+        };
+
+        cells.iter().take(count).map(|cell| cell.frame_in(area, settings)).collect()
+    }
+
+    /// Every slot the preset has room for, e.g. as drop zones for a dragged window.
+    pub fn zones(&self, area: &WindowRect, settings: &SnapSettings) -> Vec<WindowRect> {
+        self.frames(area, settings, MAX_SLOTS)
+    }
+}
+
+pub fn builtin_presets() -> Vec<LayoutPreset> {
+    let preset = |id: &str, name: &str, layout: PresetKind| LayoutPreset {
+        id: id.to_string(),
+        name: name.to_string(),
+        builtin: true,
+        layout,
+    };
+
+    vec![
+        preset("rows_2", "Two Rows", PresetKind::Grid { columns: 1, rows: 2 }),
+        preset("rows_3", "Three Rows", PresetKind::Grid { columns: 1, rows: 3 }),
+        preset("columns_2", "Two Columns", PresetKind::Grid { columns: 2, rows: 1 }),
+        preset("columns_3", "Three Columns", PresetKind::Grid { columns: 3, rows: 1 }),
+        preset("grid_4", "Grid", PresetKind::Grid { columns: 2, rows: 2 }),
+        preset("main_left", "Main Left", PresetKind::MainStack { main_ratio: 0.6, max_stack: 3 }),
+        preset(
+            "cascade",
+            "Cascade",
+            PresetKind::Cascade {
+                offset: 30.0,
+                start: 50.0,
+                width: 0.6,
+                height: 0.7,
+                max_windows: 10,
+            },
+        ),
+    ]
+}
+
+pub fn builtin_preset(id: &str) -> Option<LayoutPreset> {
+    builtin_presets().into_iter().find(|p| p.id == id)
+}
+
+fn validate_id(id: &str) -> Result<(), String> {
+    let valid_chars = id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    if id.is_empty() || id.len() > 64 || !valid_chars {
+        return Err("Preset ids may only contain letters, digits, '-' and '_'".into());
+    }
+    if builtin_preset(id).is_some() {
+        return Err(format!("\"{}\" is a built-in preset", id));
+    }
+    Ok(())
+}
+
+fn preset_from_row((id, name, definition): (String, String, String)) -> Result<LayoutPreset, String> {
+    let layout = PresetKind::parse(&definition).map_err(|e| format!("Preset \"{}\": {}", id, e))?;
+    Ok(LayoutPreset {
+        id,
+        name,
+        builtin: false,
+        layout,
+    })
+}
+
+/// Looks up a built-in or saved preset.
+pub async fn find_preset(app: &AppHandle, id: &str) -> Result<LayoutPreset, AppError> {
+    if let Some(preset) = builtin_preset(id) {
+        return Ok(preset);
+    }
+
+    let pool = open_db(app).await?;
+    let row: Option<(String, String, String)> =
+        sqlx::query_as("SELECT id, name, definition FROM layout_presets WHERE id = ?")
+            .bind(id)
+            .fetch_optional(&pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    pool.close().await;
+
+    let row = row.ok_or_else(|| AppError::PresetNotFound { preset_id: id.to_string() })?;
+    Ok(preset_from_row(row)?)
+}
+
+#[tauri::command]
+pub async fn list_layout_presets(app: AppHandle) -> Result<Vec<LayoutPreset>, AppError> {
+    let pool = open_db(&app).await?;
+    let rows: Vec<(String, String, String)> =
+        sqlx::query_as("SELECT id, name, definition FROM layout_presets ORDER BY name")
+            .fetch_all(&pool)
+            .await
+            .map_err(|e| e.to_string())?;
+    pool.close().await;
+
+    let mut presets = builtin_presets();
+    // A row that no longer validates is skipped rather than hiding every preset
+    presets.extend(rows.into_iter().filter_map(|row| preset_from_row(row).ok()));
+    Ok(presets)
+}
+
+/// Creates or replaces a user preset. `definition` is a `PresetKind` written as
+/// JSON or TOML; a new id is generated when none is given.
+#[tauri::command]
+pub async fn save_layout_preset(
+    app: AppHandle,
+    id: Option<String>,
+    name: String,
+    definition: String,
+) -> Result<LayoutPreset, AppError> {
+    let id = id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+    validate_id(&id).map_err(AppError::InvalidInput)?;
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput("Preset name cannot be empty".into()));
+    }
+    let layout = PresetKind::parse(&definition).map_err(AppError::InvalidInput)?;
+
+    // Stored normalised as JSON regardless of the input format
+    let stored = serde_json::to_string(&layout).map_err(|e| e.to_string())?;
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let pool = open_db(&app).await?;
+    sqlx::query(
+        "INSERT INTO layout_presets (id, name, definition, created_at, updated_at) VALUES (?, ?, ?, ?, ?)
+         ON CONFLICT(id) DO UPDATE SET name = excluded.name, definition = excluded.definition, updated_at = excluded.updated_at",
+    )
+    .bind(&id)
+    .bind(&name)
+    .bind(&stored)
+    .bind(&now)
+    .bind(&now)
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    pool.close().await;
+
+    Ok(LayoutPreset {
+        id,
+        name,
+        builtin: false,
+        layout,
+    })
+}
+
+#[tauri::command]
+pub async fn delete_layout_preset(app: AppHandle, id: String) -> Result<(), AppError> {
+    if builtin_preset(&id).is_some() {
+        return Err(AppError::InvalidInput("Built-in presets cannot be deleted".into()));
+    }
+
+    let pool = open_db(&app).await?;
+    sqlx::query("DELETE FROM layout_presets WHERE id = ?")
+        .bind(&id)
+        .execute(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    pool.close().await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_backend::fake::rect;
+
+    fn layout(id: &str) -> PresetKind {
+        builtin_preset(id).unwrap().layout
+    }
+
+    fn area() -> WindowRect {
+        rect(0.0, 0.0, 900.0, 600.0)
+    }
+
+    fn assert_frames(actual: Vec<WindowRect>, expected: Vec<WindowRect>) {
+        assert_eq!(actual.len(), expected.len());
+        for (a, e) in actual.iter().zip(&expected) {
+            let close = (a.x - e.x).abs() < 1e-9
+                && (a.y - e.y).abs() < 1e-9
+                && (a.width - e.width).abs() < 1e-9
+                && (a.height - e.height).abs() < 1e-9;
+            assert!(close, "{:?} != {:?}", a, e);
+        }
+    }
+
+    #[test]
+    fn builtins_are_valid() {
+        for preset in builtin_presets() {
+            assert!(preset.layout.validate().is_ok(), "{} is invalid", preset.id);
+        }
+    }
+
+    #[test]
+    fn builtins_require_enough_windows() {
+        let none = SnapSettings::default();
+        assert!(layout("rows_2").frames(&area(), &none, 1).is_empty());
+        assert!(layout("columns_3").frames(&area(), &none, 2).is_empty());
+        assert!(layout("grid_4").frames(&area(), &none, 3).is_empty());
+        assert!(layout("main_left").frames(&area(), &none, 1).is_empty());
+    }
+
+    #[test]
+    fn grids_tile_the_work_area() {
+        let none = SnapSettings::default();
+
+        assert_frames(
+            layout("columns_3").frames(&area(), &none, 3),
+            vec![rect(0.0, 0.0, 300.0, 600.0), rect(300.0, 0.0, 300.0, 600.0), rect(600.0, 0.0, 300.0, 600.0)],
+        );
+        assert_frames(
+            layout("rows_3").frames(&area(), &none, 4),
+            vec![rect(0.0, 0.0, 900.0, 200.0), rect(0.0, 200.0, 900.0, 200.0), rect(0.0, 400.0, 900.0, 200.0)],
+        );
+        assert_frames(
+            layout("grid_4").frames(&area(), &none, 4),
+            vec![
+                rect(0.0, 0.0, 450.0, 300.0),
+                rect(450.0, 0.0, 450.0, 300.0),
+                rect(0.0, 300.0, 450.0, 300.0),
+                rect(450.0, 300.0, 450.0, 300.0),
+            ],
+        );
+    }
+
+    #[test]
+    fn main_left_stacks_at_most_three_windows() {
+        let frames = layout("main_left").frames(&rect(0.0, 0.0, 1000.0, 600.0), &SnapSettings::default(), 6);
+
+        assert_eq!(frames.len(), 4);
+        assert_frames(
+            vec![frames[0].clone(), frames[1].clone(), frames[3].clone()],
+            vec![rect(0.0, 0.0, 600.0, 600.0), rect(600.0, 0.0, 400.0, 200.0), rect(600.0, 400.0, 400.0, 200.0)],
+        );
+    }
+
+    #[test]
+    fn zones_cover_every_slot() {
+        let none = SnapSettings::default();
+        assert_eq!(layout("grid_4").zones(&area(), &none).len(), 4);
+        assert_eq!(layout("main_left").zones(&area(), &none).len(), 4);
+        assert_frames(
+            layout("columns_2").zones(&area(), &none),
+            vec![rect(0.0, 0.0, 450.0, 600.0), rect(450.0, 0.0, 450.0, 600.0)],
+        );
+    }
+
+    #[test]
+    fn cascade_offsets_up_to_ten_windows() {
+        let frames = layout("cascade").frames(&rect(0.0, 0.0, 1000.0, 1000.0), &SnapSettings::default(), 12);
+
+        assert_eq!(frames.len(), 10);
+        assert_eq!(frames[0], rect(50.0, 50.0, 600.0, 700.0));
+        assert_eq!(frames[9], rect(320.0, 320.0, 600.0, 700.0));
+    }
+
+    #[test]
+    fn gaps_apply_between_preset_cells() {
+        let settings = SnapSettings { padding: 10.0, gap: 20.0 };
+        assert_frames(
+            layout("columns_2").frames(&area(), &settings, 2),
+            vec![rect(10.0, 10.0, 430.0, 580.0), rect(460.0, 10.0, 430.0, 580.0)],
+        );
+    }
+
+    #[test]
+    fn parses_split_tree_from_toml() {
+        let definition = r#"
+            type = "split"
+
+            [root]
+            type = "horizontal"
+            ratios = [2, 1]
+
+            [[root.children]]
+            type = "window"
+
+            [[root.children]]
+            type = "vertical"
+            ratios = [1, 1]
+            children = [{ type = "window" }, { type = "window" }]
+        "#;
+
+        let kind = PresetKind::parse(definition).unwrap();
+        assert_eq!(kind.min_windows(), 3);
+        assert_frames(
+            kind.frames(&area(), &SnapSettings::default(), 3),
+            vec![rect(0.0, 0.0, 600.0, 600.0), rect(600.0, 0.0, 300.0, 300.0), rect(600.0, 300.0, 300.0, 300.0)],
+        );
+    }
+
+    #[test]
+    fn parses_cells_from_json() {
+        let definition = r#"{ "type": "cells", "cells": [
+            { "x": 0, "y": 0, "width": 0.25, "height": 1 },
+            { "x": 0.25, "y": 0, "width": 0.75, "height": 1 }
+        ] }"#;
+
+        let kind = PresetKind::parse(definition).unwrap();
+        assert_frames(
+            kind.frames(&area(), &SnapSettings::default(), 5),
+            vec![rect(0.0, 0.0, 225.0, 600.0), rect(225.0, 0.0, 675.0, 600.0)],
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_definitions() {
+        let invalid = [
+            r#"{ "type": "grid", "columns": 0, "rows": 2 }"#,
+            r#"{ "type": "grid", "columns": 10, "rows": 10 }"#,
+            r#"{ "type": "cells", "cells": [] }"#,
+            r#"{ "type": "cells", "cells": [{ "x": 0.5, "y": 0, "width": 0.6, "height": 1 }] }"#,
+            r#"{ "type": "split", "root": { "type": "horizontal", "ratios": [1], "children": [{ "type": "window" }, { "type": "window" }] } }"#,
+            r#"{ "type": "split", "root": { "type": "vertical", "ratios": [1, -1], "children": [{ "type": "window" }, { "type": "window" }] } }"#,
+            r#"{ "type": "main_stack", "main_ratio": 1.0, "max_stack": 3 }"#,
+            r#"{ "type": "cascade", "offset": 30, "start": 50, "width": 1.5, "height": 0.7, "max_windows": 10 }"#,
+            r#"{ "type": "spiral" }"#,
+            "type = \"grid\"\ncolumns = \"two\"",
+        ];
+        for definition in invalid {
+            assert!(PresetKind::parse(definition).is_err(), "{} should be rejected", definition);
+        }
+    }
+
+    #[test]
+    fn ids_must_be_simple_and_not_builtin() {
+        assert!(validate_id("team-standup_2").is_ok());
+        assert!(validate_id("").is_err());
+        assert!(validate_id("has space").is_err());
+        assert!(validate_id("grid_4").is_err());
+    }
+}
@@ -1,15 +1,40 @@
+pub mod content_blocker;
+pub mod display_layouts;
+pub mod drag_snap;
+pub mod drawer;
+pub mod error;
+pub mod launcher;
+pub mod layout_history;
 pub mod layout_manager;
+pub mod layout_presets;
+pub mod page_bridge;
+pub mod permissions;
+pub mod platform;
+pub mod settings;
+pub mod snap;
+pub mod tiling;
+pub mod user_scripts;
 pub mod web_blanket;
+pub mod window_backend;
+pub mod window_match;
+pub mod window_moves;
+pub mod window_rules;
+pub mod window_search;
+pub mod workspace;
 
 use base64::prelude::*;
-use core_foundation::base::TCFType;
-use core_graphics::display::CGDisplay;
-use core_graphics::event::CGEvent;
-use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
-use layout_manager::{get_open_windows, restore_windows, WindowInfo};
-use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use error::AppError;
+use drawer::{DrawerManager, DrawerSpec, MAIN_DRAWER_ID};
+use layout_history::LayoutHistory;
+use layout_manager::{
+    capture_windows, get_open_windows, query_windows, restore_windows, FocusTracker, IgnoredApps,
+    RestoreOptions, RestoreResult, WindowInfo, WindowQuery,
+};
+use snap::{SnapConfig, SnapTarget};
 use std::time::Duration;
-use tauri::{Emitter, Manager, PhysicalPosition, image::Image, AppHandle};
+use permissions::AccessibilityPermission;
+use window_backend::{SharedBackend, WindowError};
+use tauri::{Emitter, Manager, image::Image, AppHandle};
 use tauri::menu::{Menu, MenuItem, MenuEvent, Submenu, PredefinedMenuItem};
 use tauri::tray::TrayIconBuilder;
 use tauri_plugin_sql::{Migration, MigrationKind};
@@ -22,17 +47,49 @@ use serde_json::json;
 
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 
-static IS_DRAWER_OPEN: AtomicBool = AtomicBool::new(false);
-static IS_ANIMATING: AtomicBool = AtomicBool::new(false);
-static DRAWER_CONFIG: AtomicU8 = AtomicU8::new(0); // 0=Left, 1=Right, 2=HotCorners
-static LAST_ACTIVE_SIDE: AtomicU8 = AtomicU8::new(0); // 0=Left, 1=Right
+/// Records an entry in the clipboard history, dropping the oldest unpinned
+/// entries past the user's limit.
+pub(crate) async fn add_clipboard_entry(
+    pool: &sqlx::SqlitePool,
+    content: &str,
+    source_app: &str,
+    character_count: i32,
+) -> Result<(), sqlx::Error> {
+    let id = uuid::Uuid::new_v4().to_string();
+    let now = chrono::Utc::now().to_rfc3339();
+
+    sqlx::query("INSERT INTO clipboard (id, content, source_app, timestamp, character_count, pinned) VALUES (?, ?, ?, ?, ?, ?)")
+        .bind(id)
+        .bind(content)
+        .bind(source_app)
+        .bind(now)
+        .bind(character_count)
+        .bind(false)
+        .execute(pool)
+        .await?;
+
+    // Enforce Limit
+    let limit_row: Option<(String,)> = sqlx::query_as("SELECT value FROM settings WHERE key = 'clipboard_history_limit'")
+        .fetch_optional(pool)
+        .await
+        .unwrap_or(None);
+    let limit = limit_row.and_then(|r| r.0.parse::<i32>().ok()).unwrap_or(50);
+
+    if limit > 0 {
+        sqlx::query("DELETE FROM clipboard WHERE id NOT IN (SELECT id FROM clipboard ORDER BY timestamp DESC LIMIT ?) AND pinned = 0")
+            .bind(limit)
+            .execute(pool)
+            .await?;
+    }
+    Ok(())
+}
 
 fn start_clipboard_monitor(app_handle: tauri::AppHandle) {
     std::thread::spawn(move || {
         let mut clipboard = match Clipboard::new() {
             Ok(c) => c,
             Err(e) => {
-                eprintln!("Failed to init clipboard: {}", e);
+                eprintln!("{}", AppError::Clipboard(e.to_string()));
                 return;
             }
         };
@@ -66,33 +123,7 @@ fn start_clipboard_monitor(app_handle: tauri::AppHandle) {
                         last_text_content = current_text.clone();
                         last_image_hash = 0;
                         
-                        let id = uuid::Uuid::new_v4().to_string();
-                        let now = chrono::Utc::now().to_rfc3339();
-                        
-                        let _ = sqlx::query("INSERT INTO clipboard (id, content, source_app, timestamp, character_count, pinned) VALUES (?, ?, ?, ?, ?, ?)")
-                            .bind(id)
-                            .bind(&current_text)
-                            .bind("System")
-                            .bind(now)
-                            .bind(current_text.len() as i32)
-                            .bind(false)
-                            .execute(&pool)
-                            .await;
-
-                        // Enforce Limit
-                        let limit_row: Option<(String,)> = sqlx::query_as("SELECT value FROM settings WHERE key = 'clipboard_history_limit'")
-                            .fetch_optional(&pool)
-                            .await
-                            .unwrap_or(None);
-                        let limit = limit_row.and_then(|r| r.0.parse::<i32>().ok()).unwrap_or(50);
-
-                        if limit > 0 {
-                             let _ = sqlx::query("DELETE FROM clipboard WHERE id NOT IN (SELECT id FROM clipboard ORDER BY timestamp DESC LIMIT ?) AND pinned = 0")
-                                .bind(limit)
-                                .execute(&pool)
-                                .await;
-                        }
-                            
+                        let _ = add_clipboard_entry(&pool, &current_text, "System", current_text.len() as i32).await;
                         let _ = app_handle.emit("clipboard-changed", ());
                     }
                 }
@@ -118,33 +149,7 @@ fn start_clipboard_monitor(app_handle: tauri::AppHandle) {
                              let base64_string = BASE64_STANDARD.encode(&png_buffer);
                              let content = format!("data:image/png;base64,{}", base64_string);
 
-                             let id = uuid::Uuid::new_v4().to_string();
-                             let now = chrono::Utc::now().to_rfc3339();
-
-                             let _ = sqlx::query("INSERT INTO clipboard (id, content, source_app, timestamp, character_count, pinned) VALUES (?, ?, ?, ?, ?, ?)")
-                                .bind(id)
-                                .bind(&content)
-                                .bind("System")
-                                .bind(now)
-                                .bind(0)
-                                .bind(false)
-                                .execute(&pool)
-                                .await;
-
-                             // Enforce Limit
-                             let limit_row: Option<(String,)> = sqlx::query_as("SELECT value FROM settings WHERE key = 'clipboard_history_limit'")
-                                 .fetch_optional(&pool)
-                                 .await
-                                 .unwrap_or(None);
-                             let limit = limit_row.and_then(|r| r.0.parse::<i32>().ok()).unwrap_or(50);
-     
-                             if limit > 0 {
-                                  let _ = sqlx::query("DELETE FROM clipboard WHERE id NOT IN (SELECT id FROM clipboard ORDER BY timestamp DESC LIMIT ?) AND pinned = 0")
-                                     .bind(limit)
-                                     .execute(&pool)
-                                     .await;
-                             }
-
+                             let _ = add_clipboard_entry(&pool, &content, "System", 0).await;
                              let _ = app_handle.emit("clipboard-changed", ());
                         }
                     }
@@ -156,98 +161,6 @@ fn start_clipboard_monitor(app_handle: tauri::AppHandle) {
     });
 }
 
-#[tauri::command]
-fn set_drawer_config(config: String) {
-    let val = match config.as_str() {
-        "left" => 0,
-        "right" => 1,
-        "hot-corners" => 2,
-        "top-left" => 3,
-        "bottom-left" => 4,
-        "top-right" => 5,
-        "bottom-right" => 6,
-        _ => 0,
-    };
-    DRAWER_CONFIG.store(val, Ordering::Relaxed);
-}
-
-#[tauri::command]
-fn hide_drawer(window: tauri::Window) {
-    if !IS_DRAWER_OPEN.load(Ordering::Relaxed) || IS_ANIMATING.load(Ordering::Relaxed) {
-        return;
-    }
-
-    std::thread::spawn(move || {
-        IS_ANIMATING.store(true, Ordering::Relaxed);
-
-        let monitor = window
-            .current_monitor()
-            .ok()
-            .flatten()
-            .or_else(|| window.primary_monitor().ok().flatten());
-        let scale_factor = monitor.as_ref().map(|m| m.scale_factor()).unwrap_or(2.0);
-        let screen_height = monitor.as_ref().map(|m| m.size().height).unwrap_or(2160); // Default to a common Retina height
-        let screen_width = monitor.as_ref().map(|m| m.size().width).unwrap_or(3840) as f64; // Approx 4k width
-
-        let width = 400.0 * scale_factor;
-        let height = 800.0 * scale_factor;
-
-        let active_side = LAST_ACTIVE_SIDE.load(Ordering::Relaxed);
-
-        // Calculate Start (Current) and End X
-        // For hiding: End is off-screen.
-        // If Left: End = -width.
-        // If Right: End = screen_width.
-        
-        let end_x = if active_side == 1 {
-            screen_width as i32
-        } else {
-            -width as i32
-        };
-
-        // We assume current position is the "Open" position
-        // Left Open: 30
-        // Right Open: screen_width - width - 30
-        let start_x = if active_side == 1 {
-             (screen_width - width - 20.0 * scale_factor) as i32
-        } else {
-             (20.0 * scale_factor) as i32
-        };
-        
-        let center_y = ((screen_height as f64 - height) / 2.0) as i32;
-
-        let duration_ms = 200;
-        let steps = 20;
-        let step_delay = duration_ms / steps;
-
-        for i in 0..=steps {
-            let t = i as f64 / steps as f64;
-            // Ease out cubic
-            let eased_t = 1.0 - (1.0 - t).powi(3);
-            let current_x = (start_x as f64 + (end_x as f64 - start_x as f64) * eased_t) as i32;
-
-            window
-                .set_position(PhysicalPosition::new(current_x, center_y))
-                .unwrap_or(());
-            std::thread::sleep(Duration::from_millis(step_delay));
-        }
-
-        // Ensure final position
-        window
-            .set_position(PhysicalPosition::new(end_x, center_y))
-            .unwrap_or(());
-
-        // Hide window to prevent ghosting on space switch
-        window.hide().unwrap_or(());
-
-        // Enable click-through when hidden
-        window.set_ignore_cursor_events(true).unwrap_or(());
-
-        IS_DRAWER_OPEN.store(false, Ordering::Relaxed);
-        IS_ANIMATING.store(false, Ordering::Relaxed);
-    });
-}
-
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 #[tauri::command]
 fn greet(name: &str) -> String {
@@ -255,7 +168,7 @@ fn greet(name: &str) -> String {
 }
 
 #[tauri::command]
-async fn get_app_icon(path: String) -> Result<String, String> {
+async fn get_app_icon(path: String) -> Result<String, AppError> {
     #[cfg(target_os = "macos")]
     unsafe {
         use cocoa::base::{id, nil};
@@ -272,7 +185,7 @@ async fn get_app_icon(path: String) -> Result<String, String> {
         let _: () = msg_send![path_ns, release];
 
         if icon == nil {
-            return Err("Failed to load icon".to_string());
+            return Err(format!("Failed to load the icon of {}", path).into());
         }
 
         // Convert to TIFF
@@ -286,7 +199,7 @@ async fn get_app_icon(path: String) -> Result<String, String> {
         let png_data: id = msg_send![bitmap_rep, representationUsingType: 4 properties: nil];
 
         if png_data == nil {
-            return Err("Failed to convert to PNG".to_string());
+            return Err("Failed to convert the icon to PNG".into());
         }
 
         let length: usize = msg_send![png_data, length];
@@ -299,92 +212,240 @@ async fn get_app_icon(path: String) -> Result<String, String> {
 
     #[cfg(not(target_os = "macos"))]
     {
-        Err("Not supported on this OS".to_string())
+        let _ = path;
+        Err(AppError::Unsupported { feature: "App icons" })
     }
 }
 
 #[tauri::command]
-fn launch_app(path: &str) -> Result<(), String> {
-    #[cfg(target_os = "macos")]
-    {
-        std::process::Command::new("open")
-            .arg(path)
-            .spawn()
-            .map_err(|e| e.to_string())?;
-        Ok(())
-    }
-    #[cfg(not(target_os = "macos"))]
-    {
-        Err("Not supported".to_string())
-    }
+fn launch_app(path: &str) -> Result<(), AppError> {
+    launcher::launch_path(path).map_err(|reason| AppError::Launch {
+        path: path.to_string(),
+        reason,
+    })
 }
 
 #[tauri::command]
-fn get_windows() -> Vec<WindowInfo> {
+fn get_windows(backend: tauri::State<'_, SharedBackend>, ignored: tauri::State<'_, IgnoredApps>) -> Vec<WindowInfo> {
     let pid = std::process::id() as i32;
-    get_open_windows(pid)
+    capture_windows(backend.as_ref(), &ignored.get(), pid)
 }
 
+/// Every window matching `query`, minimized and off-screen ones included if
+/// asked for, each with its state flags.
 #[tauri::command]
-fn restore_layout(windows: Vec<WindowInfo>) {
+fn list_windows(
+    backend: tauri::State<'_, SharedBackend>,
+    ignored: tauri::State<'_, IgnoredApps>,
+    query: Option<WindowQuery>,
+) -> Vec<WindowInfo> {
     let pid = std::process::id() as i32;
-    restore_windows(windows, pid);
+    query_windows(backend.as_ref(), &query.unwrap_or_default(), &ignored.get(), pid)
 }
 
+const IGNORED_APPS_KEY: &str = "ignored_apps";
+
 #[tauri::command]
-fn snap_active_window(direction: String) {
+fn get_ignored_apps(ignored: tauri::State<'_, IgnoredApps>) -> Vec<String> {
+    ignored.get()
+}
+
+/// Replaces the apps, by name or bundle id, that layouts, snapping, tiling and
+/// window actions leave alone.
+#[tauri::command]
+async fn set_ignored_apps(app: AppHandle, apps: Vec<String>) -> Result<(), AppError> {
+    settings::save(&app, IGNORED_APPS_KEY, &apps).await?;
+    app.state::<IgnoredApps>().set(apps);
+    Ok(())
+}
+
+fn load_ignored_apps(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        match settings::load::<Vec<String>>(&app, IGNORED_APPS_KEY).await {
+            Ok(Some(apps)) => app.state::<IgnoredApps>().set(apps),
+            Ok(None) => {}
+            Err(e) => eprintln!("Failed to load ignored apps: {}", e),
+        }
+    });
+}
+
+#[tauri::command]
+fn search_windows(
+    backend: tauri::State<'_, SharedBackend>,
+    ignored: tauri::State<'_, IgnoredApps>,
+    query: String,
+) -> Vec<window_search::WindowMatch> {
     let pid = std::process::id() as i32;
-    layout_manager::snap_active_window(direction, pid);
+    window_search::search(get_open_windows(backend.as_ref(), &ignored.get(), pid), &query)
 }
 
+/// Raises the window and activates its app, e.g. when picked in the switcher.
 #[tauri::command]
-fn apply_preset_layout(layout: String) {
+fn focus_window(
+    backend: tauri::State<'_, SharedBackend>,
+    permission: tauri::State<'_, AccessibilityPermission>,
+    ignored: tauri::State<'_, IgnoredApps>,
+    id: u32,
+) -> Result<(), AppError> {
+    permission.require()?;
     let pid = std::process::id() as i32;
-    layout_manager::apply_preset_layout(layout, pid);
+    let window = get_open_windows(backend.as_ref(), &ignored.get(), pid)
+        .into_iter()
+        .find(|w| w.id == id)
+        .ok_or(WindowError::WindowNotFound { id: Some(id) })?;
+    backend.focus(&window).map_err(AppError::from)
 }
 
 #[tauri::command]
-fn check_accessibility_permission() -> bool {
-    #[cfg(target_os = "macos")]
-    unsafe {
-        // Some systems might cache the result. Using WithOptions with empty dictionary
-        // can sometimes force a refresh, but AXIsProcessTrusted is standard.
-        // However, if the user toggles it, we want to know immediately.
-        // Let's stick to the standard call, but ensure we aren't using the WithOptions call that prompts.
-        let trusted = accessibility_sys::AXIsProcessTrusted();
-        trusted
-    }
-    #[cfg(not(target_os = "macos"))]
-    {
-        true
+async fn restore_layout(
+    app: AppHandle,
+    backend: tauri::State<'_, SharedBackend>,
+    permission: tauri::State<'_, AccessibilityPermission>,
+    windows: Vec<WindowInfo>,
+    launch_missing: Option<bool>,
+    timeout_ms: Option<u64>,
+) -> Result<Vec<RestoreResult>, AppError> {
+    permission.require()?;
+    let mut options = RestoreOptions {
+        launch_missing: launch_missing.unwrap_or(false),
+        ..Default::default()
+    };
+    if let Some(ms) = timeout_ms {
+        options.timeout = std::time::Duration::from_millis(ms);
     }
+    run_restore(app, backend.inner().clone(), windows, options).await
+}
+
+/// Restores `windows`, emitting `layout-restore-progress` as it goes.
+pub(crate) async fn run_restore(
+    app: AppHandle,
+    backend: SharedBackend,
+    windows: Vec<WindowInfo>,
+    options: RestoreOptions,
+) -> Result<Vec<RestoreResult>, AppError> {
+    let pid = std::process::id() as i32;
+
+    // Waiting for launched apps blocks, so keep it off the async runtime
+    tauri::async_runtime::spawn_blocking(move || {
+        let ignored = app.state::<IgnoredApps>().get();
+        app.state::<LayoutHistory>().record(backend.as_ref(), |backend| {
+            restore_windows(
+                backend,
+                windows,
+                &options,
+                &ignored,
+                pid,
+                &launcher::launch_window_app,
+                &|progress| {
+                    let _ = app.emit("layout-restore-progress", progress);
+                },
+            )
+        })
+    })
+    .await
+    .map_err(|e| AppError::Other(e.to_string()))
 }
 
 #[tauri::command]
-fn request_accessibility_permission() {
-    #[cfg(target_os = "macos")]
-    unsafe {
-        let options = core_foundation::dictionary::CFDictionary::from_CFType_pairs(&[(
-            core_foundation::string::CFString::new("AXTrustedCheckOptionPrompt"),
-            core_foundation::boolean::CFBoolean::true_value(),
-        )]);
-        accessibility_sys::AXIsProcessTrustedWithOptions(options.as_concrete_TypeRef());
-    }
+fn snap_active_window(
+    backend: tauri::State<'_, SharedBackend>,
+    focus: tauri::State<'_, FocusTracker>,
+    snap: tauri::State<'_, SnapConfig>,
+    history: tauri::State<'_, LayoutHistory>,
+    permission: tauri::State<'_, AccessibilityPermission>,
+    ignored: tauri::State<'_, IgnoredApps>,
+    direction: String,
+) -> Result<(), AppError> {
+    let pid = std::process::id() as i32;
+    let target: SnapTarget = direction.parse().map_err(AppError::InvalidInput)?;
+    permission.require()?;
+    history
+        .record(backend.as_ref(), |backend| {
+            layout_manager::snap_active_window(
+                backend,
+                target,
+                &snap.get(),
+                focus.last().as_ref(),
+                &ignored.get(),
+                pid,
+            )
+        })
+        .map_err(AppError::from)
+}
+
+#[tauri::command]
+async fn apply_preset_layout(
+    app: AppHandle,
+    backend: tauri::State<'_, SharedBackend>,
+    focus: tauri::State<'_, FocusTracker>,
+    snap: tauri::State<'_, SnapConfig>,
+    history: tauri::State<'_, LayoutHistory>,
+    permission: tauri::State<'_, AccessibilityPermission>,
+    layout: String,
+) -> Result<(), AppError> {
+    let pid = std::process::id() as i32;
+    let ignored = app.state::<IgnoredApps>().get();
+    let preset = layout_presets::find_preset(&app, &layout).await?;
+    permission.require()?;
+    history
+        .record(backend.as_ref(), |backend| {
+            layout_manager::apply_preset_layout(
+                backend,
+                &preset.layout,
+                &snap.get(),
+                focus.last().as_ref(),
+                &ignored,
+                pid,
+            )
+        })
+        .map_err(AppError::from)
 }
 
+/// Puts back the windows moved by the last layout operation. Returns false when
+/// there is nothing to undo.
 #[tauri::command]
-async fn fetch_webpage(url: String) -> Result<String, String> {
+fn undo_layout(
+    backend: tauri::State<'_, SharedBackend>,
+    history: tauri::State<'_, LayoutHistory>,
+    permission: tauri::State<'_, AccessibilityPermission>,
+) -> Result<bool, AppError> {
+    permission.require()?;
+    Ok(history.undo(backend.as_ref())?)
+}
+
+#[tauri::command]
+fn redo_layout(
+    backend: tauri::State<'_, SharedBackend>,
+    history: tauri::State<'_, LayoutHistory>,
+    permission: tauri::State<'_, AccessibilityPermission>,
+) -> Result<bool, AppError> {
+    permission.require()?;
+    Ok(history.redo(backend.as_ref())?)
+}
+
+#[tauri::command]
+async fn fetch_webpage(url: String) -> Result<String, AppError> {
+    let fetch_error = |e: &dyn std::fmt::Display| AppError::Fetch {
+        url: url.clone(),
+        reason: e.to_string(),
+    };
+
     let client = reqwest::Client::builder()
         .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/91.0.4472.124 Safari/537.36")
         .build()
-        .map_err(|e| e.to_string())?;
+        .map_err(|e| fetch_error(&e))?;
 
-    let resp = client.get(&url).send().await.map_err(|e| e.to_string())?;
+    let resp = client
+        .get(&url)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| fetch_error(&e))?;
 
-    let html = resp.text().await.map_err(|e| e.to_string())?;
+    let html = resp.text().await.map_err(|e| fetch_error(&e))?;
 
     // Sanitize and convert to text
-    let text = html2text::from_read(html.as_bytes(), 80).map_err(|e| e.to_string())?;
+    let text = html2text::from_read(html.as_bytes(), 80).map_err(|e| fetch_error(&e))?;
 
     Ok(text)
 }
@@ -482,6 +543,71 @@ pub fn run() {
             ",
             kind: MigrationKind::Up,
         },
+        Migration {
+            version: 4,
+            description: "create_layout_presets",
+            sql: "
+                CREATE TABLE IF NOT EXISTS layout_presets (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    definition TEXT NOT NULL,
+                    created_at TEXT NOT NULL,
+                    updated_at TEXT NOT NULL
+                );
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 5,
+            description: "bind_layouts_to_displays",
+            sql: "
+                ALTER TABLE window_layouts ADD COLUMN display_key TEXT;
+                ALTER TABLE window_layouts ADD COLUMN auto_apply INTEGER NOT NULL DEFAULT 0;
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 6,
+            description: "create_workspaces",
+            sql: "
+                CREATE TABLE IF NOT EXISTS workspaces (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    data TEXT NOT NULL,
+                    created_at TEXT NOT NULL,
+                    updated_at TEXT NOT NULL
+                );
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 7,
+            description: "create_window_rules",
+            sql: "
+                CREATE TABLE IF NOT EXISTS window_rules (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    data TEXT NOT NULL,
+                    created_at TEXT NOT NULL,
+                    updated_at TEXT NOT NULL
+                );
+            ",
+            kind: MigrationKind::Up,
+        },
+        Migration {
+            version: 8,
+            description: "create_user_scripts",
+            sql: "
+                CREATE TABLE IF NOT EXISTS user_scripts (
+                    id TEXT PRIMARY KEY,
+                    name TEXT NOT NULL,
+                    data TEXT NOT NULL,
+                    created_at TEXT NOT NULL,
+                    updated_at TEXT NOT NULL
+                );
+            ",
+            kind: MigrationKind::Up,
+        },
     ];
 
     tauri::Builder::default()
@@ -504,6 +630,32 @@ pub fn run() {
         })
         .setup(|app| {
             app.manage(web_blanket::WebBlanketState::new());
+            app.manage(user_scripts::UserScripts::new());
+            user_scripts::load(app.handle().clone());
+            app.manage(content_blocker::ContentBlocker::new());
+            content_blocker::load(app.handle().clone());
+            app.manage(page_bridge::PageBridge::new());
+            page_bridge::load(app.handle().clone());
+            app.manage(window_backend::platform_backend());
+            app.manage(AccessibilityPermission::new());
+            permissions::spawn_watcher(app.handle().clone());
+            app.manage(FocusTracker::new());
+            app.manage(LayoutHistory::new());
+            app.manage(SnapConfig::new());
+            snap::load(app.handle().clone());
+            app.manage(IgnoredApps::new());
+            load_ignored_apps(app.handle().clone());
+            app.manage(drag_snap::DragSnap::new());
+            drag_snap::load(app.handle().clone());
+            app.manage(tiling::TilingManager::new());
+            app.manage(window_rules::WindowRules::new());
+            tiling::spawn_watcher(app.handle().clone());
+            display_layouts::spawn_watcher(app.handle().clone());
+            window_rules::spawn_watcher(app.handle().clone());
+            let drawers = DrawerManager::new();
+            drawers.register(DrawerSpec::main());
+            app.manage(drawers);
+            drawer::load(app.handle().clone());
             let window = app.get_webview_window("main").unwrap();
 
             #[cfg(target_os = "macos")]
@@ -586,68 +738,8 @@ pub fn run() {
                 .on_menu_event(|app: &AppHandle, event: MenuEvent| {
                      match event.id().as_ref() {
                          "show" => {
-                            if let Some(window) = app.get_webview_window("main") {
-                                // Prevent multiple triggers
-                                if IS_ANIMATING.load(Ordering::Relaxed) || IS_DRAWER_OPEN.load(Ordering::Relaxed) {
-                                    return;
-                                }
-
-                                let win_clone = window.clone();
-                                std::thread::spawn(move || {
-                                    IS_ANIMATING.store(true, Ordering::Relaxed);
-                                    
-                                    // Move window to visible position
-                                    let monitor = win_clone.current_monitor().ok().flatten().or_else(|| win_clone.primary_monitor().ok().flatten());
-                                    if let Some(monitor) = monitor {
-                                        let scale_factor = monitor.scale_factor();
-                                        let screen_size = monitor.size();
-                                        let screen_width = screen_size.width as f64;
-                                        let screen_height = screen_size.height as f64;
-                                        
-                                        let width = 400.0 * scale_factor;
-                                        let height = 800.0 * scale_factor;
-                                        
-                                        let active_side = LAST_ACTIVE_SIDE.load(Ordering::Relaxed);
-                                        
-                                        // Calculate start (offscreen) and end (onscreen) positions
-                                        let (start_x, end_x) = if active_side == 1 {
-                                            // Right Side
-                                            (screen_width as i32, (screen_width - width - 20.0 * scale_factor) as i32)
-                                        } else {
-                                            // Left Side
-                                            (-width as i32, (20.0 * scale_factor) as i32)
-                                        };
-                                        
-                                        let center_y = ((screen_height - height) / 2.0) as i32;
-                                        
-                                        // Set initial position off-screen
-                                        win_clone.set_position(PhysicalPosition::new(start_x, center_y)).unwrap_or(());
-                                        win_clone.set_ignore_cursor_events(false).unwrap_or(());
-                                        win_clone.show().unwrap();
-                                        win_clone.set_focus().unwrap();
-
-                                        // Animate
-                                        let duration_ms = 200;
-                                        let steps = 20;
-                                        let step_delay = duration_ms / steps;
-
-                                        for i in 0..=steps {
-                                            let t = i as f64 / steps as f64;
-                                            // Ease out cubic
-                                            let eased_t = 1.0 - (1.0 - t).powi(3);
-                                            let current_x = (start_x as f64 + (end_x as f64 - start_x as f64) * eased_t) as i32;
-                                            
-                                            win_clone.set_position(PhysicalPosition::new(current_x, center_y)).unwrap_or(());
-                                            std::thread::sleep(Duration::from_millis(step_delay));
-                                        }
-                                        
-                                        // Ensure final position
-                                        win_clone.set_position(PhysicalPosition::new(end_x, center_y)).unwrap_or(());
-                                    }
-                                    
-                                    IS_DRAWER_OPEN.store(true, Ordering::Relaxed);
-                                    IS_ANIMATING.store(false, Ordering::Relaxed);
-                                });
+                            if let Some(drawer) = app.state::<DrawerManager>().get(MAIN_DRAWER_ID) {
+                                drawer.show(app, drawer.last_side());
                             }
                          }
                          "quit" => {
@@ -661,164 +753,30 @@ pub fn run() {
             // Start Clipboard Monitor (Rust Background Thread)
             start_clipboard_monitor(app.handle().clone());
 
-            #[cfg(target_os = "macos")]
-            {
-                apply_macos_window_customizations(&window);
+            platform::configure_drawer_window(&window);
+
+            if let Err(e) = drag_snap::build_overlay_window(app.handle()) {
+                eprintln!("Failed to create snap overlay: {}", e);
             }
 
-            // Start Mouse Polling Thread
+            // Pointer events feed the drawer's hot edges and drag-to-snap
+            let (sender, events) = std::sync::mpsc::channel();
+            std::thread::spawn(move || platform::watch_pointer(sender));
             let handle = app.handle().clone();
             std::thread::spawn(move || {
-                loop {
-                    // Check mouse position using CoreGraphics
-                    if let Ok(event) = CGEvent::new(
-                        CGEventSource::new(CGEventSourceStateID::HIDSystemState).unwrap(),
-                    ) {
-                        let point = event.location();
-                        
-                        // Get screen dimensions locally to be responsive
-                        let display_id = unsafe { CGDisplay::main().id };
-                        let display = CGDisplay::new(display_id);
-                        let bounds = display.bounds();
-                        let screen_width = bounds.size.width;
-                        let screen_height = bounds.size.height;
-
-                        let config = DRAWER_CONFIG.load(Ordering::Relaxed);
-                        let mut should_trigger = false;
-                        let mut trigger_side = 0; // 0=Left, 1=Right
-
-                        match config {
-                            0 => { // Left Only
-                                if point.x < 5.0 {
-                                    should_trigger = true;
-                                    trigger_side = 0;
-                                }
-                            },
-                            1 => { // Right Only
-                                if point.x > screen_width - 5.0 {
-                                    should_trigger = true;
-                                    trigger_side = 1;
-                                }
-                            },
-                            2 => { // Hot Corners
-                                // Top-Left or Bottom-Left -> Left Side
-                                if point.x < 5.0 && (point.y < 50.0 || point.y > screen_height - 50.0) {
-                                    should_trigger = true;
-                                    trigger_side = 0;
-                                }
-                                // Top-Right or Bottom-Right -> Right Side
-                                else if point.x > screen_width - 5.0 && (point.y < 50.0 || point.y > screen_height - 50.0) {
-                                    should_trigger = true;
-                                    trigger_side = 1;
-                                }
-                            },
-                            3 => { // Top-Left Only
-                                if point.x < 5.0 && point.y < 50.0 {
-                                    should_trigger = true;
-                                    trigger_side = 0;
-                                }
-                            },
-                            4 => { // Bottom-Left Only
-                                if point.x < 5.0 && point.y > screen_height - 50.0 {
-                                    should_trigger = true;
-                                    trigger_side = 0;
-                                }
-                            },
-                            5 => { // Top-Right Only
-                                if point.x > screen_width - 5.0 && point.y < 50.0 {
-                                    should_trigger = true;
-                                    trigger_side = 1;
-                                }
-                            },
-                            6 => { // Bottom-Right Only
-                                if point.x > screen_width - 5.0 && point.y > screen_height - 50.0 {
-                                    should_trigger = true;
-                                    trigger_side = 1;
-                                }
-                            },
-                            _ => {}
-                        }
-
-                        // Trigger if condition met AND drawer is closed AND not animating
-                        if should_trigger
-                            && !IS_DRAWER_OPEN.load(Ordering::Relaxed)
-                            && !IS_ANIMATING.load(Ordering::Relaxed)
-                        {
-                            LAST_ACTIVE_SIDE.store(trigger_side, Ordering::Relaxed);
-                            
-                            if let Some(window) = handle.get_webview_window("main") {
-                                IS_ANIMATING.store(true, Ordering::Relaxed);
-                                let win_clone = window.clone();
-
-                                std::thread::spawn(move || {
-                                    // Disable click-through before showing
-                                    win_clone.set_ignore_cursor_events(false).unwrap_or(());
-
-                                    // Show window before animating
-                                    win_clone.show().unwrap_or(());
-                                    win_clone.set_focus().unwrap_or(());
-
-                                    let monitor = win_clone
-                                        .current_monitor()
-                                        .ok()
-                                        .flatten()
-                                        .or_else(|| win_clone.primary_monitor().ok().flatten());
-                                    let scale_factor =
-                                        monitor.as_ref().map(|m| m.scale_factor()).unwrap_or(2.0);
-                                    let screen_height =
-                                        monitor.as_ref().map(|m| m.size().height).unwrap_or(2160);
-                                    let screen_width =
-                                        monitor.as_ref().map(|m| m.size().width).unwrap_or(3840) as f64;
-
-                                    let width = 400.0 * scale_factor;
-                                    let height = 800.0 * scale_factor; // Window height from config
-                                    
-                                    // Determine animation start/end based on trigger_side
-                                    let (start_x, end_x) = if trigger_side == 1 {
-                                        // Right Side
-                                        // Start: screen_width (offscreen right)
-                                        // End: screen_width - width - 30 (visible)
-                                        (screen_width as i32, (screen_width - width - 20.0 * scale_factor) as i32)
-                                    } else {
-                                        // Left Side
-                                        // Start: -width (offscreen left)
-                                        // End: 30 (visible)
-                                        (-width as i32, (20.0 * scale_factor) as i32)
-                                    };
-
-                                    let center_y = ((screen_height as f64 - height) / 2.0) as i32;
-
-                                    let duration_ms = 200;
-                                    let steps = 20;
-                                    let step_delay = duration_ms / steps;
-
-                                    for i in 0..=steps {
-                                        let t = i as f64 / steps as f64;
-                                        // Ease out cubic
-                                        let eased_t = 1.0 - (1.0 - t).powi(3);
-                                        let current_x = (start_x as f64
-                                            + (end_x as f64 - start_x as f64) * eased_t)
-                                            as i32;
-
-                                        win_clone
-                                            .set_position(PhysicalPosition::new(
-                                                current_x, center_y,
-                                            ))
-                                            .unwrap_or(());
-                                        std::thread::sleep(Duration::from_millis(step_delay));
-                                    }
-
-                                    win_clone
-                                        .set_position(PhysicalPosition::new(end_x, center_y))
-                                        .unwrap_or(());
-
-                                    IS_DRAWER_OPEN.store(true, Ordering::Relaxed);
-                                    IS_ANIMATING.store(false, Ordering::Relaxed);
-                                });
-                            }
-                        }
+                for event in events {
+                    // Dragging a window to an edge shouldn't open the drawer
+                    if event.kind == platform::PointerEventKind::Move {
+                        let pointer = event.location;
+                        handle.state::<DrawerManager>().handle_pointer(
+                            &handle,
+                            pointer.x,
+                            pointer.y,
+                            pointer.screen_width,
+                            pointer.screen_height,
+                        );
                     }
-                    std::thread::sleep(Duration::from_millis(50));
+                    drag_snap::handle_pointer(&handle, event);
                 }
             });
 
@@ -843,15 +801,63 @@ pub fn run() {
             get_app_icon,
             launch_app,
             get_windows,
+            list_windows,
+            get_ignored_apps,
+            set_ignored_apps,
+            search_windows,
+            focus_window,
+            drag_snap::get_drag_snap_settings,
+            drag_snap::set_drag_snap_settings,
+            window_moves::move_window,
+            window_moves::bind_window_shortcut,
+            window_moves::unbind_window_shortcut,
+            window_rules::list_window_rules,
+            window_rules::save_window_rule,
+            window_rules::delete_window_rule,
+            window_rules::preview_window_rules,
+            user_scripts::list_user_scripts,
+            user_scripts::add_user_script,
+            user_scripts::toggle_user_script,
+            user_scripts::delete_user_script,
+            content_blocker::get_content_blocker_settings,
+            content_blocker::get_content_blocker_status,
+            content_blocker::set_content_blocker_settings,
+            content_blocker::set_site_blocking,
+            content_blocker::update_filter_lists,
+            page_bridge::get_bridge_permissions,
+            page_bridge::set_bridge_permission,
+            page_bridge::resolve_bridge_request,
             restore_layout,
             snap_active_window,
             apply_preset_layout,
-            check_accessibility_permission,
-            request_accessibility_permission,
+            undo_layout,
+            redo_layout,
+            permissions::check_accessibility_permission,
+            permissions::request_accessibility_permission,
             fetch_webpage,
             set_ignore_mouse_events,
-            hide_drawer,
-            set_drawer_config,
+            drawer::set_drawer_config,
+            drawer::show_drawer,
+            drawer::hide_drawer,
+            drawer::list_drawers,
+            drawer::create_drawer,
+            drawer::remove_drawer,
+            layout_presets::list_layout_presets,
+            layout_presets::save_layout_preset,
+            layout_presets::delete_layout_preset,
+            workspace::capture_workspace,
+            workspace::list_workspaces,
+            workspace::delete_workspace,
+            workspace::restore_workspace,
+            snap::get_snap_settings,
+            snap::set_snap_settings,
+            tiling::get_tiling_mode,
+            tiling::set_tiling_mode,
+            tiling::tiling_rotate,
+            tiling::tiling_swap,
+            tiling::tiling_promote,
+            tiling::tiling_resize,
+            display_layouts::get_display_arrangement,
             web_blanket::web_blanket_show,
             web_blanket::web_blanket_hide,
             web_blanket::web_blanket_set_bounds,
@@ -872,29 +878,3 @@ pub fn run() {
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
-
-#[cfg(target_os = "macos")]
-fn apply_macos_window_customizations(window: &tauri::WebviewWindow) {
-    use cocoa::appkit::{NSView, NSWindow};
-    use cocoa::base::{id, nil};
-    use objc::{class, msg_send, sel, sel_impl};
-
-    let ns_window = window.ns_window().unwrap() as id;
-    unsafe {
-        let content_view = ns_window.contentView();
-        let _: () = msg_send![content_view, setWantsLayer: true];
-        let layer = content_view.layer();
-        let _: () = msg_send![layer, setCornerRadius: 16.0_f64];
-        let _: () = msg_send![layer, setMasksToBounds: true];
-        let _: () = msg_send![ns_window, setOpaque: false];
-        let clear_color: id = msg_send![class!(NSColor), clearColor];
-        let _: () = msg_send![ns_window, setBackgroundColor: clear_color];
-        let _: () = msg_send![ns_window, setHasShadow: false];
-        // Initially ignore mouse events so user can click through
-        let _: () = msg_send![ns_window, setIgnoresMouseEvents: true];
-        // Ensure it floats above
-        let _: () = msg_send![ns_window, setLevel: 5]; // kCGFloatingWindowLevel
-                                                       // Allow joining all spaces (desktops)
-        let _: () = msg_send![ns_window, setCollectionBehavior: 1]; // NSWindowCollectionBehaviorCanJoinAllSpaces
-    }
-}
@@ -0,0 +1,547 @@
+//! The `window.myDrawer` API web blanket pages and user scripts can call.
+//!
+//! Calls arrive as JSON through a WebKit script message handler, tagged with
+//! the origin WebKit reports for the sending frame, never one the page claims.
+//! Each origin needs the user's permission per action. Handled calls are
+//! emitted as `web-blanket:bridge` events and the page's promise is settled
+//! with the outcome. Only WKWebView tabs have the bridge.
+
+use crate::error::AppError;
+use crate::settings::open_db;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager};
+
+pub const BRIDGE_EVENT: &str = "web-blanket:bridge";
+/// Name of the message handler, i.e. `window.webkit.messageHandlers.myDrawer`
+pub const MESSAGE_HANDLER: &str = "myDrawer";
+pub const BRIDGE_SCRIPT: &str = include_str!("../scripts/bridge.js");
+
+const SETTINGS_KEY: &str = "page_bridge_permissions";
+const MAX_TEXT_LEN: usize = 100_000;
+const MAX_TITLE_LEN: usize = 200;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum BridgeAction {
+    CopyText,
+    SaveToNotes,
+    SendToChat,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "action", rename_all = "camelCase")]
+pub enum BridgeRequest {
+    CopyText { text: String },
+    SaveToNotes { title: Option<String>, content: String },
+    SendToChat { text: String },
+}
+
+impl BridgeRequest {
+    pub fn action(&self) -> BridgeAction {
+        match self {
+            BridgeRequest::CopyText { .. } => BridgeAction::CopyText,
+            BridgeRequest::SaveToNotes { .. } => BridgeAction::SaveToNotes,
+            BridgeRequest::SendToChat { .. } => BridgeAction::SendToChat,
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let (text, title) = match self {
+            BridgeRequest::CopyText { text } | BridgeRequest::SendToChat { text } => (text, None),
+            BridgeRequest::SaveToNotes { title, content } => (content, title.as_ref()),
+        };
+        if text.trim().is_empty() {
+            return Err("Nothing to send".into());
+        }
+        if text.len() > MAX_TEXT_LEN {
+            return Err(format!("Text is longer than {} bytes", MAX_TEXT_LEN));
+        }
+        if title.is_some_and(|title| title.chars().count() > MAX_TITLE_LEN) {
+            return Err(format!("Title is longer than {} characters", MAX_TITLE_LEN));
+        }
+        Ok(())
+    }
+}
+
+/// Reads a message posted by the bridge script: its call id and the request,
+/// or why the request was rejected. `None` when there's no call to reply to.
+pub fn parse_call(body: &str) -> Option<(u64, Result<BridgeRequest, String>)> {
+    let value: serde_json::Value = serde_json::from_str(body).ok()?;
+    let id = value.get("id")?.as_u64()?;
+    let request = serde_json::from_value::<BridgeRequest>(value)
+        .map_err(|e| format!("Invalid request: {}", e))
+        .and_then(|request| request.validate().map(|_| request));
+    Some((id, request))
+}
+
+/// `scheme://host[:port]` for http(s) pages, with default ports left out.
+pub fn format_origin(protocol: &str, host: &str, port: u16) -> Option<String> {
+    let protocol = protocol.to_ascii_lowercase();
+    let default_port = match protocol.as_str() {
+        "http" => 80,
+        "https" => 443,
+        _ => return None,
+    };
+    if host.is_empty() {
+        return None;
+    }
+    let host = host.to_ascii_lowercase();
+    Some(if port == 0 || port == default_port {
+        format!("{}://{}", protocol, host)
+    } else {
+        format!("{}://{}:{}", protocol, host, port)
+    })
+}
+
+fn origin_host(origin: &str) -> &str {
+    origin.split_once("://").map_or(origin, |(_, host)| host)
+}
+
+/// The note saved for `content` clipped from `url`.
+pub fn note_content(content: &str, url: &str) -> String {
+    let content = content.trim();
+    if url.is_empty() || content == url {
+        content.to_string()
+    } else {
+        format!("{}\n\n{}", content, url)
+    }
+}
+
+/// Settles call `id` in the page: `error` rejects it, `None` resolves it.
+pub fn reply_script(id: u64, error: Option<&str>) -> String {
+    let error = serde_json::to_string(&error).unwrap_or_else(|_| "null".into());
+    format!("window.__myDrawerReply && window.__myDrawerReply({}, {});", id, error)
+}
+
+/// The user's answers per origin. Origins or actions missing from it are asked about.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct BridgePermissions(HashMap<String, HashMap<BridgeAction, bool>>);
+
+impl BridgePermissions {
+    pub fn get(&self, origin: &str, action: BridgeAction) -> Option<bool> {
+        self.0.get(origin)?.get(&action).copied()
+    }
+
+    /// Records the answer for `action` on `origin`, or forgets it on `None`.
+    pub fn set(&mut self, origin: &str, action: BridgeAction, allowed: Option<bool>) {
+        match allowed {
+            Some(allowed) => {
+                self.0.entry(origin.to_string()).or_default().insert(action, allowed);
+            }
+            None => {
+                if let Some(actions) = self.0.get_mut(origin) {
+                    actions.remove(&action);
+                    if actions.is_empty() {
+                        self.0.remove(origin);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds the answers in `saved` for origins and actions not answered here.
+    pub fn merge(&mut self, saved: BridgePermissions) {
+        for (origin, actions) in saved.0 {
+            let answers = self.0.entry(origin).or_default();
+            for (action, allowed) in actions {
+                answers.entry(action).or_insert(allowed);
+            }
+        }
+    }
+}
+
+/// A call from a page, with what WebKit told us about where it came from.
+#[derive(Debug, Clone)]
+struct PageCall {
+    /// Label of the window showing the tab; its events go only there
+    window_label: String,
+    tab_id: String,
+    call_id: u64,
+    origin: String,
+    url: String,
+    request: BridgeRequest,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BridgeEvent {
+    pub tab_id: String,
+    pub origin: String,
+    pub url: String,
+    #[serde(flatten)]
+    pub kind: BridgeEventKind,
+}
+
+#[derive(Debug, Serialize, Clone)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum BridgeEventKind {
+    /// The origin hasn't been allowed or denied `action` yet. Answer with
+    /// `resolve_bridge_request`.
+    PermissionRequest { request_id: u64, action: BridgeAction },
+    CopiedText { text: String },
+    SavedNote { note_id: String, title: String },
+    SendToChat { text: String },
+}
+
+#[derive(Default)]
+pub struct PageBridge {
+    permissions: Mutex<BridgePermissions>,
+    // Calls waiting on the user, by request id
+    pending: Mutex<HashMap<u64, PageCall>>,
+    next_request_id: AtomicU64,
+}
+
+impl PageBridge {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn permissions(&self) -> BridgePermissions {
+        self.permissions.lock().unwrap().clone()
+    }
+
+    /// Drops the calls of a closed tab that were waiting on the user.
+    pub fn forget_tab(&self, tab_id: &str) {
+        self.pending.lock().unwrap().retain(|_, call| call.tab_id != tab_id);
+    }
+}
+
+fn emit(app: &AppHandle, call: &PageCall, kind: BridgeEventKind) {
+    let event = BridgeEvent {
+        tab_id: call.tab_id.clone(),
+        origin: call.origin.clone(),
+        url: call.url.clone(),
+        kind,
+    };
+    let _ = app.emit_to(call.window_label.as_str(), BRIDGE_EVENT, event);
+}
+
+fn reply(app: &AppHandle, tab_id: &str, call_id: u64, error: Option<&str>) {
+    crate::web_blanket::evaluate_in_tab(app, tab_id, reply_script(call_id, error));
+}
+
+/// Handles a message posted to the bridge by the main frame of tab `tab_id`,
+/// shown in window `window_label`.
+pub fn receive(app: &AppHandle, window_label: String, tab_id: String, origin: String, url: String, body: &str) {
+    let Some((call_id, request)) = parse_call(body) else {
+        return;
+    };
+    let request = match request {
+        Ok(request) => request,
+        Err(reason) => {
+            reply(app, &tab_id, call_id, Some(&reason));
+            return;
+        }
+    };
+    let call = PageCall {
+        window_label,
+        tab_id,
+        call_id,
+        origin,
+        url,
+        request,
+    };
+
+    let bridge = app.state::<PageBridge>();
+    let action = call.request.action();
+    match bridge.permissions().get(&call.origin, action) {
+        Some(true) => dispatch(app, call),
+        Some(false) => reply(app, &call.tab_id, call.call_id, Some("Not allowed")),
+        None => {
+            let mut pending = bridge.pending.lock().unwrap();
+            let already_asked = pending
+                .values()
+                .any(|p| p.tab_id == call.tab_id && p.origin == call.origin && p.request.action() == action);
+            if already_asked {
+                drop(pending);
+                reply(app, &call.tab_id, call.call_id, Some("Waiting for permission"));
+                return;
+            }
+
+            let request_id = bridge.next_request_id.fetch_add(1, Ordering::Relaxed) + 1;
+            pending.insert(request_id, call.clone());
+            drop(pending);
+            emit(app, &call, BridgeEventKind::PermissionRequest { request_id, action });
+        }
+    }
+}
+
+/// Runs an allowed call and settles it in the page.
+fn dispatch(app: &AppHandle, call: PageCall) {
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        match run(&app, &call).await {
+            Ok(kind) => {
+                emit(&app, &call, kind);
+                reply(&app, &call.tab_id, call.call_id, None);
+            }
+            Err(e) => {
+                eprintln!("Bridge call from {} failed: {}", call.origin, e);
+                reply(&app, &call.tab_id, call.call_id, Some(&e.to_string()));
+            }
+        }
+    });
+}
+
+async fn run(app: &AppHandle, call: &PageCall) -> Result<BridgeEventKind, AppError> {
+    match &call.request {
+        BridgeRequest::CopyText { text } => {
+            let pool = open_db(app).await?;
+            let added = crate::add_clipboard_entry(&pool, text, origin_host(&call.origin), text.len() as i32).await;
+            pool.close().await;
+            added.map_err(|e| e.to_string())?;
+
+            let _ = app.emit("clipboard-changed", ());
+            Ok(BridgeEventKind::CopiedText { text: text.clone() })
+        }
+        BridgeRequest::SaveToNotes { title, content } => {
+            let title = title
+                .as_deref()
+                .map(str::trim)
+                .filter(|title| !title.is_empty())
+                .unwrap_or_else(|| origin_host(&call.origin))
+                .to_string();
+            let note_id = uuid::Uuid::new_v4().to_string();
+            let now = chrono::Utc::now().to_rfc3339();
+
+            let pool = open_db(app).await?;
+            let saved = sqlx::query("INSERT INTO notes (id, title, content, updated_at, created_at) VALUES (?, ?, ?, ?, ?)")
+                .bind(&note_id)
+                .bind(&title)
+                .bind(note_content(content, &call.url))
+                .bind(&now)
+                .bind(&now)
+                .execute(&pool)
+                .await;
+            pool.close().await;
+            saved.map_err(|e| e.to_string())?;
+
+            Ok(BridgeEventKind::SavedNote { note_id, title })
+        }
+        BridgeRequest::SendToChat { text } => Ok(BridgeEventKind::SendToChat { text: text.clone() }),
+    }
+}
+
+async fn save_permissions(app: &AppHandle, permissions: &BridgePermissions) -> Result<(), AppError> {
+    let value = serde_json::to_string(permissions).map_err(|e| e.to_string())?;
+    let pool = open_db(app).await?;
+    let saved = sqlx::query("INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES (?, ?, ?)")
+        .bind(SETTINGS_KEY)
+        .bind(&value)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .execute(&pool)
+        .await;
+    pool.close().await;
+    saved.map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+pub fn load(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let loaded: Result<Option<(String,)>, AppError> = async {
+            let pool = open_db(&app).await?;
+            let row = sqlx::query_as("SELECT value FROM settings WHERE key = ?")
+                .bind(SETTINGS_KEY)
+                .fetch_optional(&pool)
+                .await;
+            pool.close().await;
+            let row = row.map_err(|e| e.to_string())?;
+            Ok(row)
+        }
+        .await;
+
+        match loaded {
+            Ok(row) => {
+                let saved = row.and_then(|(value,)| serde_json::from_str(&value).ok()).unwrap_or_default();
+                // Answers given while this was loading win, and were saved without the rest
+                let (merged, answered_early) = {
+                    let mut permissions = app.state::<PageBridge>().permissions.lock().unwrap();
+                    let answered_early = *permissions != BridgePermissions::default();
+                    permissions.merge(saved);
+                    (permissions.clone(), answered_early)
+                };
+                if answered_early {
+                    if let Err(e) = save_permissions(&app, &merged).await {
+                        eprintln!("Failed to save bridge permissions: {}", e);
+                    }
+                }
+            }
+            Err(e) => eprintln!("Failed to load bridge permissions: {}", e),
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_bridge_permissions(bridge: tauri::State<'_, PageBridge>) -> BridgePermissions {
+    bridge.permissions()
+}
+
+/// Allows or denies `action` for `origin`, or forgets the answer on `None`
+/// so the page is asked again.
+#[tauri::command]
+pub async fn set_bridge_permission(
+    app: AppHandle,
+    origin: String,
+    action: BridgeAction,
+    allowed: Option<bool>,
+) -> Result<BridgePermissions, AppError> {
+    let permissions = {
+        let bridge = app.state::<PageBridge>();
+        let mut permissions = bridge.permissions.lock()?;
+        permissions.set(&origin, action, allowed);
+        permissions.clone()
+    };
+    save_permissions(&app, &permissions).await?;
+    Ok(permissions)
+}
+
+/// Answers a `permissionRequest` event. With `remember` the answer sticks for
+/// the origin, otherwise it only covers this call.
+#[tauri::command]
+pub async fn resolve_bridge_request(
+    app: AppHandle,
+    request_id: u64,
+    allowed: bool,
+    remember: bool,
+) -> Result<(), AppError> {
+    let call = app
+        .state::<PageBridge>()
+        .pending
+        .lock()?
+        .remove(&request_id)
+        .ok_or(AppError::BridgeRequestNotFound { request_id })?;
+
+    if remember {
+        set_bridge_permission(app.clone(), call.origin.clone(), call.request.action(), Some(allowed)).await?;
+    }
+    if allowed {
+        dispatch(&app, call);
+    } else {
+        reply(&app, &call.tab_id, call.call_id, Some("Not allowed"));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn calls_are_parsed_and_validated() {
+        assert_eq!(
+            parse_call(r#"{"id":3,"action":"copyText","text":"hello"}"#),
+            Some((3, Ok(BridgeRequest::CopyText { text: "hello".into() })))
+        );
+        assert_eq!(
+            parse_call(r#"{"id":4,"action":"saveToNotes","content":"clip"}"#),
+            Some((4, Ok(BridgeRequest::SaveToNotes { title: None, content: "clip".into() })))
+        );
+        assert_eq!(parse_call(r#"{"id":5,"action":"sendToChat","text":"  "}"#), Some((5, Err("Nothing to send".into()))));
+        assert!(matches!(parse_call(r#"{"id":6,"action":"deleteEverything"}"#), Some((6, Err(_)))));
+
+        let long = "a".repeat(MAX_TEXT_LEN + 1);
+        let body = serde_json::json!({ "id": 7, "action": "copyText", "text": long }).to_string();
+        assert!(matches!(parse_call(&body), Some((7, Err(_)))));
+
+        // Without an id there's no call to reject
+        assert_eq!(parse_call(r#"{"action":"copyText","text":"hello"}"#), None);
+        assert_eq!(parse_call("not json"), None);
+    }
+
+    #[test]
+    fn origins_leave_out_default_ports_and_other_schemes() {
+        assert_eq!(format_origin("https", "Example.com", 0).as_deref(), Some("https://example.com"));
+        assert_eq!(format_origin("https", "example.com", 443).as_deref(), Some("https://example.com"));
+        assert_eq!(format_origin("http", "localhost", 8080).as_deref(), Some("http://localhost:8080"));
+        assert_eq!(format_origin("file", "", 0), None);
+        assert_eq!(format_origin("about", "", 0), None);
+        assert_eq!(format_origin("https", "", 0), None);
+    }
+
+    #[test]
+    fn permissions_are_per_origin_and_action() {
+        let mut permissions = BridgePermissions::default();
+        permissions.set("https://example.com", BridgeAction::CopyText, Some(true));
+        permissions.set("https://example.com", BridgeAction::SendToChat, Some(false));
+
+        assert_eq!(permissions.get("https://example.com", BridgeAction::CopyText), Some(true));
+        assert_eq!(permissions.get("https://example.com", BridgeAction::SendToChat), Some(false));
+        assert_eq!(permissions.get("https://example.com", BridgeAction::SaveToNotes), None);
+        assert_eq!(permissions.get("http://example.com", BridgeAction::CopyText), None);
+
+        let json = serde_json::to_value(&permissions).unwrap();
+        assert_eq!(json["https://example.com"]["copyText"], true);
+        assert_eq!(serde_json::from_value::<BridgePermissions>(json).unwrap(), permissions);
+
+        permissions.set("https://example.com", BridgeAction::CopyText, None);
+        permissions.set("https://example.com", BridgeAction::SendToChat, None);
+        assert_eq!(permissions, BridgePermissions::default());
+    }
+
+    #[test]
+    fn merging_keeps_answers_already_given() {
+        let mut saved = BridgePermissions::default();
+        saved.set("https://example.com", BridgeAction::CopyText, Some(true));
+        saved.set("https://example.com", BridgeAction::SendToChat, Some(true));
+        saved.set("https://other.com", BridgeAction::SaveToNotes, Some(false));
+
+        let mut permissions = BridgePermissions::default();
+        permissions.set("https://example.com", BridgeAction::CopyText, Some(false));
+        permissions.merge(saved);
+
+        assert_eq!(permissions.get("https://example.com", BridgeAction::CopyText), Some(false));
+        assert_eq!(permissions.get("https://example.com", BridgeAction::SendToChat), Some(true));
+        assert_eq!(permissions.get("https://other.com", BridgeAction::SaveToNotes), Some(false));
+    }
+
+    #[test]
+    fn replies_and_events_serialize_for_the_page_and_frontend() {
+        assert_eq!(reply_script(2, None), "window.__myDrawerReply && window.__myDrawerReply(2, null);");
+        assert_eq!(
+            reply_script(3, Some("Not \"allowed\"")),
+            r#"window.__myDrawerReply && window.__myDrawerReply(3, "Not \"allowed\"");"#
+        );
+
+        let event = BridgeEvent {
+            tab_id: "t1".into(),
+            origin: "https://example.com".into(),
+            url: "https://example.com/a".into(),
+            kind: BridgeEventKind::PermissionRequest { request_id: 1, action: BridgeAction::SaveToNotes },
+        };
+        assert_eq!(
+            serde_json::to_value(event).unwrap(),
+            serde_json::json!({
+                "tabId": "t1",
+                "origin": "https://example.com",
+                "url": "https://example.com/a",
+                "type": "permissionRequest",
+                "requestId": 1,
+                "action": "saveToNotes"
+            })
+        );
+
+        assert_eq!(note_content(" clip \n", "https://example.com/a"), "clip\n\nhttps://example.com/a");
+        assert_eq!(note_content("https://example.com/a", "https://example.com/a"), "https://example.com/a");
+    }
+
+    #[test]
+    fn closing_a_tab_drops_its_waiting_calls() {
+        let call = |tab_id: &str| PageCall {
+            window_label: "main".into(),
+            tab_id: tab_id.into(),
+            call_id: 1,
+            origin: "https://example.com".into(),
+            url: "https://example.com/a".into(),
+            request: BridgeRequest::CopyText { text: "hi".into() },
+        };
+        let bridge = PageBridge::new();
+        bridge.pending.lock().unwrap().extend([(1, call("t1")), (2, call("t2")), (3, call("t1"))]);
+
+        bridge.forget_tab("t1");
+        let left: Vec<u64> = bridge.pending.lock().unwrap().keys().copied().collect();
+        assert_eq!(left, vec![2]);
+    }
+}
@@ -0,0 +1,99 @@
+//! Accessibility permission, which macOS requires for moving other apps'
+//! windows. It can be granted or revoked in System Settings at any time, so it
+//! is watched rather than checked once.
+
+use crate::window_backend::WindowError;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+// Trust changes are rare and only user-initiated
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Whether the process may use the Accessibility API. Always true where no
+/// such permission exists.
+pub fn is_trusted() -> bool {
+    #[cfg(target_os = "macos")]
+    unsafe {
+        accessibility_sys::AXIsProcessTrusted()
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        true
+    }
+}
+
+/// The last known trust state.
+pub struct AccessibilityPermission {
+    trusted: Mutex<bool>,
+}
+
+impl Default for AccessibilityPermission {
+    fn default() -> Self {
+        Self {
+            trusted: Mutex::new(is_trusted()),
+        }
+    }
+}
+
+impl AccessibilityPermission {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `trusted`. True if that is a change.
+    pub fn update(&self, trusted: bool) -> bool {
+        let mut current = self.trusted.lock().unwrap();
+        let changed = *current != trusted;
+        *current = trusted;
+        changed
+    }
+
+    /// Fails with `PermissionDenied` unless the permission is granted right now.
+    /// Checks live rather than waiting for the watcher to notice a change.
+    pub fn require(&self) -> Result<(), WindowError> {
+        if is_trusted() {
+            Ok(())
+        } else {
+            Err(WindowError::PermissionDenied)
+        }
+    }
+}
+
+/// Re-checks the permission, emitting `accessibility-permission-changed` with
+/// the new state when it changed.
+fn refresh(app: &AppHandle) -> bool {
+    let trusted = is_trusted();
+    if app.state::<AccessibilityPermission>().update(trusted) {
+        let _ = app.emit("accessibility-permission-changed", trusted);
+    }
+    trusted
+}
+
+pub fn spawn_watcher(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(POLL_INTERVAL);
+        refresh(&app);
+    });
+}
+
+#[tauri::command]
+pub fn check_accessibility_permission(app: AppHandle) -> bool {
+    refresh(&app)
+}
+
+/// Shows the system prompt that leads to the Accessibility settings. The
+/// watcher reports when the user grants access.
+#[tauri::command]
+pub fn request_accessibility_permission() {
+    #[cfg(target_os = "macos")]
+    unsafe {
+        use core_foundation::base::TCFType;
+
+        let options = core_foundation::dictionary::CFDictionary::from_CFType_pairs(&[(
+            core_foundation::string::CFString::new("AXTrustedCheckOptionPrompt"),
+            core_foundation::boolean::CFBoolean::true_value(),
+        )]);
+        accessibility_sys::AXIsProcessTrustedWithOptions(options.as_concrete_TypeRef());
+    }
+}
@@ -0,0 +1,107 @@
+use super::{PointerEvent, PointerEventKind, PointerLocation};
+use std::sync::mpsc::Sender;
+use tauri::window::Color;
+use x11rb::connection::Connection;
+use x11rb::protocol::xinput::{ConnectionExt as _, Device, EventMask, XIEventMask};
+use x11rb::protocol::xproto::{ConnectionExt, Window};
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+
+const LEFT_BUTTON: u32 = 1;
+
+/// Reads the pointer through a dedicated X11 connection. Works on any X server,
+/// including Xvfb and XWayland.
+pub struct PointerTracker {
+    conn: RustConnection,
+    root: Window,
+}
+
+impl PointerTracker {
+    pub fn new() -> Option<Self> {
+        let (conn, screen_num) = match x11rb::connect(None) {
+            Ok(c) => c,
+            Err(e) => {
+                eprintln!("Failed to connect to X server: {}", e);
+                return None;
+            }
+        };
+        let root = conn.setup().roots[screen_num].root;
+        Some(Self { conn, root })
+    }
+
+    pub fn location(&self) -> Option<PointerLocation> {
+        let pointer = self.conn.query_pointer(self.root).ok()?.reply().ok()?;
+        let geometry = self.conn.get_geometry(self.root).ok()?.reply().ok()?;
+
+        Some(PointerLocation {
+            x: pointer.root_x as f64,
+            y: pointer.root_y as f64,
+            screen_width: geometry.width as f64,
+            screen_height: geometry.height as f64,
+        })
+    }
+}
+
+/// XInput2 raw events on the root window. Unlike core events they keep coming
+/// while the window manager grabs the pointer to move a window, but only to
+/// clients announcing XI 2.1 or later. Blocks.
+pub(super) fn listen_pointer(events: &Sender<PointerEvent>) -> Result<(), String> {
+    let tracker = PointerTracker::new().ok_or("no X server")?;
+    let conn = &tracker.conn;
+
+    let version = conn
+        .xinput_xi_query_version(2, 2)
+        .map_err(|e| e.to_string())?
+        .reply()
+        .map_err(|e| format!("XInput2 missing: {}", e))?;
+    if (version.major_version, version.minor_version) < (2, 1) {
+        return Err(format!(
+            "XInput {}.{} is too old, raw events during grabs need 2.1",
+            version.major_version, version.minor_version
+        ));
+    }
+    let mask = EventMask {
+        deviceid: Device::ALL_MASTER.into(),
+        mask: vec![XIEventMask::RAW_MOTION | XIEventMask::RAW_BUTTON_PRESS | XIEventMask::RAW_BUTTON_RELEASE],
+    };
+    conn.xinput_xi_select_events(tracker.root, &[mask])
+        .map_err(|e| e.to_string())?
+        .check()
+        .map_err(|e| e.to_string())?;
+
+    let mut pressed = false;
+    loop {
+        let kind = match conn.wait_for_event().map_err(|e| e.to_string())? {
+            Event::XinputRawMotion(_) if pressed => PointerEventKind::Drag,
+            Event::XinputRawMotion(_) => PointerEventKind::Move,
+            Event::XinputRawButtonPress(e) if e.detail == LEFT_BUTTON => {
+                pressed = true;
+                PointerEventKind::Press
+            }
+            Event::XinputRawButtonRelease(e) if e.detail == LEFT_BUTTON => {
+                pressed = false;
+                PointerEventKind::Release
+            }
+            _ => continue,
+        };
+        // Raw events carry deltas only; the position comes from the server
+        let Some(location) = tracker.location() else {
+            continue;
+        };
+        if events.send(PointerEvent { kind, location }).is_err() {
+            return Ok(());
+        }
+    }
+}
+
+pub fn configure_drawer_window(window: &tauri::WebviewWindow) {
+    // Transparency needs a compositing window manager; the window itself is
+    // created with `transparent: true`, so only the background is cleared here.
+    window.set_background_color(Some(Color(0, 0, 0, 0))).unwrap_or(());
+    window.set_always_on_top(true).unwrap_or(());
+    // Sets _NET_WM_STATE_STICKY so the drawer follows the user across workspaces
+    window.set_visible_on_all_workspaces(true).unwrap_or(());
+    window.set_skip_taskbar(true).unwrap_or(());
+    // Initially ignore mouse events so user can click through
+    window.set_ignore_cursor_events(true).unwrap_or(());
+}
@@ -0,0 +1,99 @@
+use super::{PointerEvent, PointerEventKind, PointerLocation};
+use core_foundation::runloop::CFRunLoop;
+use core_graphics::display::CGDisplay;
+use core_graphics::event::{
+    CGEvent, CGEventTap, CGEventTapLocation, CGEventTapOptions, CGEventTapPlacement, CGEventType,
+    CallbackResult,
+};
+use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+use core_graphics::geometry::CGPoint;
+use std::sync::mpsc::Sender;
+
+pub struct PointerTracker;
+
+impl PointerTracker {
+    pub fn new() -> Option<Self> {
+        Some(Self)
+    }
+
+    pub fn location(&self) -> Option<PointerLocation> {
+        // Check mouse position using CoreGraphics
+        let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState).ok()?;
+        let event = CGEvent::new(source).ok()?;
+        Some(pointer_location(event.location()))
+    }
+}
+
+fn pointer_location(point: CGPoint) -> PointerLocation {
+    // Get screen dimensions locally to be responsive
+    let display_id = unsafe { CGDisplay::main().id };
+    let bounds = CGDisplay::new(display_id).bounds();
+
+    PointerLocation {
+        x: point.x,
+        y: point.y,
+        screen_width: bounds.size.width,
+        screen_height: bounds.size.height,
+    }
+}
+
+/// Listen-only event tap on the HID stream; blocks running this thread's run
+/// loop. The callback only forwards events so the tap never times out.
+pub(super) fn listen_pointer(events: &Sender<PointerEvent>) -> Result<(), String> {
+    let interest = vec![
+        CGEventType::MouseMoved,
+        CGEventType::LeftMouseDown,
+        CGEventType::LeftMouseDragged,
+        CGEventType::LeftMouseUp,
+    ];
+
+    CGEventTap::with_enabled(
+        CGEventTapLocation::HID,
+        CGEventTapPlacement::TailAppendEventTap,
+        CGEventTapOptions::ListenOnly,
+        interest,
+        |_proxy, event_type, event| {
+            let kind = match event_type {
+                CGEventType::MouseMoved => Some(PointerEventKind::Move),
+                CGEventType::LeftMouseDown => Some(PointerEventKind::Press),
+                CGEventType::LeftMouseDragged => Some(PointerEventKind::Drag),
+                CGEventType::LeftMouseUp => Some(PointerEventKind::Release),
+                _ => None,
+            };
+            if let Some(kind) = kind {
+                let location = pointer_location(event.location());
+                if events.send(PointerEvent { kind, location }).is_err() {
+                    CFRunLoop::get_current().stop();
+                }
+            }
+            CallbackResult::Keep
+        },
+        CFRunLoop::run_current,
+    )
+    .map_err(|_| "could not create an event tap".to_string())
+}
+
+pub fn configure_drawer_window(window: &tauri::WebviewWindow) {
+    use cocoa::appkit::{NSView, NSWindow};
+    use cocoa::base::id;
+    use objc::{class, msg_send, sel, sel_impl};
+
+    let ns_window = window.ns_window().unwrap() as id;
+    unsafe {
+        let content_view = ns_window.contentView();
+        let _: () = msg_send![content_view, setWantsLayer: true];
+        let layer = content_view.layer();
+        let _: () = msg_send![layer, setCornerRadius: 16.0_f64];
+        let _: () = msg_send![layer, setMasksToBounds: true];
+        let _: () = msg_send![ns_window, setOpaque: false];
+        let clear_color: id = msg_send![class!(NSColor), clearColor];
+        let _: () = msg_send![ns_window, setBackgroundColor: clear_color];
+        let _: () = msg_send![ns_window, setHasShadow: false];
+        // Initially ignore mouse events so user can click through
+        let _: () = msg_send![ns_window, setIgnoresMouseEvents: true];
+        // Ensure it floats above
+        let _: () = msg_send![ns_window, setLevel: 5]; // kCGFloatingWindowLevel
+        // Allow joining all spaces (desktops)
+        let _: () = msg_send![ns_window, setCollectionBehavior: 1]; // NSWindowCollectionBehaviorCanJoinAllSpaces
+    }
+}
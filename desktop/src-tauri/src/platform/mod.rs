@@ -0,0 +1,94 @@
+//! Platform-specific pieces of the drawer: pointer tracking for the hot edges
+//! and drag-to-snap, and the native window tweaks (transparency, level,
+//! all-workspaces).
+
+use std::sync::mpsc::Sender;
+use std::time::Duration;
+
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "macos")]
+pub use macos::*;
+
+#[cfg(target_os = "linux")]
+mod linux;
+#[cfg(target_os = "linux")]
+pub use linux::*;
+
+/// Pointer position together with the size of the screen it is measured against.
+/// Both use the same units (points on macOS, pixels on X11).
+#[derive(Debug, Clone, Copy)]
+pub struct PointerLocation {
+    pub x: f64,
+    pub y: f64,
+    pub screen_width: f64,
+    pub screen_height: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PointerEventKind {
+    /// Moved with no button held
+    Move,
+    /// Left button pressed
+    Press,
+    /// Moved with the left button held
+    Drag,
+    /// Left button released
+    Release,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PointerEvent {
+    pub kind: PointerEventKind,
+    pub location: PointerLocation,
+}
+
+// Only used when the platform has no pointer event source
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Sends pointer events until the receiver goes away. Uses the platform's
+/// global event source (a CGEventTap, XInput2 raw events) and falls back to
+/// polling the location, which only yields `Move` events.
+pub fn watch_pointer(events: Sender<PointerEvent>) {
+    if let Err(e) = listen_pointer(&events) {
+        eprintln!("Pointer events unavailable, polling instead: {}", e);
+    }
+
+    let Some(tracker) = PointerTracker::new() else {
+        return;
+    };
+    loop {
+        if let Some(location) = tracker.location() {
+            let event = PointerEvent {
+                kind: PointerEventKind::Move,
+                location,
+            };
+            if events.send(event).is_err() {
+                return;
+            }
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+fn listen_pointer(_events: &Sender<PointerEvent>) -> Result<(), String> {
+    Err("not supported on this platform".into())
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub struct PointerTracker;
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+impl PointerTracker {
+    pub fn new() -> Option<Self> {
+        None
+    }
+
+    pub fn location(&self) -> Option<PointerLocation> {
+        None
+    }
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "linux")))]
+pub fn configure_drawer_window(_window: &tauri::WebviewWindow) {}
@@ -0,0 +1,53 @@
+//! Values the backend keeps in the `settings` table, next to the frontend's.
+//! Stored as JSON, except that plain strings written by the frontend's
+//! `SettingsRepository` are read back as they are.
+
+use crate::error::AppError;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use sqlx::sqlite::{SqlitePool, SqlitePoolOptions};
+use tauri::{AppHandle, Manager};
+
+/// Opens the app's database, shared with the frontend. Close the pool when done.
+pub(crate) async fn open_db(app: &AppHandle) -> Result<SqlitePool, String> {
+    let db_path = app.path().app_data_dir().map_err(|e| e.to_string())?.join("mydrawer.db");
+    let conn_str = format!("sqlite://{}", db_path.to_string_lossy());
+    SqlitePoolOptions::new()
+        .max_connections(1)
+        .connect(&conn_str)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+
+/// The value saved under `key`. `None` when there is none, or when it no
+/// longer parses as a `T`.
+pub async fn load<T: DeserializeOwned>(app: &AppHandle, key: &str) -> Result<Option<T>, AppError> {
+    let pool = open_db(app).await?;
+    let row: Option<(String,)> = sqlx::query_as("SELECT value FROM settings WHERE key = ?")
+        .bind(key)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    pool.close().await;
+
+    Ok(row.and_then(|(value,)| {
+        serde_json::from_str(&value)
+            .or_else(|_| serde_json::from_value(serde_json::Value::String(value)))
+            .ok()
+    }))
+}
+
+pub async fn save<T: Serialize>(app: &AppHandle, key: &str, value: &T) -> Result<(), AppError> {
+    let value = serde_json::to_string(value).map_err(|e| e.to_string())?;
+    let pool = open_db(app).await?;
+    sqlx::query("INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES (?, ?, ?)")
+        .bind(key)
+        .bind(&value)
+        .bind(chrono::Utc::now().to_rfc3339())
+        .execute(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    pool.close().await;
+    Ok(())
+}
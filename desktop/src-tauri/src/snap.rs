@@ -0,0 +1,401 @@
+use crate::error::AppError;
+use crate::layout_manager::WindowRect;
+use crate::window_backend::frames_match;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+const SETTINGS_KEY: &str = "snap";
+// Slack for ratios that should add up to 1 (e.g. 0.33 + 0.67)
+const RATIO_EPSILON: f64 = 0.001;
+
+/// A rectangle expressed as fractions (0..=1) of a display's working area.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Cell {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl Cell {
+    pub const fn new(x: f64, y: f64, width: f64, height: f64) -> Self {
+        Self { x, y, width, height }
+    }
+
+    /// Parses `"<width>x<height>[@<x>,<y>]"`, e.g. `"0.33x1@0,0"` or `"1/3x1@2/3,0"`.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid ratio \"{}\", expected e.g. 0.33x1@0,0", input);
+
+        let (size, position) = match input.split_once('@') {
+            Some((size, position)) => (size, Some(position)),
+            None => (input, None),
+        };
+        let (width, height) = size.split_once('x').ok_or_else(invalid)?;
+        let (x, y) = match position {
+            Some(position) => position.split_once(',').ok_or_else(invalid)?,
+            None => ("0", "0"),
+        };
+
+        let number = |s: &str| parse_ratio(s.trim()).ok_or_else(invalid);
+        let cell = Cell::new(number(x)?, number(y)?, number(width)?, number(height)?);
+        cell.validate()?;
+        Ok(cell)
+    }
+
+    /// Checks that the cell has a size and lies inside the unit square.
+    pub fn validate(&self) -> Result<(), String> {
+        let values = [self.x, self.y, self.width, self.height];
+        if values.iter().any(|v| !v.is_finite()) {
+            return Err("Ratios must be finite numbers".into());
+        }
+        if self.width <= 0.0 || self.height <= 0.0 {
+            return Err("Width and height ratios must be greater than 0".into());
+        }
+        if self.x < 0.0 || self.y < 0.0 {
+            return Err("Position ratios must not be negative".into());
+        }
+        if self.x + self.width > 1.0 + RATIO_EPSILON || self.y + self.height > 1.0 + RATIO_EPSILON {
+            return Err("Cell must fit inside the screen (x + width <= 1, y + height <= 1)".into());
+        }
+        Ok(())
+    }
+
+    /// The cell's frame inside `area`. `padding` is kept around the area and half
+    /// of `gap` on every edge shared with a neighbouring cell, so adjacent
+    /// windows end up exactly one gap apart.
+    pub fn frame_in(&self, area: &WindowRect, settings: &SnapSettings) -> WindowRect {
+        let padding = settings.padding;
+        let inner = WindowRect {
+            x: area.x + padding,
+            y: area.y + padding,
+            width: (area.width - padding * 2.0).max(0.0),
+            height: (area.height - padding * 2.0).max(0.0),
+        };
+
+        let half_gap = settings.gap / 2.0;
+        let inset = |at_edge: bool| if at_edge { 0.0 } else { half_gap };
+
+        let left = inner.x + self.x * inner.width + inset(self.x <= RATIO_EPSILON);
+        let top = inner.y + self.y * inner.height + inset(self.y <= RATIO_EPSILON);
+        let right = inner.x + (self.x + self.width) * inner.width - inset(self.x + self.width >= 1.0 - RATIO_EPSILON);
+        let bottom = inner.y + (self.y + self.height) * inner.height - inset(self.y + self.height >= 1.0 - RATIO_EPSILON);
+
+        WindowRect {
+            x: left,
+            y: top,
+            width: (right - left).max(0.0),
+            height: (bottom - top).max(0.0),
+        }
+    }
+}
+
+/// A decimal (`0.5`) or a fraction (`2/3`).
+fn parse_ratio(s: &str) -> Option<f64> {
+    match s.split_once('/') {
+        Some((numerator, denominator)) => {
+            let numerator: f64 = numerator.trim().parse().ok()?;
+            let denominator: f64 = denominator.trim().parse().ok()?;
+            (denominator != 0.0).then(|| numerator / denominator)
+        }
+        None => s.parse().ok(),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SnapTarget {
+    LeftHalf,
+    RightHalf,
+    TopHalf,
+    BottomHalf,
+    LeftThird,
+    CenterThird,
+    RightThird,
+    LeftTwoThirds,
+    RightTwoThirds,
+    TopLeftQuarter,
+    TopRightQuarter,
+    BottomLeftQuarter,
+    BottomRightQuarter,
+    TopLeftSixth,
+    TopCenterSixth,
+    TopRightSixth,
+    BottomLeftSixth,
+    BottomCenterSixth,
+    BottomRightSixth,
+    Maximize,
+    Center,
+    Custom(Cell),
+}
+
+impl SnapTarget {
+    pub fn cell(&self) -> Cell {
+        const THIRD: f64 = 1.0 / 3.0;
+        match self {
+            SnapTarget::LeftHalf => Cell::new(0.0, 0.0, 0.5, 1.0),
+            SnapTarget::RightHalf => Cell::new(0.5, 0.0, 0.5, 1.0),
+            SnapTarget::TopHalf => Cell::new(0.0, 0.0, 1.0, 0.5),
+            SnapTarget::BottomHalf => Cell::new(0.0, 0.5, 1.0, 0.5),
+            SnapTarget::LeftThird => Cell::new(0.0, 0.0, THIRD, 1.0),
+            SnapTarget::CenterThird => Cell::new(THIRD, 0.0, THIRD, 1.0),
+            SnapTarget::RightThird => Cell::new(THIRD * 2.0, 0.0, THIRD, 1.0),
+            SnapTarget::LeftTwoThirds => Cell::new(0.0, 0.0, THIRD * 2.0, 1.0),
+            SnapTarget::RightTwoThirds => Cell::new(THIRD, 0.0, THIRD * 2.0, 1.0),
+            SnapTarget::TopLeftQuarter => Cell::new(0.0, 0.0, 0.5, 0.5),
+            SnapTarget::TopRightQuarter => Cell::new(0.5, 0.0, 0.5, 0.5),
+            SnapTarget::BottomLeftQuarter => Cell::new(0.0, 0.5, 0.5, 0.5),
+            SnapTarget::BottomRightQuarter => Cell::new(0.5, 0.5, 0.5, 0.5),
+            SnapTarget::TopLeftSixth => Cell::new(0.0, 0.0, THIRD, 0.5),
+            SnapTarget::TopCenterSixth => Cell::new(THIRD, 0.0, THIRD, 0.5),
+            SnapTarget::TopRightSixth => Cell::new(THIRD * 2.0, 0.0, THIRD, 0.5),
+            SnapTarget::BottomLeftSixth => Cell::new(0.0, 0.5, THIRD, 0.5),
+            SnapTarget::BottomCenterSixth => Cell::new(THIRD, 0.5, THIRD, 0.5),
+            SnapTarget::BottomRightSixth => Cell::new(THIRD * 2.0, 0.5, THIRD, 0.5),
+            SnapTarget::Maximize => Cell::new(0.0, 0.0, 1.0, 1.0),
+            SnapTarget::Center => Cell::new(0.1, 0.1, 0.8, 0.8),
+            SnapTarget::Custom(cell) => *cell,
+        }
+    }
+
+    /// Targets visited by pressing the same snap repeatedly.
+    pub fn cycle(&self) -> Vec<SnapTarget> {
+        match self {
+            SnapTarget::LeftHalf => vec![SnapTarget::LeftHalf, SnapTarget::LeftThird, SnapTarget::LeftTwoThirds],
+            SnapTarget::RightHalf => vec![SnapTarget::RightHalf, SnapTarget::RightThird, SnapTarget::RightTwoThirds],
+            other => vec![*other],
+        }
+    }
+
+    /// Where a window currently at `current` goes: the step after the one it
+    /// already occupies in this target's cycle, or the first step.
+    pub fn next_frame(&self, current: &WindowRect, area: &WindowRect, settings: &SnapSettings) -> WindowRect {
+        let steps: Vec<WindowRect> = self
+            .cycle()
+            .iter()
+            .map(|target| target.cell().frame_in(area, settings))
+            .collect();
+        let next = steps
+            .iter()
+            .position(|frame| frames_match(frame, current))
+            .map_or(0, |i| (i + 1) % steps.len());
+        steps[next].clone()
+    }
+}
+
+impl FromStr for SnapTarget {
+    type Err = String;
+
+    /// Accepts kebab or snake case names ("left", "left-third", "top_left_sixth")
+    /// and custom ratios ("0.33x1@0,0").
+    fn from_str(input: &str) -> Result<Self, Self::Err> {
+        let name = input.trim().to_ascii_lowercase().replace('_', "-");
+
+        let target = match name.as_str() {
+            "left" | "left-half" => SnapTarget::LeftHalf,
+            "right" | "right-half" => SnapTarget::RightHalf,
+            "top" | "top-half" => SnapTarget::TopHalf,
+            "bottom" | "bottom-half" => SnapTarget::BottomHalf,
+            "left-third" | "first-third" => SnapTarget::LeftThird,
+            "center-third" => SnapTarget::CenterThird,
+            "right-third" | "last-third" => SnapTarget::RightThird,
+            "left-two-thirds" | "first-two-thirds" => SnapTarget::LeftTwoThirds,
+            "right-two-thirds" | "last-two-thirds" => SnapTarget::RightTwoThirds,
+            "top-left" | "top-left-quarter" => SnapTarget::TopLeftQuarter,
+            "top-right" | "top-right-quarter" => SnapTarget::TopRightQuarter,
+            "bottom-left" | "bottom-left-quarter" => SnapTarget::BottomLeftQuarter,
+            "bottom-right" | "bottom-right-quarter" => SnapTarget::BottomRightQuarter,
+            "top-left-sixth" => SnapTarget::TopLeftSixth,
+            "top-center-sixth" => SnapTarget::TopCenterSixth,
+            "top-right-sixth" => SnapTarget::TopRightSixth,
+            "bottom-left-sixth" => SnapTarget::BottomLeftSixth,
+            "bottom-center-sixth" => SnapTarget::BottomCenterSixth,
+            "bottom-right-sixth" => SnapTarget::BottomRightSixth,
+            "maximize" => SnapTarget::Maximize,
+            "center" => SnapTarget::Center,
+            _ if name.contains('x') => SnapTarget::Custom(Cell::parse(&name)?),
+            _ => return Err(format!("Unknown snap target \"{}\"", input)),
+        };
+        Ok(target)
+    }
+}
+
+/// Spacing applied to every snapped and tiled window, in points.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapSettings {
+    /// Space kept between windows and the edges of the working area
+    pub padding: f64,
+    /// Space between adjacent windows
+    pub gap: f64,
+}
+
+impl SnapSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(self.padding.is_finite() && self.padding >= 0.0 && self.gap.is_finite() && self.gap >= 0.0) {
+            return Err("Padding and gap must be non-negative numbers".into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct SnapConfig {
+    settings: Mutex<SnapSettings>,
+}
+
+impl SnapConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self) -> SnapSettings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    pub fn set(&self, settings: SnapSettings) {
+        *self.settings.lock().unwrap() = settings;
+    }
+}
+
+/// Restores the saved padding and gap.
+pub fn load(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        match crate::settings::load::<SnapSettings>(&app, SETTINGS_KEY).await {
+            Ok(Some(settings)) if settings.validate().is_ok() => app.state::<SnapConfig>().set(settings),
+            Ok(_) => {}
+            Err(e) => eprintln!("Failed to load snap settings: {}", e),
+        }
+    });
+}
+
+#[tauri::command]
+pub fn get_snap_settings(config: tauri::State<SnapConfig>) -> SnapSettings {
+    config.get()
+}
+
+#[tauri::command]
+pub async fn set_snap_settings(
+    app: AppHandle,
+    config: tauri::State<'_, SnapConfig>,
+    settings: SnapSettings,
+) -> Result<(), AppError> {
+    settings.validate().map_err(AppError::InvalidInput)?;
+    crate::settings::save(&app, SETTINGS_KEY, &settings).await?;
+    config.set(settings);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_backend::fake::rect;
+
+    fn area() -> WindowRect {
+        rect(0.0, 0.0, 1200.0, 800.0)
+    }
+
+    fn spaced(padding: f64, gap: f64) -> SnapSettings {
+        SnapSettings { padding, gap }
+    }
+
+    #[test]
+    fn parses_names_in_any_case_style() {
+        assert_eq!("left".parse::<SnapTarget>(), Ok(SnapTarget::LeftHalf));
+        assert_eq!("Right-Half".parse::<SnapTarget>(), Ok(SnapTarget::RightHalf));
+        assert_eq!("left_two_thirds".parse::<SnapTarget>(), Ok(SnapTarget::LeftTwoThirds));
+        assert_eq!(" bottom-center-sixth ".parse::<SnapTarget>(), Ok(SnapTarget::BottomCenterSixth));
+        assert!("sideways".parse::<SnapTarget>().is_err());
+    }
+
+    #[test]
+    fn parses_custom_ratios() {
+        assert_eq!(
+            "0.33x1@0,0".parse::<SnapTarget>(),
+            Ok(SnapTarget::Custom(Cell::new(0.0, 0.0, 0.33, 1.0)))
+        );
+        assert_eq!(
+            "0.5x0.5@0.5,0.5".parse::<SnapTarget>(),
+            Ok(SnapTarget::Custom(Cell::new(0.5, 0.5, 0.5, 0.5)))
+        );
+        // Position defaults to the top-left corner; fractions are allowed
+        assert_eq!(
+            "1/4x1".parse::<SnapTarget>(),
+            Ok(SnapTarget::Custom(Cell::new(0.0, 0.0, 0.25, 1.0)))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_custom_ratios() {
+        for input in ["0x1", "0.5x", "0.5x1@", "0.5x1@0.6,0", "1.5x1", "0.5x1@-0.1,0", "ax1", "1/0x1", "0.5x1@0"] {
+            assert!(Cell::parse(input).is_err(), "{} should be rejected", input);
+        }
+        // Rounded thirds still fit
+        assert!(Cell::parse("0.67x1@0.33,0").is_ok());
+    }
+
+    #[test]
+    fn fractions_map_onto_the_work_area() {
+        let area = rect(100.0, 25.0, 1200.0, 900.0);
+        let none = SnapSettings::default();
+
+        assert_eq!(SnapTarget::LeftHalf.cell().frame_in(&area, &none), rect(100.0, 25.0, 600.0, 900.0));
+        assert_eq!(SnapTarget::RightThird.cell().frame_in(&area, &none), rect(900.0, 25.0, 400.0, 900.0));
+        assert_eq!(SnapTarget::BottomRightQuarter.cell().frame_in(&area, &none), rect(700.0, 475.0, 600.0, 450.0));
+        assert_eq!(SnapTarget::TopCenterSixth.cell().frame_in(&area, &none), rect(500.0, 25.0, 400.0, 450.0));
+        assert_eq!(SnapTarget::Center.cell().frame_in(&area, &none), rect(220.0, 115.0, 960.0, 720.0));
+    }
+
+    #[test]
+    fn padding_insets_the_area_and_gaps_only_shared_edges() {
+        let settings = spaced(10.0, 20.0);
+
+        // Outer edges get padding only, the shared middle edge half a gap
+        assert_eq!(SnapTarget::LeftHalf.cell().frame_in(&area(), &settings), rect(10.0, 10.0, 580.0, 780.0));
+        assert_eq!(SnapTarget::RightHalf.cell().frame_in(&area(), &settings), rect(610.0, 10.0, 580.0, 780.0));
+        assert_eq!(SnapTarget::Maximize.cell().frame_in(&area(), &settings), rect(10.0, 10.0, 1180.0, 780.0));
+    }
+
+    #[test]
+    fn adjacent_cells_are_exactly_one_gap_apart() {
+        let settings = spaced(8.0, 12.0);
+        let left = SnapTarget::LeftThird.cell().frame_in(&area(), &settings);
+        let middle = SnapTarget::CenterThird.cell().frame_in(&area(), &settings);
+        let right = SnapTarget::RightTwoThirds.cell().frame_in(&area(), &settings);
+
+        assert!((middle.x - (left.x + left.width) - 12.0).abs() < 1e-9);
+        assert!((right.x - (left.x + left.width) - 12.0).abs() < 1e-9);
+        assert!((right.x + right.width - (1200.0 - 8.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn repeating_left_cycles_half_third_two_thirds() {
+        let none = SnapSettings::default();
+        let elsewhere = rect(300.0, 200.0, 400.0, 300.0);
+
+        let half = SnapTarget::LeftHalf.next_frame(&elsewhere, &area(), &none);
+        assert_eq!(half, rect(0.0, 0.0, 600.0, 800.0));
+
+        let third = SnapTarget::LeftHalf.next_frame(&half, &area(), &none);
+        assert_eq!(third, rect(0.0, 0.0, 400.0, 800.0));
+
+        let two_thirds = SnapTarget::LeftHalf.next_frame(&third, &area(), &none);
+        assert_eq!(two_thirds, rect(0.0, 0.0, 800.0, 800.0));
+
+        assert_eq!(SnapTarget::LeftHalf.next_frame(&two_thirds, &area(), &none), half);
+    }
+
+    #[test]
+    fn targets_without_a_cycle_repeat_themselves() {
+        let none = SnapSettings::default();
+        let max = SnapTarget::Maximize.next_frame(&rect(0.0, 0.0, 10.0, 10.0), &area(), &none);
+        assert_eq!(SnapTarget::Maximize.next_frame(&max, &area(), &none), max);
+    }
+
+    #[test]
+    fn settings_reject_negative_spacing() {
+        assert!(spaced(0.0, 0.0).validate().is_ok());
+        assert!(spaced(-1.0, 0.0).validate().is_err());
+        assert!(spaced(0.0, f64::NAN).validate().is_err());
+    }
+}
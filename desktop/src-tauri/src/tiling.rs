@@ -0,0 +1,1002 @@
+use crate::error::AppError;
+use crate::layout_manager::{display_for, get_open_windows, target_window, FocusTracker, IgnoredApps, WindowInfo, WindowRect};
+use crate::permissions::AccessibilityPermission;
+use crate::snap::{Cell, SnapConfig, SnapSettings};
+use crate::window_backend::{frames_match, SharedBackend, WindowBackend, WindowError};
+use crate::window_rules::{with_details, CompiledRule, WindowRules};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+// How often the watcher diffs the window list
+const RETILE_INTERVAL: Duration = Duration::from_millis(500);
+// Splits never shrink a side below this fraction
+const MIN_RATIO: f64 = 0.1;
+const MAX_RATIO: f64 = 0.9;
+const DEFAULT_MASTER_RATIO: f64 = 0.6;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TilingMode {
+    Bsp,
+    MasterStack,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    /// Children side by side
+    Horizontal,
+    /// Children stacked top to bottom
+    Vertical,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Node {
+    Leaf(u32),
+    Split {
+        axis: Axis,
+        /// Share of the split taken by `first`
+        ratio: f64,
+        first: Box<Node>,
+        second: Box<Node>,
+    },
+}
+
+fn clamp_ratio(ratio: f64) -> f64 {
+    ratio.clamp(MIN_RATIO, MAX_RATIO)
+}
+
+impl Node {
+    fn split(axis: Axis, first: Node, second: Node) -> Node {
+        Node::Split {
+            axis,
+            ratio: 0.5,
+            first: Box::new(first),
+            second: Box::new(second),
+        }
+    }
+
+    pub fn contains(&self, id: u32) -> bool {
+        match self {
+            Node::Leaf(w) => *w == id,
+            Node::Split { first, second, .. } => first.contains(id) || second.contains(id),
+        }
+    }
+
+    fn collect_leaves(&self, out: &mut Vec<u32>) {
+        match self {
+            Node::Leaf(w) => out.push(*w),
+            Node::Split { first, second, .. } => {
+                first.collect_leaves(out);
+                second.collect_leaves(out);
+            }
+        }
+    }
+
+    fn collect_cells(&self, cell: Cell, out: &mut Vec<(u32, Cell)>) {
+        match self {
+            Node::Leaf(w) => out.push((*w, cell)),
+            Node::Split { axis, ratio, first, second } => {
+                let (a, b) = match axis {
+                    Axis::Horizontal => {
+                        let w = cell.width * ratio;
+                        (
+                            Cell::new(cell.x, cell.y, w, cell.height),
+                            Cell::new(cell.x + w, cell.y, cell.width - w, cell.height),
+                        )
+                    }
+                    Axis::Vertical => {
+                        let h = cell.height * ratio;
+                        (
+                            Cell::new(cell.x, cell.y, cell.width, h),
+                            Cell::new(cell.x, cell.y + h, cell.width, cell.height - h),
+                        )
+                    }
+                };
+                first.collect_cells(a, out);
+                second.collect_cells(b, out);
+            }
+        }
+    }
+
+    /// Replaces leaf `target` with a split holding `target` then `new`.
+    fn insert_next_to(&mut self, target: u32, new: u32, axis: Axis) -> bool {
+        match self {
+            Node::Leaf(w) if *w == target => {
+                *self = Node::split(axis, Node::Leaf(target), Node::Leaf(new));
+                true
+            }
+            Node::Leaf(_) => false,
+            Node::Split { first, second, .. } => {
+                first.insert_next_to(target, new, axis) || second.insert_next_to(target, new, axis)
+            }
+        }
+    }
+
+    /// The tree without leaf `id`; its sibling takes over the parent split.
+    fn without(self, id: u32) -> Option<Node> {
+        match self {
+            Node::Leaf(w) => (w != id).then_some(Node::Leaf(w)),
+            Node::Split { axis, ratio, first, second } => match (first.without(id), second.without(id)) {
+                (Some(first), Some(second)) => Some(Node::Split {
+                    axis,
+                    ratio,
+                    first: Box::new(first),
+                    second: Box::new(second),
+                }),
+                (Some(only), None) | (None, Some(only)) => Some(only),
+                (None, None) => None,
+            },
+        }
+    }
+
+    fn swap_ids(&mut self, a: u32, b: u32) {
+        match self {
+            Node::Leaf(w) if *w == a => *w = b,
+            Node::Leaf(w) if *w == b => *w = a,
+            Node::Leaf(_) => {}
+            Node::Split { first, second, .. } => {
+                first.swap_ids(a, b);
+                second.swap_ids(a, b);
+            }
+        }
+    }
+
+    /// Grows `id` by `delta` in the nearest split that contains it.
+    fn resize(&mut self, id: u32, delta: f64) -> bool {
+        let Node::Split { ratio, first, second, .. } = self else {
+            return false;
+        };
+        if first.contains(id) {
+            if !first.resize(id, delta) {
+                *ratio = clamp_ratio(*ratio + delta);
+            }
+            true
+        } else if second.contains(id) {
+            if !second.resize(id, delta) {
+                *ratio = clamp_ratio(*ratio - delta);
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Rotates the tree 90° clockwise.
+    fn rotate(&mut self) {
+        if let Node::Split { axis, ratio, first, second } = self {
+            first.rotate();
+            second.rotate();
+            match axis {
+                Axis::Horizontal => *axis = Axis::Vertical,
+                Axis::Vertical => {
+                    // Top goes right, bottom goes left
+                    *axis = Axis::Horizontal;
+                    std::mem::swap(first, second);
+                    *ratio = 1.0 - *ratio;
+                }
+            }
+        }
+    }
+}
+
+/// The arrangement of one display's windows. Pure data: callers feed it the
+/// current window ids and read back fractional cells.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TileLayout {
+    Bsp { root: Option<Node> },
+    /// `windows[0]` is the master; the rest share the stack evenly
+    MasterStack { windows: Vec<u32>, ratio: f64 },
+}
+
+impl TileLayout {
+    pub fn new(mode: TilingMode) -> Self {
+        match mode {
+            TilingMode::Bsp => TileLayout::Bsp { root: None },
+            TilingMode::MasterStack => TileLayout::MasterStack {
+                windows: Vec::new(),
+                ratio: DEFAULT_MASTER_RATIO,
+            },
+        }
+    }
+
+    /// Window ids in tile order (master / first leaf first).
+    pub fn windows(&self) -> Vec<u32> {
+        match self {
+            TileLayout::Bsp { root } => {
+                let mut leaves = Vec::new();
+                if let Some(root) = root {
+                    root.collect_leaves(&mut leaves);
+                }
+                leaves
+            }
+            TileLayout::MasterStack { windows, .. } => windows.clone(),
+        }
+    }
+
+    pub fn contains(&self, id: u32) -> bool {
+        self.windows().contains(&id)
+    }
+
+    /// Adds `id`. In BSP mode it splits `focused` (or the largest tile) along
+    /// its longer side, measured in `area`; in master-stack mode it joins the
+    /// bottom of the stack.
+    pub fn insert(&mut self, id: u32, focused: Option<u32>, area: &WindowRect) {
+        if self.contains(id) {
+            return;
+        }
+        match self {
+            TileLayout::Bsp { root } => {
+                let Some(tree) = root else {
+                    *root = Some(Node::Leaf(id));
+                    return;
+                };
+
+                let mut cells = Vec::new();
+                tree.collect_cells(Cell::new(0.0, 0.0, 1.0, 1.0), &mut cells);
+                let size = |c: &Cell| (c.width * area.width, c.height * area.height);
+                let (target, cell) = cells
+                    .iter()
+                    .find(|(w, _)| Some(*w) == focused)
+                    .or_else(|| {
+                        cells.iter().max_by(|(_, a), (_, b)| {
+                            let (aw, ah) = size(a);
+                            let (bw, bh) = size(b);
+                            (aw * ah).total_cmp(&(bw * bh))
+                        })
+                    })
+                    .copied()
+                    .expect("a non-empty tree has leaves");
+
+                let (width, height) = size(&cell);
+                let axis = if width >= height { Axis::Horizontal } else { Axis::Vertical };
+                tree.insert_next_to(target, id, axis);
+            }
+            TileLayout::MasterStack { windows, .. } => windows.push(id),
+        }
+    }
+
+    pub fn remove(&mut self, id: u32) {
+        match self {
+            TileLayout::Bsp { root } => *root = root.take().and_then(|r| r.without(id)),
+            TileLayout::MasterStack { windows, .. } => windows.retain(|w| *w != id),
+        }
+    }
+
+    /// Brings the layout in line with `visible` (front-most first): closed
+    /// windows are dropped, new ones inserted. Returns whether anything changed.
+    pub fn sync(&mut self, visible: &[u32], focused: Option<u32>, area: &WindowRect) -> bool {
+        let current = self.windows();
+        let closed: Vec<u32> = current.iter().copied().filter(|w| !visible.contains(w)).collect();
+        let opened: Vec<u32> = visible.iter().copied().filter(|w| !current.contains(w)).collect();
+
+        for id in &closed {
+            self.remove(*id);
+        }
+        for id in &opened {
+            let anchor = focused.filter(|f| self.contains(*f));
+            self.insert(*id, anchor, area);
+        }
+        !closed.is_empty() || !opened.is_empty()
+    }
+
+    pub fn swap(&mut self, a: u32, b: u32) -> bool {
+        if a == b || !self.contains(a) || !self.contains(b) {
+            return false;
+        }
+        match self {
+            TileLayout::Bsp { root } => {
+                if let Some(root) = root {
+                    root.swap_ids(a, b);
+                }
+            }
+            TileLayout::MasterStack { windows, .. } => {
+                let i = windows.iter().position(|w| *w == a).unwrap();
+                let j = windows.iter().position(|w| *w == b).unwrap();
+                windows.swap(i, j);
+            }
+        }
+        true
+    }
+
+    /// Moves `id` into the master slot (the first leaf in BSP mode).
+    pub fn promote(&mut self, id: u32) -> bool {
+        match self {
+            TileLayout::Bsp { .. } => {
+                let Some(first) = self.windows().first().copied() else {
+                    return false;
+                };
+                first == id || self.swap(first, id)
+            }
+            TileLayout::MasterStack { windows, .. } => {
+                let Some(index) = windows.iter().position(|w| *w == id) else {
+                    return false;
+                };
+                let w = windows.remove(index);
+                windows.insert(0, w);
+                true
+            }
+        }
+    }
+
+    /// Grows `id`'s tile by `delta` (a fraction of the split; negative shrinks).
+    pub fn resize(&mut self, id: u32, delta: f64) -> bool {
+        match self {
+            TileLayout::Bsp { root } => root.as_mut().is_some_and(|r| r.resize(id, delta)),
+            TileLayout::MasterStack { windows, ratio } => {
+                let Some(index) = windows.iter().position(|w| *w == id) else {
+                    return false;
+                };
+                if windows.len() < 2 {
+                    return false;
+                }
+                *ratio = clamp_ratio(if index == 0 { *ratio + delta } else { *ratio - delta });
+                true
+            }
+        }
+    }
+
+    /// BSP: rotates the tree 90° clockwise. Master-stack: moves every window
+    /// one slot down, the last one becoming master.
+    pub fn rotate(&mut self) {
+        match self {
+            TileLayout::Bsp { root } => {
+                if let Some(root) = root {
+                    root.rotate();
+                }
+            }
+            TileLayout::MasterStack { windows, .. } => {
+                if !windows.is_empty() {
+                    windows.rotate_right(1);
+                }
+            }
+        }
+    }
+
+    /// The id after `id` in tile order, wrapping around.
+    pub fn next_window(&self, id: u32) -> Option<u32> {
+        let windows = self.windows();
+        let index = windows.iter().position(|w| *w == id)?;
+        let next = windows[(index + 1) % windows.len()];
+        (next != id).then_some(next)
+    }
+
+    pub fn cells(&self) -> Vec<(u32, Cell)> {
+        match self {
+            TileLayout::Bsp { root } => {
+                let mut cells = Vec::new();
+                if let Some(root) = root {
+                    root.collect_cells(Cell::new(0.0, 0.0, 1.0, 1.0), &mut cells);
+                }
+                cells
+            }
+            TileLayout::MasterStack { windows, ratio } => match windows.as_slice() {
+                [] => Vec::new(),
+                [only] => vec![(*only, Cell::new(0.0, 0.0, 1.0, 1.0))],
+                [master, stack @ ..] => {
+                    let h = 1.0 / stack.len() as f64;
+                    let mut cells = vec![(*master, Cell::new(0.0, 0.0, *ratio, 1.0))];
+                    cells.extend(
+                        stack
+                            .iter()
+                            .enumerate()
+                            .map(|(i, w)| (*w, Cell::new(*ratio, i as f64 * h, 1.0 - ratio, h))),
+                    );
+                    cells
+                }
+            },
+        }
+    }
+
+    pub fn frames(&self, area: &WindowRect, settings: &SnapSettings) -> Vec<(u32, WindowRect)> {
+        self.cells()
+            .into_iter()
+            .map(|(id, cell)| (id, cell.frame_in(area, settings)))
+            .collect()
+    }
+}
+
+/// Auto-tiling state: one layout per display while a mode is active.
+#[derive(Default)]
+pub struct TilingManager {
+    mode: Mutex<Option<TilingMode>>,
+    layouts: Mutex<HashMap<u32, TileLayout>>,
+    /// Windows that refused to move. Left out of every layout until they close
+    unmovable: Mutex<HashSet<u32>>,
+}
+
+impl TilingManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn mode(&self) -> Option<TilingMode> {
+        *self.mode.lock().unwrap()
+    }
+
+    pub fn set_mode(&self, mode: Option<TilingMode>) {
+        *self.mode.lock().unwrap() = mode;
+        self.layouts.lock().unwrap().clear();
+        self.unmovable.lock().unwrap().clear();
+    }
+
+    /// Diffs the visible windows against every display's layout and moves
+    /// windows that are not where their tile says. Windows matching one of the
+    /// `no_tile` rules are left out, and released if they were tiled.
+    ///
+    /// A window that can't be moved is left out from then on; it is returned
+    /// once, with why, so the caller can report it. Without the permission to
+    /// move windows the pass stops and nothing is marked.
+    pub fn retile(
+        &self,
+        backend: &dyn WindowBackend,
+        settings: &SnapSettings,
+        no_tile: &[CompiledRule],
+        ignored: &[String],
+        my_pid: i32,
+    ) -> Vec<(WindowInfo, WindowError)> {
+        let Some(mode) = self.mode() else {
+            return Vec::new();
+        };
+
+        let mut windows = get_open_windows(backend, ignored, my_pid);
+        if !no_tile.is_empty() {
+            windows = with_details(backend, windows);
+            windows.retain(|w| !no_tile.iter().any(|rule| rule.matches(w)));
+        }
+        let mut unmovable = self.unmovable.lock().unwrap();
+        unmovable.retain(|id| windows.iter().any(|w| w.id == *id));
+        windows.retain(|w| !unmovable.contains(&w.id));
+
+        let displays = backend.displays();
+        let focused = backend.focused_window().map(|w| w.id);
+        let mut layouts = self.layouts.lock().unwrap();
+        let mut failures = Vec::new();
+        layouts.retain(|id, _| displays.iter().any(|d| d.id == *id));
+
+        for display in &displays {
+            let visible: Vec<u32> = windows
+                .iter()
+                .filter(|w| display_for(&displays, &w.frame).map(|d| d.id) == Some(display.id))
+                .map(|w| w.id)
+                .collect();
+
+            let layout = layouts.entry(display.id).or_insert_with(|| TileLayout::new(mode));
+            layout.sync(&visible, focused, &display.work_area);
+
+            for (id, frame) in layout.frames(&display.work_area, settings) {
+                let Some(window) = windows.iter().find(|w| w.id == id) else {
+                    continue;
+                };
+                if frames_match(&window.frame, &frame) {
+                    continue;
+                }
+                match backend.set_frame(window, &frame) {
+                    Ok(()) => {}
+                    Err(WindowError::PermissionDenied) => return failures,
+                    // Closed since it was listed
+                    Err(WindowError::WindowNotFound { .. }) => {}
+                    Err(error) => {
+                        unmovable.insert(window.id);
+                        failures.push((window.clone(), error));
+                    }
+                }
+            }
+        }
+        failures
+    }
+
+    /// Runs `op` on the layout holding `id`. Returns whether it changed anything.
+    pub fn update(&self, id: u32, op: impl FnOnce(&mut TileLayout) -> bool) -> bool {
+        let mut layouts = self.layouts.lock().unwrap();
+        layouts.values_mut().find(|l| l.contains(id)).is_some_and(op)
+    }
+}
+
+pub fn spawn_watcher(app: AppHandle) {
+    std::thread::spawn(move || {
+        let my_pid = std::process::id() as i32;
+        loop {
+            std::thread::sleep(RETILE_INTERVAL);
+            if app.state::<TilingManager>().mode().is_none()
+                || app.state::<AccessibilityPermission>().require().is_err()
+            {
+                continue;
+            }
+            retile_now(&app, my_pid);
+        }
+    });
+}
+
+/// Retiles with the current settings and rules, logging windows that
+/// refused to move.
+fn retile_now(app: &AppHandle, my_pid: i32) {
+    let backend = app.state::<SharedBackend>();
+    let settings = app.state::<SnapConfig>().get();
+    let no_tile = app.state::<WindowRules>().no_tile_rules();
+    let ignored = app.state::<IgnoredApps>().get();
+    let failures = app
+        .state::<TilingManager>()
+        .retile(backend.as_ref(), &settings, &no_tile, &ignored, my_pid);
+    for (window, error) in failures {
+        eprintln!("Not tiling \"{}\" ({}): {}", window.title, window.app_name, error);
+    }
+}
+
+/// Applies `op` to the layout of the window layout actions target, then retiles.
+fn update_target(app: &AppHandle, op: impl FnOnce(&mut TileLayout, u32) -> bool) -> Result<(), AppError> {
+    let tiling = app.state::<TilingManager>();
+    if tiling.mode().is_none() {
+        return Err(AppError::TilingOff);
+    }
+    let backend = app.state::<SharedBackend>();
+    let focus = app.state::<FocusTracker>();
+    let my_pid = std::process::id() as i32;
+
+    // Pick up windows opened since the last tick before resolving the target
+    retile_now(app, my_pid);
+
+    let ignored = app.state::<IgnoredApps>().get();
+    let windows = get_open_windows(backend.as_ref(), &ignored, my_pid);
+    let target = target_window(backend.as_ref(), &windows, focus.last().as_ref(), &ignored, my_pid)
+        .ok_or(WindowError::WindowNotFound { id: None })?;
+
+    if tiling.update(target.id, |layout| op(layout, target.id)) {
+        retile_now(app, my_pid);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_tiling_mode(tiling: tauri::State<TilingManager>) -> Option<TilingMode> {
+    tiling.mode()
+}
+
+/// Turns auto-tiling on with `mode`, or off when `mode` is null.
+#[tauri::command]
+pub fn set_tiling_mode(app: AppHandle, mode: Option<TilingMode>) {
+    let tiling = app.state::<TilingManager>();
+    tiling.set_mode(mode);
+    retile_now(&app, std::process::id() as i32);
+}
+
+#[tauri::command]
+pub fn tiling_rotate(app: AppHandle) -> Result<(), AppError> {
+    update_target(&app, |layout, _| {
+        layout.rotate();
+        true
+    })
+}
+
+/// Swaps the target window with `other`, or with the next tile when omitted.
+#[tauri::command]
+pub fn tiling_swap(app: AppHandle, other: Option<u32>) -> Result<(), AppError> {
+    update_target(&app, |layout, target| {
+        let Some(other) = other.or_else(|| layout.next_window(target)) else {
+            return false;
+        };
+        layout.swap(target, other)
+    })
+}
+
+#[tauri::command]
+pub fn tiling_promote(app: AppHandle) -> Result<(), AppError> {
+    update_target(&app, |layout, target| layout.promote(target))
+}
+
+/// Grows (positive) or shrinks (negative) the target's split by `delta`, a fraction of the split.
+#[tauri::command]
+pub fn tiling_resize(app: AppHandle, delta: f64) -> Result<(), AppError> {
+    if !delta.is_finite() {
+        return Err(AppError::InvalidInput("delta must be a number".into()));
+    }
+    update_target(&app, |layout, target| layout.resize(target, delta))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_backend::fake::{display, rect, window, FakeBackend};
+    use crate::window_rules::{RuleAction, RuleMatch, WindowRule};
+
+    const MY_PID: i32 = 1;
+
+    fn wide() -> WindowRect {
+        rect(0.0, 0.0, 1600.0, 900.0)
+    }
+
+    fn bsp(ids: &[u32]) -> TileLayout {
+        let mut layout = TileLayout::new(TilingMode::Bsp);
+        for id in ids {
+            layout.insert(*id, None, &wide());
+        }
+        layout
+    }
+
+    fn cell_of(layout: &TileLayout, id: u32) -> Cell {
+        layout.cells().into_iter().find(|(w, _)| *w == id).unwrap().1
+    }
+
+    fn assert_cell(actual: Cell, expected: Cell) {
+        let close = (actual.x - expected.x).abs() < 1e-9
+            && (actual.y - expected.y).abs() < 1e-9
+            && (actual.width - expected.width).abs() < 1e-9
+            && (actual.height - expected.height).abs() < 1e-9;
+        assert!(close, "{:?} != {:?}", actual, expected);
+    }
+
+    fn total_area(layout: &TileLayout) -> f64 {
+        layout.cells().iter().map(|(_, c)| c.width * c.height).sum()
+    }
+
+    #[test]
+    fn bsp_single_window_fills_the_area() {
+        let layout = bsp(&[1]);
+        assert_eq!(layout.cells(), vec![(1, Cell::new(0.0, 0.0, 1.0, 1.0))]);
+    }
+
+    #[test]
+    fn bsp_splits_along_the_longer_side() {
+        let layout = bsp(&[1, 2, 3]);
+
+        // Wide screen: first split is side by side, then the right half
+        // (800x900, taller than wide) is split top/bottom
+        assert_cell(cell_of(&layout, 1), Cell::new(0.0, 0.0, 0.5, 1.0));
+        assert_cell(cell_of(&layout, 2), Cell::new(0.5, 0.0, 0.5, 0.5));
+        assert_cell(cell_of(&layout, 3), Cell::new(0.5, 0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn bsp_splits_the_focused_window() {
+        let mut layout = bsp(&[1, 2]);
+        layout.insert(3, Some(1), &wide());
+
+        assert_cell(cell_of(&layout, 1), Cell::new(0.0, 0.0, 0.5, 0.5));
+        assert_cell(cell_of(&layout, 3), Cell::new(0.0, 0.5, 0.5, 0.5));
+        assert_cell(cell_of(&layout, 2), Cell::new(0.5, 0.0, 0.5, 1.0));
+    }
+
+    #[test]
+    fn bsp_tiles_always_cover_the_area() {
+        let layout = bsp(&[1, 2, 3, 4, 5, 6, 7]);
+        assert_eq!(layout.windows().len(), 7);
+        assert!((total_area(&layout) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn bsp_insert_ignores_duplicates() {
+        let mut layout = bsp(&[1, 2]);
+        layout.insert(2, None, &wide());
+        assert_eq!(layout.windows(), vec![1, 2]);
+    }
+
+    #[test]
+    fn bsp_remove_gives_space_to_the_sibling() {
+        let mut layout = bsp(&[1, 2, 3]);
+        layout.remove(2);
+
+        assert_eq!(layout.windows(), vec![1, 3]);
+        assert_cell(cell_of(&layout, 3), Cell::new(0.5, 0.0, 0.5, 1.0));
+
+        layout.remove(1);
+        layout.remove(3);
+        assert_eq!(layout, TileLayout::Bsp { root: None });
+    }
+
+    #[test]
+    fn bsp_remove_unknown_window_is_a_no_op() {
+        let mut layout = bsp(&[1, 2]);
+        let before = layout.clone();
+        layout.remove(42);
+        assert_eq!(layout, before);
+    }
+
+    #[test]
+    fn bsp_swap_exchanges_tiles() {
+        let mut layout = bsp(&[1, 2, 3]);
+        let one = cell_of(&layout, 1);
+        let three = cell_of(&layout, 3);
+
+        assert!(layout.swap(1, 3));
+        assert_cell(cell_of(&layout, 3), one);
+        assert_cell(cell_of(&layout, 1), three);
+
+        assert!(!layout.swap(1, 1));
+        assert!(!layout.swap(1, 42));
+    }
+
+    #[test]
+    fn bsp_promote_swaps_with_the_first_leaf() {
+        let mut layout = bsp(&[1, 2, 3]);
+        assert!(layout.promote(3));
+        assert_eq!(layout.windows(), vec![3, 2, 1]);
+        assert!(layout.promote(3));
+        assert!(!layout.promote(42));
+    }
+
+    #[test]
+    fn bsp_resize_moves_the_nearest_split() {
+        let mut layout = bsp(&[1, 2, 3]);
+
+        // 2 and 3 share the right column's vertical split
+        assert!(layout.resize(2, 0.2));
+        assert_cell(cell_of(&layout, 2), Cell::new(0.5, 0.0, 0.5, 0.7));
+        assert_cell(cell_of(&layout, 3), Cell::new(0.5, 0.7, 0.5, 0.3));
+
+        // 1 sits directly under the root split
+        assert!(layout.resize(1, 0.1));
+        assert_cell(cell_of(&layout, 1), Cell::new(0.0, 0.0, 0.6, 1.0));
+
+        assert!(!layout.resize(42, 0.1));
+    }
+
+    #[test]
+    fn resize_is_clamped() {
+        let mut layout = bsp(&[1, 2]);
+        layout.resize(1, 5.0);
+        assert_cell(cell_of(&layout, 1), Cell::new(0.0, 0.0, MAX_RATIO, 1.0));
+        layout.resize(1, -5.0);
+        assert_cell(cell_of(&layout, 1), Cell::new(0.0, 0.0, MIN_RATIO, 1.0));
+    }
+
+    #[test]
+    fn bsp_rotate_turns_clockwise() {
+        let mut layout = bsp(&[1, 2]);
+
+        // [1|2] -> 1 over 2
+        layout.rotate();
+        assert_cell(cell_of(&layout, 1), Cell::new(0.0, 0.0, 1.0, 0.5));
+        assert_cell(cell_of(&layout, 2), Cell::new(0.0, 0.5, 1.0, 0.5));
+
+        // 1 over 2 -> [2|1]
+        layout.rotate();
+        assert_cell(cell_of(&layout, 2), Cell::new(0.0, 0.0, 0.5, 1.0));
+        assert_cell(cell_of(&layout, 1), Cell::new(0.5, 0.0, 0.5, 1.0));
+    }
+
+    #[test]
+    fn bsp_rotate_keeps_uneven_ratios_with_their_window() {
+        let mut layout = bsp(&[1, 2]);
+        layout.resize(1, 0.2);
+        layout.rotate();
+        layout.rotate();
+
+        // Two quarter turns: 1 is now on the right and still 70% wide
+        assert_cell(cell_of(&layout, 1), Cell::new(0.3, 0.0, 0.7, 1.0));
+    }
+
+    #[test]
+    fn master_stack_cells() {
+        let mut layout = TileLayout::new(TilingMode::MasterStack);
+        for id in [1, 2, 3] {
+            layout.insert(id, None, &wide());
+        }
+
+        assert_cell(cell_of(&layout, 1), Cell::new(0.0, 0.0, 0.6, 1.0));
+        assert_cell(cell_of(&layout, 2), Cell::new(0.6, 0.0, 0.4, 0.5));
+        assert_cell(cell_of(&layout, 3), Cell::new(0.6, 0.5, 0.4, 0.5));
+        assert!((total_area(&layout) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn master_stack_single_window_fills_the_area() {
+        let mut layout = TileLayout::new(TilingMode::MasterStack);
+        layout.insert(1, None, &wide());
+        assert_eq!(layout.cells(), vec![(1, Cell::new(0.0, 0.0, 1.0, 1.0))]);
+        // Nothing to resize against
+        assert!(!layout.resize(1, 0.1));
+    }
+
+    #[test]
+    fn master_stack_promote_swap_rotate_resize() {
+        let mut layout = TileLayout::new(TilingMode::MasterStack);
+        for id in [1, 2, 3, 4] {
+            layout.insert(id, None, &wide());
+        }
+
+        assert!(layout.promote(3));
+        assert_eq!(layout.windows(), vec![3, 1, 2, 4]);
+
+        assert!(layout.swap(1, 4));
+        assert_eq!(layout.windows(), vec![3, 4, 2, 1]);
+
+        layout.rotate();
+        assert_eq!(layout.windows(), vec![1, 3, 4, 2]);
+
+        // Growing a stack window shrinks the master
+        assert!(layout.resize(3, 0.1));
+        assert_cell(cell_of(&layout, 1), Cell::new(0.0, 0.0, 0.5, 1.0));
+        assert!(layout.resize(1, 0.2));
+        assert_cell(cell_of(&layout, 1), Cell::new(0.0, 0.0, 0.7, 1.0));
+    }
+
+    #[test]
+    fn sync_diffs_snapshots() {
+        let mut layout = bsp(&[1, 2]);
+
+        assert!(!layout.sync(&[2, 1], None, &wide()));
+        assert!(layout.sync(&[3, 1], Some(1), &wide()));
+
+        let mut windows = layout.windows();
+        windows.sort();
+        assert_eq!(windows, vec![1, 3]);
+        assert!((total_area(&layout) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn sync_inserts_next_to_focus() {
+        let mut layout = bsp(&[1, 2]);
+        layout.sync(&[1, 2, 3], Some(2), &wide());
+        assert_cell(cell_of(&layout, 2), Cell::new(0.5, 0.0, 0.5, 0.5));
+        assert_cell(cell_of(&layout, 3), Cell::new(0.5, 0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn next_window_wraps() {
+        let layout = bsp(&[1, 2, 3]);
+        assert_eq!(layout.next_window(3), Some(1));
+        assert_eq!(bsp(&[1]).next_window(1), None);
+        assert_eq!(layout.next_window(42), None);
+    }
+
+    #[test]
+    fn frames_apply_gaps() {
+        let layout = bsp(&[1, 2]);
+        let settings = SnapSettings { padding: 0.0, gap: 10.0 };
+        assert_eq!(
+            layout.frames(&wide(), &settings),
+            vec![(1, rect(0.0, 0.0, 795.0, 900.0)), (2, rect(805.0, 0.0, 795.0, 900.0))]
+        );
+    }
+
+    #[test]
+    fn retile_arranges_each_display_and_follows_changes() {
+        let fake = FakeBackend::new(
+            vec![
+                window(10, 100, "Safari", "", rect(100.0, 100.0, 300.0, 300.0)),
+                window(11, 200, "Notes", "", rect(200.0, 200.0, 300.0, 300.0)),
+                window(20, 300, "Mail", "", rect(1100.0, 100.0, 300.0, 300.0)),
+                window(2, MY_PID, "My Drawer", "", rect(0.0, 0.0, 400.0, 800.0)),
+            ],
+            vec![display(1, 0.0, 0.0), display(2, 1000.0, 0.0)],
+        );
+        let tiling = TilingManager::new();
+        tiling.set_mode(Some(TilingMode::Bsp));
+        tiling.retile(&fake, &SnapSettings::default(), &[], &[], MY_PID);
+
+        assert_eq!(fake.frame_of(10), Some(rect(0.0, 25.0, 500.0, 775.0)));
+        assert_eq!(fake.frame_of(11), Some(rect(500.0, 25.0, 500.0, 775.0)));
+        assert_eq!(fake.frame_of(20), Some(rect(1000.0, 25.0, 1000.0, 775.0)));
+        assert_eq!(fake.frame_of(2), Some(rect(0.0, 0.0, 400.0, 800.0)));
+
+        // A second pass with nothing changed moves nothing
+        let moves = fake.moved_ids().len();
+        tiling.retile(&fake, &SnapSettings::default(), &[], &[], MY_PID);
+        assert_eq!(fake.moved_ids().len(), moves);
+
+        // Closing a window gives its space back
+        fake.windows.lock().unwrap().retain(|w| w.id != 11);
+        tiling.retile(&fake, &SnapSettings::default(), &[], &[], MY_PID);
+        assert_eq!(fake.frame_of(10), Some(rect(0.0, 25.0, 1000.0, 775.0)));
+    }
+
+    #[test]
+    fn retile_does_nothing_when_off() {
+        let fake = FakeBackend::new(
+            vec![window(10, 100, "Safari", "", rect(100.0, 100.0, 300.0, 300.0))],
+            vec![display(1, 0.0, 0.0)],
+        );
+        let tiling = TilingManager::new();
+        tiling.retile(&fake, &SnapSettings::default(), &[], &[], MY_PID);
+        assert!(fake.moved_ids().is_empty());
+    }
+
+    #[test]
+    fn update_targets_the_layout_holding_the_window() {
+        let fake = FakeBackend::new(
+            vec![
+                window(10, 100, "Safari", "", rect(100.0, 100.0, 300.0, 300.0)),
+                window(11, 200, "Notes", "", rect(200.0, 200.0, 300.0, 300.0)),
+            ],
+            vec![display(1, 0.0, 0.0)],
+        );
+        let tiling = TilingManager::new();
+        tiling.set_mode(Some(TilingMode::MasterStack));
+        tiling.retile(&fake, &SnapSettings::default(), &[], &[], MY_PID);
+
+        assert!(tiling.update(11, |layout| layout.promote(11)));
+        assert!(!tiling.update(42, |layout| layout.promote(42)));
+
+        tiling.retile(&fake, &SnapSettings::default(), &[], &[], MY_PID);
+        assert_eq!(fake.frame_of(11), Some(rect(0.0, 25.0, 600.0, 775.0)));
+    }
+
+    fn no_tile(app: &str) -> CompiledRule {
+        CompiledRule::new(WindowRule {
+            id: "r".into(),
+            name: "Rule".into(),
+            enabled: true,
+            matcher: RuleMatch {
+                app: Some(app.into()),
+                ..Default::default()
+            },
+            action: RuleAction::NoTile,
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn no_tile_windows_are_left_alone() {
+        let fake = FakeBackend::new(
+            vec![
+                window(10, 100, "Safari", "", rect(100.0, 100.0, 300.0, 300.0)),
+                window(11, 200, "Finder", "Copy", rect(200.0, 200.0, 300.0, 300.0)),
+            ],
+            vec![display(1, 0.0, 0.0)],
+        );
+        let tiling = TilingManager::new();
+        tiling.set_mode(Some(TilingMode::Bsp));
+        tiling.retile(&fake, &SnapSettings::default(), &[no_tile("Finder")], &[], MY_PID);
+
+        assert_eq!(fake.frame_of(10), Some(rect(0.0, 25.0, 1000.0, 775.0)));
+        assert_eq!(fake.frame_of(11), Some(rect(200.0, 200.0, 300.0, 300.0)));
+    }
+
+    #[test]
+    fn a_new_no_tile_rule_releases_tiled_windows() {
+        let fake = FakeBackend::new(
+            vec![
+                window(10, 100, "Safari", "", rect(100.0, 100.0, 300.0, 300.0)),
+                window(11, 200, "Finder", "Copy", rect(200.0, 200.0, 300.0, 300.0)),
+            ],
+            vec![display(1, 0.0, 0.0)],
+        );
+        let tiling = TilingManager::new();
+        tiling.set_mode(Some(TilingMode::Bsp));
+        tiling.retile(&fake, &SnapSettings::default(), &[], &[], MY_PID);
+        assert_eq!(fake.frame_of(10), Some(rect(0.0, 25.0, 500.0, 775.0)));
+
+        // Safari takes the whole display once Finder is kept out
+        tiling.retile(&fake, &SnapSettings::default(), &[no_tile("Finder")], &[], MY_PID);
+        assert_eq!(fake.frame_of(10), Some(rect(0.0, 25.0, 1000.0, 775.0)));
+    }
+
+    #[test]
+    fn windows_that_refuse_to_move_are_reported_once() {
+        let fake = FakeBackend::new(
+            vec![window(10, 100, "Safari", "", rect(100.0, 100.0, 300.0, 300.0))],
+            vec![display(1, 0.0, 0.0)],
+        );
+        let refusal = WindowError::AppNotScriptable { app_name: "Safari".into() };
+        *fake.refuse.lock().unwrap() = Some(refusal.clone());
+        let tiling = TilingManager::new();
+        tiling.set_mode(Some(TilingMode::Bsp));
+
+        let failures = tiling.retile(&fake, &SnapSettings::default(), &[], &[], MY_PID);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].0.id, 10);
+        assert_eq!(failures[0].1, refusal);
+
+        *fake.refuse.lock().unwrap() = None;
+        assert!(tiling.retile(&fake, &SnapSettings::default(), &[], &[], MY_PID).is_empty());
+        assert!(fake.moved_ids().is_empty());
+    }
+
+    #[test]
+    fn a_missing_permission_does_not_mark_windows() {
+        let fake = FakeBackend::new(
+            vec![window(10, 100, "Safari", "", rect(100.0, 100.0, 300.0, 300.0))],
+            vec![display(1, 0.0, 0.0)],
+        );
+        *fake.refuse.lock().unwrap() = Some(WindowError::PermissionDenied);
+        let tiling = TilingManager::new();
+        tiling.set_mode(Some(TilingMode::Bsp));
+        assert!(tiling.retile(&fake, &SnapSettings::default(), &[], &[], MY_PID).is_empty());
+
+        *fake.refuse.lock().unwrap() = None;
+        tiling.retile(&fake, &SnapSettings::default(), &[], &[], MY_PID);
+        assert_eq!(fake.moved_ids(), vec![10]);
+    }
+}
@@ -0,0 +1,454 @@
+//! Scripts and styles injected into web blanket tabs, each restricted to the
+//! pages its URL match patterns cover.
+//!
+//! User scripts are stored in the database. The bundled ones (WhatsApp's
+//! sidebar toggle) live in code and only their enabled state is stored.
+
+use crate::error::AppError;
+use crate::settings::open_db;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager};
+
+const WHATSAPP_SCRIPT: &str = include_str!("../scripts/whatsapp.js");
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ScriptKind {
+    /// JavaScript, run once per page
+    #[default]
+    Script,
+    /// CSS, added to the page in a `<style>` element
+    Style,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum RunAt {
+    /// Before the page's own scripts, when only `<html>` exists
+    DocumentStart,
+    /// Once the DOM is parsed
+    #[default]
+    DocumentEnd,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UserScript {
+    /// Generated on save when empty
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    /// Match patterns such as `*://*.example.com/*` or `<all_urls>`
+    pub matches: Vec<String>,
+    #[serde(default)]
+    pub kind: ScriptKind,
+    pub source: String,
+    #[serde(default)]
+    pub run_at: RunAt,
+    /// Leave iframes alone
+    #[serde(default)]
+    pub main_frame_only: bool,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+    /// Shipped with the app. Can be turned off but not edited or deleted.
+    #[serde(default)]
+    pub builtin: bool,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+fn invalid_pattern(pattern: &str, reason: &str) -> AppError {
+    AppError::InvalidMatchPattern {
+        pattern: pattern.to_string(),
+        reason: reason.to_string(),
+    }
+}
+
+// Escapes what both Rust and JavaScript regexes treat as special
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\^$.|?*+()[]{}".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Regex for the URLs a match pattern covers, usable from Rust and from
+/// JavaScript. Patterns follow the browser extension format: `*` as the scheme
+/// means http or https, a `*.` host prefix also matches subdomains, any port
+/// is accepted and `*` in the path matches anything, query included.
+pub fn pattern_regex(pattern: &str) -> Result<String, AppError> {
+    if pattern == "<all_urls>" {
+        return Ok("^(?:https?|file)://.*$".to_string());
+    }
+
+    let (scheme, rest) = pattern
+        .split_once("://")
+        .ok_or_else(|| invalid_pattern(pattern, "missing scheme"))?;
+    let (host, path) = rest
+        .find('/')
+        .map(|i| rest.split_at(i))
+        .ok_or_else(|| invalid_pattern(pattern, "missing path"))?;
+
+    let scheme = match scheme {
+        "*" => "https?".to_string(),
+        "http" | "https" | "file" => scheme.to_string(),
+        _ => return Err(invalid_pattern(pattern, "scheme must be *, http, https or file")),
+    };
+
+    let host = if scheme == "file" {
+        if !host.is_empty() {
+            return Err(invalid_pattern(pattern, "file patterns have no host"));
+        }
+        String::new()
+    } else if host == "*" {
+        "[^/]+".to_string()
+    } else if let Some(domain) = host.strip_prefix("*.") {
+        if domain.is_empty() || domain.contains('*') {
+            return Err(invalid_pattern(pattern, "* must be alone or the first label of the host"));
+        }
+        format!("(?:[^/]+\\.)?{}(?::\\d+)?", escape(domain))
+    } else if host.is_empty() || host.contains('*') {
+        return Err(invalid_pattern(pattern, "* must be alone or the first label of the host"));
+    } else {
+        format!("{}(?::\\d+)?", escape(host))
+    };
+
+    let path = path.split('*').map(escape).collect::<Vec<_>>().join(".*");
+
+    Ok(format!("^{}://{}{}$", scheme, host, path))
+}
+
+impl UserScript {
+    /// The URL patterns as regexes, failing on the first invalid one.
+    fn pattern_regexes(&self) -> Result<Vec<String>, AppError> {
+        if self.matches.is_empty() {
+            return Err(AppError::Other(format!("Script \"{}\" has no match patterns", self.name)));
+        }
+        self.matches.iter().map(|pattern| pattern_regex(pattern)).collect()
+    }
+
+    /// Checks the patterns and that there is something to inject.
+    pub fn validate(&self) -> Result<(), AppError> {
+        if self.name.trim().is_empty() {
+            return Err("Script name cannot be empty".into());
+        }
+        if self.source.trim().is_empty() {
+            return Err(AppError::Other(format!("Script \"{}\" is empty", self.name)));
+        }
+        for regex in self.pattern_regexes()? {
+            Regex::new(&regex).map_err(|e| AppError::Other(e.to_string()))?;
+        }
+        Ok(())
+    }
+
+    /// Whether the script runs on `url`. The fragment is ignored.
+    pub fn matches(&self, url: &str) -> bool {
+        let url = url.split('#').next().unwrap_or_default();
+        self.pattern_regexes()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|regex| Regex::new(regex).ok())
+            .any(|regex| regex.is_match(url))
+    }
+
+    /// JavaScript to register with the webview. A webview injects the same
+    /// scripts into every page it loads, so the source checks the page's URL
+    /// itself and, where injection always happens at document start, waits for
+    /// the DOM when the script runs at document end.
+    pub fn injected_source(&self) -> String {
+        let regexes = self.pattern_regexes().unwrap_or_default();
+        let patterns = regexes
+            .iter()
+            .map(|regex| format!("new RegExp({})", serde_json::to_string(regex).unwrap_or_default()))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let inject = match self.kind {
+            ScriptKind::Script => self.source.clone(),
+            ScriptKind::Style => format!(
+                "const style = document.createElement('style');\n\
+                 style.dataset.userScript = {};\n\
+                 style.textContent = {};\n\
+                 (document.head || document.documentElement).appendChild(style);",
+                serde_json::to_string(&self.id).unwrap_or_default(),
+                serde_json::to_string(&self.source).unwrap_or_default(),
+            ),
+        };
+        let run = match self.run_at {
+            RunAt::DocumentStart => "inject();",
+            RunAt::DocumentEnd => {
+                "if (document.readyState === 'loading') document.addEventListener('DOMContentLoaded', inject);\n\
+                 else inject();"
+            }
+        };
+
+        format!(
+            "(function() {{\n\
+             const url = location.href.split('#')[0];\n\
+             if (![{}].some(re => re.test(url))) return;\n\
+             const inject = () => {{\n{}\n}};\n\
+             {}\n\
+             }})();\n",
+            patterns, inject, run
+        )
+    }
+}
+
+/// The scripts shipped with the app, all enabled.
+pub fn builtin_scripts() -> Vec<UserScript> {
+    vec![UserScript {
+        id: "builtin-whatsapp-sidebar".into(),
+        name: "WhatsApp sidebar toggle".into(),
+        matches: vec!["https://web.whatsapp.com/*".into()],
+        kind: ScriptKind::Script,
+        source: WHATSAPP_SCRIPT.into(),
+        run_at: RunAt::DocumentEnd,
+        main_frame_only: false,
+        enabled: true,
+        builtin: true,
+    }]
+}
+
+/// The bundled scripts with the enabled state saved for them, followed by the
+/// user's own scripts.
+fn merge_builtins(stored: Vec<UserScript>) -> Vec<UserScript> {
+    let mut scripts = builtin_scripts();
+    for script in &mut scripts {
+        if let Some(saved) = stored.iter().find(|s| s.id == script.id) {
+            script.enabled = saved.enabled;
+        }
+    }
+    let builtin_ids: Vec<String> = scripts.iter().map(|s| s.id.clone()).collect();
+    scripts.extend(
+        stored
+            .into_iter()
+            .filter(|s| !builtin_ids.contains(&s.id))
+            .map(|s| UserScript { builtin: false, ..s }),
+    );
+    scripts
+}
+
+/// Every script, as last loaded from the database. Starts with the bundled
+/// ones so tabs opened before the database is read still get them.
+pub struct UserScripts {
+    scripts: Mutex<Vec<UserScript>>,
+}
+
+impl Default for UserScripts {
+    fn default() -> Self {
+        Self {
+            scripts: Mutex::new(builtin_scripts()),
+        }
+    }
+}
+
+impl UserScripts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set(&self, scripts: Vec<UserScript>) {
+        *self.scripts.lock().unwrap() = scripts;
+    }
+
+    pub fn all(&self) -> Vec<UserScript> {
+        self.scripts.lock().unwrap().clone()
+    }
+
+    /// What a new tab gets injected.
+    pub fn enabled(&self) -> Vec<UserScript> {
+        self.all().into_iter().filter(|s| s.enabled).collect()
+    }
+}
+
+async fn load_scripts(app: &AppHandle) -> Result<Vec<UserScript>, AppError> {
+    let pool = open_db(app).await?;
+    let rows: Vec<(String,)> = sqlx::query_as("SELECT data FROM user_scripts ORDER BY created_at")
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    pool.close().await;
+
+    let stored = rows
+        .into_iter()
+        .filter_map(|(data,)| serde_json::from_str(&data).ok())
+        .collect();
+    Ok(merge_builtins(stored))
+}
+
+async fn store_script(app: &AppHandle, script: &UserScript) -> Result<(), AppError> {
+    let data = serde_json::to_string(script).map_err(|e| e.to_string())?;
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let pool = open_db(app).await?;
+    sqlx::query(
+        "INSERT INTO user_scripts (id, name, data, created_at, updated_at) VALUES (?, ?, ?, ?, ?)
+         ON CONFLICT(id) DO UPDATE SET name = excluded.name, data = excluded.data, updated_at = excluded.updated_at",
+    )
+    .bind(&script.id)
+    .bind(&script.name)
+    .bind(&data)
+    .bind(&now)
+    .bind(&now)
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    pool.close().await;
+    Ok(())
+}
+
+/// Reloads the scripts and re-injects them into the open tabs.
+async fn reload(app: &AppHandle) -> Result<(), AppError> {
+    let scripts = load_scripts(app).await?;
+    app.state::<UserScripts>().set(scripts);
+    crate::web_blanket::refresh_user_scripts(app);
+    Ok(())
+}
+
+pub fn load(app: AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = reload(&app).await {
+            eprintln!("Failed to load user scripts: {}", e);
+        }
+    });
+}
+
+#[tauri::command]
+pub fn list_user_scripts(scripts: tauri::State<'_, UserScripts>) -> Vec<UserScript> {
+    scripts.all()
+}
+
+/// Saves a new script, or replaces one of the user's own with the same id.
+#[tauri::command]
+pub async fn add_user_script(app: AppHandle, mut script: UserScript) -> Result<UserScript, AppError> {
+    if script.id.is_empty() {
+        script.id = uuid::Uuid::new_v4().to_string();
+    }
+    if builtin_scripts().iter().any(|s| s.id == script.id) {
+        return Err("Bundled scripts cannot be edited".into());
+    }
+    script.name = script.name.trim().to_string();
+    script.matches = script.matches.iter().map(|m| m.trim().to_string()).collect();
+    script.builtin = false;
+    script.validate()?;
+
+    store_script(&app, &script).await?;
+    reload(&app).await?;
+    Ok(script)
+}
+
+#[tauri::command]
+pub async fn toggle_user_script(app: AppHandle, id: String, enabled: bool) -> Result<UserScript, AppError> {
+    let mut script = load_scripts(&app)
+        .await?
+        .into_iter()
+        .find(|s| s.id == id)
+        .ok_or_else(|| AppError::Other(format!("No user script with id {}", id)))?;
+    script.enabled = enabled;
+
+    store_script(&app, &script).await?;
+    reload(&app).await?;
+    Ok(script)
+}
+
+#[tauri::command]
+pub async fn delete_user_script(app: AppHandle, id: String) -> Result<(), AppError> {
+    if builtin_scripts().iter().any(|s| s.id == id) {
+        return Err("Bundled scripts can only be turned off".into());
+    }
+    let pool = open_db(&app).await?;
+    sqlx::query("DELETE FROM user_scripts WHERE id = ?")
+        .bind(&id)
+        .execute(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    pool.close().await;
+
+    reload(&app).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn script(matches: &[&str]) -> UserScript {
+        UserScript {
+            id: "s".into(),
+            name: "Script".into(),
+            matches: matches.iter().map(|m| m.to_string()).collect(),
+            kind: ScriptKind::Script,
+            source: "console.log(1);".into(),
+            run_at: RunAt::DocumentEnd,
+            main_frame_only: true,
+            enabled: true,
+            builtin: false,
+        }
+    }
+
+    #[test]
+    fn patterns_match_scheme_host_and_path() {
+        let docs = script(&["*://*.example.com/docs/*"]);
+        assert!(docs.matches("https://example.com/docs/intro"));
+        assert!(docs.matches("http://www.example.com:8080/docs/a?b=c#top"));
+        assert!(!docs.matches("https://example.com/blog/docs/"));
+        assert!(!docs.matches("https://notexample.com/docs/"));
+        assert!(!docs.matches("ftp://example.com/docs/"));
+
+        let exact = script(&["https://web.whatsapp.com/*"]);
+        assert!(exact.matches("https://web.whatsapp.com/"));
+        assert!(!exact.matches("http://web.whatsapp.com/"));
+        assert!(!exact.matches("https://web.whatsapp.com.evil.io/"));
+
+        let all = script(&["<all_urls>"]);
+        assert!(all.matches("file:///tmp/index.html"));
+        assert!(!all.matches("about:blank"));
+    }
+
+    #[test]
+    fn invalid_patterns_are_rejected() {
+        for pattern in ["example.com/*", "*://example.com", "ws://example.com/*", "*://exa*mple.com/*", "file://host/*"] {
+            assert!(
+                matches!(pattern_regex(pattern), Err(AppError::InvalidMatchPattern { .. })),
+                "{} should be invalid",
+                pattern
+            );
+        }
+        assert!(script(&[]).validate().is_err());
+        assert!(script(&["*://*/*"]).validate().is_ok());
+    }
+
+    #[test]
+    fn injected_source_checks_the_url_and_wraps_styles() {
+        let mut style = script(&["*://example.com/*"]);
+        style.kind = ScriptKind::Style;
+        style.source = "body { color: red; }".into();
+
+        let source = style.injected_source();
+        assert!(source.contains(r#"new RegExp("^https?://example\\.com(?::\\d+)?/.*$")"#));
+        assert!(source.contains(r#"style.textContent = "body { color: red; }";"#));
+        assert!(source.contains("DOMContentLoaded"));
+    }
+
+    #[test]
+    fn builtins_keep_their_source_but_take_the_saved_state() {
+        let mut saved_builtin = builtin_scripts().remove(0);
+        saved_builtin.enabled = false;
+        saved_builtin.source = "stale".into();
+        let mut own = script(&["*://*/*"]);
+        own.builtin = true;
+
+        let merged = merge_builtins(vec![own, saved_builtin]);
+        assert_eq!(merged.len(), 2);
+        assert!(merged[0].builtin && !merged[0].enabled);
+        assert_eq!(merged[0].source, WHATSAPP_SCRIPT);
+        assert!(!merged[1].builtin);
+    }
+}
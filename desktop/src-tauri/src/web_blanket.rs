@@ -1,7 +1,17 @@
+use crate::error::AppError;
+use crate::user_scripts::UserScripts;
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex, Once};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Manager, Runtime, WebviewWindow, Emitter, EventTarget};
+
+#[cfg(target_os = "macos")]
+use crate::page_bridge;
+#[cfg(target_os = "macos")]
+use crate::user_scripts::{RunAt, UserScript};
+#[cfg(target_os = "macos")]
 use std::ffi::c_void;
-use tauri::{AppHandle, Manager, Runtime, WebviewWindow, Emitter};
+#[cfg(target_os = "macos")]
+use std::sync::Once;
 
 #[cfg(target_os = "macos")]
 use objc::declare::ClassDecl;
@@ -16,122 +26,59 @@ use objc::runtime::{Object, Sel};
 
 const DESKTOP_USER_AGENT: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.4 Safari/605.1.15";
 const MOBILE_USER_AGENT: &str = "Mozilla/5.0 (iPhone; CPU iPhone OS 17_4 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/17.4 Mobile/15E148 Safari/604.1";
-
-const WHATSAPP_SCRIPT: &str = r#"
-(function() {
-    if (!window.location.host.includes('whatsapp.com')) return;
-    const BTN_ID = 'gemini-wa-toggle';
-    
-    // Inject Styles
-    if (!document.getElementById('gemini-wa-style')) {
-         const style = document.createElement('style');
-         style.id = 'gemini-wa-style';
-         style.innerHTML = `
-            * { border-inline-start-width: 0px !important; }
-            
-            /* Custom Scrollbar */
-            ::-webkit-scrollbar {
-                width: 6px !important;
-                height: 6px !important;
-            }
-            ::-webkit-scrollbar-track {
-                background: transparent !important;
-            }
-            ::-webkit-scrollbar-thumb {
-                background-color: rgba(128, 128, 128, 0.4) !important;
-                border-radius: 3px !important;
-            }
-            ::-webkit-scrollbar-thumb:hover {
-                background-color: rgba(128, 128, 128, 0.6) !important;
-            }
-            
-            /* Hide sidebar wrapper when closed */
-            body.gemini-sidebar-closed :has(> #side) {
-                display: none !important;
-            }
-            
-            /* Fix main width when sidebar is closed */
-            body.gemini-sidebar-closed :has(> #main) {
-                max-width: calc(100vw - 64px) !important;
-            }
-
-            /* Icon Toggling */
-            /* Default (Sidebar Open): Show X (Close) */
-            .gemini-icon-close { display: block !important; }
-            .gemini-icon-menu { display: none !important; }
-
-            /* Sidebar Closed: Show Menu */
-            body.gemini-sidebar-closed .gemini-icon-close { display: none !important; }
-            body.gemini-sidebar-closed .gemini-icon-menu { display: block !important; }
-         `;
-         document.head.appendChild(style);
-    }
-
-    setInterval(() => {
-        if (document.getElementById(BTN_ID)) return;
-        
-        const firstNavItem = document.querySelector('[data-navbar-item]');
-        if (!firstNavItem) return;
-        
-        // Target: parent -> parent -> parent
-        const targetContainer = firstNavItem.parentElement?.parentElement?.parentElement;
-        if (!targetContainer) return;
-
-        const btn = document.createElement('div');
-        btn.id = BTN_ID;
-        
-        // X Icon (Close Sidebar)
-        const closeIcon = '<svg class="gemini-icon-close" viewBox="0 0 24 24" width="24" height="24" fill="currentColor"><path d="M19 6.41L17.59 5 12 10.59 6.41 5 5 6.41 10.59 12 5 17.59 6.41 19 12 13.41 17.59 19 19 17.59 13.41 12z"></path></svg>';
-        
-        // Menu Icon (Open Sidebar)
-        const menuIcon = '<svg class="gemini-icon-menu" viewBox="0 0 24 24" width="24" height="24" fill="currentColor"><path d="M3 18h18v-2H3v2zm0-5h18v-2H3v2zm0-7v2h18V6H3z"></path></svg>';
-
-        btn.innerHTML = `<div role="button" title="Toggle Sidebar" style="width: 100%; height: 100%; display: flex; align-items: center; justify-content: center;">${closeIcon}${menuIcon}</div>`;
-        
-        btn.style.cssText = 'height: 40px; width: 40px; display: flex; align-items: center; justify-content: center; cursor: pointer; border-radius: 50%; transition: background-color 0.2s; margin: 0 auto; margin-bottom: 8px; color: var(--icon, #54656f); z-index: 1000;';
-        
-        btn.onmouseenter = () => btn.style.backgroundColor = 'var(--background-default-hover, rgba(0,0,0,0.05))';
-        btn.onmouseleave = () => btn.style.backgroundColor = 'transparent';
-
-        btn.onclick = () => {
-            document.body.classList.toggle('gemini-sidebar-closed');
-            window.dispatchEvent(new Event('resize'));
-        };
-        
-        targetContainer.insertBefore(btn, targetContainer.firstChild);
-
-    }, 1000);
-})();
-"#;
+// Page zoom range for the zoom in/out commands
+const MIN_ZOOM: f64 = 0.5;
+const MAX_ZOOM: f64 = 3.0;
 
 // Wrapper for Objective-C pointers to be Send + Sync
+#[cfg(target_os = "macos")]
 #[derive(Clone, Copy, Debug)]
 struct SafeId(pub *mut Object);
 
+#[cfg(target_os = "macos")]
 unsafe impl Send for SafeId {}
+#[cfg(target_os = "macos")]
 unsafe impl Sync for SafeId {}
 
+#[cfg(target_os = "macos")]
 impl From<id> for SafeId {
     fn from(p: id) -> Self {
         Self(p)
     }
 }
 
+#[cfg(target_os = "macos")]
 impl SafeId {
     fn as_id(&self) -> id {
         self.0
     }
 }
 
+// On macOS each tab is a WKWebView inside our own container NSView.
+// Elsewhere each tab is a Tauri child webview attached to the drawer window.
+#[cfg(target_os = "macos")]
+type TabHandle = SafeId;
+#[cfg(not(target_os = "macos"))]
+type TabHandle = ChildTab;
+
 pub struct WebBlanketState {
     inner: Mutex<WebBlanketInner>,
 }
 
 struct WebBlanketInner {
     // Container view hosting all WKWebViews
+    #[cfg(target_os = "macos")]
     container_view: Option<SafeId>,
+    // Compiled WKContentRuleList attached to every tab, retained
+    #[cfg(target_os = "macos")]
+    content_rules: Option<SafeId>,
+    // Whether the blanket is currently shown (child webviews have no shared container)
+    #[cfg(not(target_os = "macos"))]
+    visible: bool,
     // Map of tab_id -> WKWebView
-    tabs: HashMap<String, SafeId>,
+    tabs: HashMap<String, TabHandle>,
+    // Tab ids in the order they were created
+    tab_order: Vec<String>,
     active_tab_id: Option<String>,
     // Last bounds set by frontend
     last_bounds: Option<Bounds>,
@@ -151,13 +98,78 @@ impl WebBlanketState {
     pub fn new() -> Self {
         Self {
             inner: Mutex::new(WebBlanketInner {
+                #[cfg(target_os = "macos")]
                 container_view: None,
+                #[cfg(target_os = "macos")]
+                content_rules: None,
+                #[cfg(not(target_os = "macos"))]
+                visible: false,
                 tabs: HashMap::new(),
+                tab_order: Vec::new(),
                 active_tab_id: None,
                 last_bounds: None,
             }),
         }
     }
+
+    /// The open tabs, the URL each one is currently showing and its user agent.
+    pub fn session(&self) -> Result<WebSession, AppError> {
+        let inner = self.inner.lock()?;
+        let tabs = inner
+            .tab_order
+            .iter()
+            .filter_map(|tab_id| {
+                let tab = inner.tabs.get(tab_id)?;
+                #[cfg(target_os = "macos")]
+                let (url, title, desktop) = unsafe {
+                    (
+                        webview_url(tab.as_id()),
+                        webview_title(tab.as_id()),
+                        webview_is_desktop(tab.as_id()),
+                    )
+                };
+                #[cfg(not(target_os = "macos"))]
+                let (url, title, desktop) = (
+                    tab.webview.url().map(|u| u.to_string()).unwrap_or_default(),
+                    tab.page.lock().unwrap().title.clone(),
+                    tab.desktop,
+                );
+                Some(WebTabSession {
+                    id: tab_id.clone(),
+                    url,
+                    title,
+                    user_agent: if desktop { "desktop" } else { "mobile" }.to_string(),
+                })
+            })
+            .collect();
+
+        Ok(WebSession {
+            tabs,
+            active_tab_id: inner.active_tab_id.clone(),
+        })
+    }
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct WebSession {
+    pub tabs: Vec<WebTabSession>,
+    pub active_tab_id: Option<String>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WebTabSession {
+    pub id: String,
+    pub url: String,
+    pub title: String,
+    /// `"desktop"` or `"mobile"`, as passed to `web_blanket_set_user_agent`
+    #[serde(default = "mobile_user_agent")]
+    pub user_agent: String,
+}
+
+fn mobile_user_agent() -> String {
+    "mobile".to_string()
 }
 
 #[derive(serde::Deserialize, Clone, Copy, Debug)]
@@ -184,17 +196,72 @@ impl From<BoundsPayload> for Bounds {
 }
 
 // -----------------------------------------------------------------------------
-// UI Delegate
+// Tab Events
 // -----------------------------------------------------------------------------
+
+/// Emitted on every page change so the frontend doesn't have to poll
+/// `web_blanket_get_tab_state`.
+pub const TAB_EVENT: &str = "web-blanket:tab";
+
+/// Something that happened in a tab's page, sent as e.g.
+/// `{ "tabId": "t1", "type": "finished", "url": "https://example.com/" }`.
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TabEvent {
+    pub tab_id: String,
+    #[serde(flatten)]
+    pub kind: TabEventKind,
+}
+
+#[derive(serde::Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum TabEventKind {
+    /// A navigation began; the page may still be replaced by a redirect
+    Started { url: String },
+    /// The new page's content started arriving
+    Committed { url: String },
+    Finished { url: String },
+    /// `code` is the platform's error code, e.g. an `NSURLErrorDomain` value
+    Failed { url: String, code: i64, message: String },
+    /// Estimated load progress from 0 to 1
+    Progress { progress: f64 },
+    Title { title: String },
+    /// The URL changed without a navigation, e.g. through `history.pushState`
+    UrlChanged { url: String },
+    History { can_go_back: bool, can_go_forward: bool },
+}
+
+fn emit_tab_event(window: &WebviewWindow, tab_id: &str, kind: TabEventKind) {
+    let event = TabEvent {
+        tab_id: tab_id.to_string(),
+        kind,
+    };
+    // Only the window showing the tab tracks it
+    let _ = window.emit_to(EventTarget::webview_window(window.label()), TAB_EVENT, event);
+}
+
+// -----------------------------------------------------------------------------
+// Tab Delegate
+// -----------------------------------------------------------------------------
+// One per WKWebView: its UI and navigation delegate, and the observer of the
+// page properties that have no delegate callback.
 #[cfg(target_os = "macos")]
 static DELEGATE_CLASS: Once = Once::new();
 
+#[cfg(target_os = "macos")]
+const OBSERVED_KEY_PATHS: [&str; 5] = ["title", "URL", "estimatedProgress", "canGoBack", "canGoForward"];
+
+// NSURLErrorCancelled: the navigation was replaced by another one
+#[cfg(target_os = "macos")]
+const NSURL_ERROR_CANCELLED: isize = -999;
+
 #[cfg(target_os = "macos")]
 fn get_delegate_class() -> &'static objc::runtime::Class {
     DELEGATE_CLASS.call_once(|| {
-        let mut decl = ClassDecl::new("WebBlanketUIDelegate", class!(NSObject)).unwrap();
+        let mut decl = ClassDecl::new("WebBlanketTabDelegate", class!(NSObject)).unwrap();
         
         decl.add_ivar::<*mut c_void>("window_ptr");
+        decl.add_ivar::<*mut c_void>("tab_id_ptr");
 
         extern "C" fn dealloc(this: &Object, _sel: Sel) {
             unsafe {
@@ -202,6 +269,10 @@ fn get_delegate_class() -> &'static objc::runtime::Class {
                 if !ptr.is_null() {
                     let _ = Box::from_raw(ptr as *mut WebviewWindow);
                 }
+                let ptr: *mut c_void = *this.get_ivar("tab_id_ptr");
+                if !ptr.is_null() {
+                    let _ = Box::from_raw(ptr as *mut String);
+                }
                 let _: () = msg_send![super(this, class!(NSObject)), dealloc];
             }
         }
@@ -232,6 +303,104 @@ fn get_delegate_class() -> &'static objc::runtime::Class {
             }
             nil
         }
+
+        extern "C" fn did_start(this: &Object, _sel: Sel, webview: id, _navigation: id) {
+            unsafe { emit_from_delegate(this, TabEventKind::Started { url: webview_url(webview) }) }
+        }
+
+        extern "C" fn did_commit(this: &Object, _sel: Sel, webview: id, _navigation: id) {
+            unsafe { emit_from_delegate(this, TabEventKind::Committed { url: webview_url(webview) }) }
+        }
+
+        extern "C" fn did_finish(this: &Object, _sel: Sel, webview: id, _navigation: id) {
+            unsafe { emit_from_delegate(this, TabEventKind::Finished { url: webview_url(webview) }) }
+        }
+
+        extern "C" fn did_fail(this: &Object, _sel: Sel, webview: id, _navigation: id, error: id) {
+            unsafe {
+                let code: isize = msg_send![error, code];
+                if code == NSURL_ERROR_CANCELLED {
+                    return;
+                }
+                let description: id = msg_send![error, localizedDescription];
+
+                // A failed provisional navigation never changed the webview's URL
+                let info: id = msg_send![error, userInfo];
+                let key = NSString::alloc(nil).init_str("NSErrorFailingURLStringKey");
+                let failing_url: id = msg_send![info, objectForKey: key];
+                let _: () = msg_send![key, release];
+                let url = if failing_url != nil { nsstring_to_string(failing_url) } else { webview_url(webview) };
+
+                emit_from_delegate(this, TabEventKind::Failed {
+                    url,
+                    code: code as i64,
+                    message: nsstring_to_string(description),
+                });
+            }
+        }
+
+        // WKScriptMessageHandler, for calls through `window.myDrawer`
+        extern "C" fn did_receive_script_message(this: &Object, _sel: Sel, _controller: id, message: id) {
+            unsafe {
+                let frame: id = msg_send![message, frameInfo];
+                let main_frame: bool = msg_send![frame, isMainFrame];
+                let body: id = msg_send![message, body];
+                let body_is_string: bool = msg_send![body, isKindOfClass: class!(NSString)];
+                if !main_frame || !body_is_string {
+                    return;
+                }
+
+                let security_origin: id = msg_send![frame, securityOrigin];
+                let protocol: id = msg_send![security_origin, protocol];
+                let host: id = msg_send![security_origin, host];
+                let port: isize = msg_send![security_origin, port];
+                let Some(origin) = page_bridge::format_origin(
+                    &nsstring_to_string(protocol),
+                    &nsstring_to_string(host),
+                    u16::try_from(port).unwrap_or(0),
+                ) else {
+                    return;
+                };
+
+                let window_ptr: *mut c_void = *this.get_ivar("window_ptr");
+                let tab_id_ptr: *mut c_void = *this.get_ivar("tab_id_ptr");
+                if window_ptr.is_null() || tab_id_ptr.is_null() {
+                    return;
+                }
+                let window = &*(window_ptr as *mut WebviewWindow);
+                let tab_id = &*(tab_id_ptr as *mut String);
+                let webview: id = msg_send![message, webView];
+
+                page_bridge::receive(
+                    window.app_handle(),
+                    window.label().to_string(),
+                    tab_id.clone(),
+                    origin,
+                    webview_url(webview),
+                    &nsstring_to_string(body),
+                );
+            }
+        }
+
+        extern "C" fn observe_value(this: &Object, _sel: Sel, key_path: id, webview: id, _change: id, _context: *mut c_void) {
+            unsafe {
+                let kind = match nsstring_to_string(key_path).as_str() {
+                    "title" => TabEventKind::Title { title: webview_title(webview) },
+                    "URL" => TabEventKind::UrlChanged { url: webview_url(webview) },
+                    "estimatedProgress" => {
+                        let progress: f64 = msg_send![webview, estimatedProgress];
+                        TabEventKind::Progress { progress }
+                    }
+                    "canGoBack" | "canGoForward" => {
+                        let can_go_back: bool = msg_send![webview, canGoBack];
+                        let can_go_forward: bool = msg_send![webview, canGoForward];
+                        TabEventKind::History { can_go_back, can_go_forward }
+                    }
+                    _ => return,
+                };
+                emit_from_delegate(this, kind);
+            }
+        }
         
         unsafe {
             decl.add_method(sel!(dealloc), dealloc as extern "C" fn(&Object, Sel));
@@ -239,11 +408,82 @@ fn get_delegate_class() -> &'static objc::runtime::Class {
                 sel!(webView:createWebViewWithConfiguration:forNavigationAction:windowFeatures:),
                 create_webview as extern "C" fn(&Object, Sel, id, id, id, id) -> id
             );
+            decl.add_method(
+                sel!(webView:didStartProvisionalNavigation:),
+                did_start as extern "C" fn(&Object, Sel, id, id)
+            );
+            decl.add_method(
+                sel!(webView:didCommitNavigation:),
+                did_commit as extern "C" fn(&Object, Sel, id, id)
+            );
+            decl.add_method(
+                sel!(webView:didFinishNavigation:),
+                did_finish as extern "C" fn(&Object, Sel, id, id)
+            );
+            decl.add_method(
+                sel!(webView:didFailNavigation:withError:),
+                did_fail as extern "C" fn(&Object, Sel, id, id, id)
+            );
+            decl.add_method(
+                sel!(webView:didFailProvisionalNavigation:withError:),
+                did_fail as extern "C" fn(&Object, Sel, id, id, id)
+            );
+            decl.add_method(
+                sel!(userContentController:didReceiveScriptMessage:),
+                did_receive_script_message as extern "C" fn(&Object, Sel, id, id)
+            );
+            decl.add_method(
+                sel!(observeValueForKeyPath:ofObject:change:context:),
+                observe_value as extern "C" fn(&Object, Sel, id, id, id, *mut c_void)
+            );
         }
 
         decl.register();
     });
-    class!(WebBlanketUIDelegate)
+    class!(WebBlanketTabDelegate)
+}
+
+#[cfg(target_os = "macos")]
+unsafe fn emit_from_delegate(delegate: &Object, kind: TabEventKind) {
+    let window_ptr: *mut c_void = *delegate.get_ivar("window_ptr");
+    let tab_id_ptr: *mut c_void = *delegate.get_ivar("tab_id_ptr");
+    if window_ptr.is_null() || tab_id_ptr.is_null() {
+        return;
+    }
+    let window = &*(window_ptr as *mut WebviewWindow);
+    let tab_id = &*(tab_id_ptr as *mut String);
+    emit_tab_event(window, tab_id, kind);
+}
+
+#[cfg(target_os = "macos")]
+unsafe fn add_tab_observers(webview: id, delegate: id) {
+    for key_path in OBSERVED_KEY_PATHS {
+        let key = NSString::alloc(nil).init_str(key_path);
+        // NSKeyValueObservingOptionNew
+        let _: () = msg_send![webview, addObserver:delegate forKeyPath:key options:1usize context:std::ptr::null_mut::<c_void>()];
+        let _: () = msg_send![key, release];
+    }
+}
+
+/// Detaches the tab's delegate so a closing webview sends no more events.
+#[cfg(target_os = "macos")]
+unsafe fn detach_tab_delegate(webview: id) {
+    let delegate: id = msg_send![webview, UIDelegate];
+    if delegate == nil {
+        return;
+    }
+    for key_path in OBSERVED_KEY_PATHS {
+        let key = NSString::alloc(nil).init_str(key_path);
+        let _: () = msg_send![webview, removeObserver:delegate forKeyPath:key];
+        let _: () = msg_send![key, release];
+    }
+    let _: () = msg_send![webview, setNavigationDelegate: nil];
+
+    let config: id = msg_send![webview, configuration];
+    let controller: id = msg_send![config, userContentController];
+    let name = NSString::alloc(nil).init_str(page_bridge::MESSAGE_HANDLER);
+    let _: () = msg_send![controller, removeScriptMessageHandlerForName: name];
+    let _: () = msg_send![name, release];
 }
 
 #[cfg(target_os = "macos")]
@@ -264,10 +504,10 @@ static ASSOCIATED_DELEGATE_KEY: u8 = 0;
 pub fn web_blanket_show(
     window: WebviewWindow,
     state: tauri::State<WebBlanketState>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     #[cfg(target_os = "macos")]
     {
-        let mut inner = state.inner.lock().map_err(|e| e.to_string())?;
+        let mut inner = state.inner.lock()?;
         ensure_container(&window, &mut inner)?;
         
         if let Some(container) = inner.container_view {
@@ -278,17 +518,26 @@ pub fn web_blanket_show(
         Ok(())
     }
     #[cfg(not(target_os = "macos"))]
-    Err("Not supported on this OS".into())
+    {
+        let _ = window;
+        let mut inner = state.inner.lock()?;
+        inner.visible = true;
+
+        if let Some(tab) = inner.active_tab_id.as_ref().and_then(|tid| inner.tabs.get(tid)) {
+            tab.webview.show()?;
+        }
+        Ok(())
+    }
 }
 
 #[tauri::command]
 pub fn web_blanket_hide(
     window: WebviewWindow,
     state: tauri::State<WebBlanketState>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     #[cfg(target_os = "macos")]
     {
-        let inner = state.inner.lock().map_err(|e| e.to_string())?;
+        let inner = state.inner.lock()?;
         if let Some(container) = inner.container_view {
             unsafe {
                 let _: () = msg_send![container.as_id(), setHidden: true];
@@ -297,7 +546,16 @@ pub fn web_blanket_hide(
         Ok(())
     }
     #[cfg(not(target_os = "macos"))]
-    Err("Not supported on this OS".into())
+    {
+        let _ = window;
+        let mut inner = state.inner.lock()?;
+        inner.visible = false;
+
+        for tab in inner.tabs.values() {
+            tab.webview.hide()?;
+        }
+        Ok(())
+    }
 }
 
 #[tauri::command]
@@ -305,10 +563,10 @@ pub fn web_blanket_set_bounds(
     window: WebviewWindow,
     state: tauri::State<WebBlanketState>,
     bounds: BoundsPayload,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     #[cfg(target_os = "macos")]
     {
-        let mut inner = state.inner.lock().map_err(|e| e.to_string())?;
+        let mut inner = state.inner.lock()?;
         let b: Bounds = bounds.into();
         inner.last_bounds = Some(b);
         
@@ -322,7 +580,17 @@ pub fn web_blanket_set_bounds(
         Ok(())
     }
     #[cfg(not(target_os = "macos"))]
-    Err("Not supported on this OS".into())
+    {
+        let _ = window;
+        let mut inner = state.inner.lock()?;
+        let b: Bounds = bounds.into();
+        inner.last_bounds = Some(b);
+
+        for tab in inner.tabs.values() {
+            update_child_frame(&tab.webview, &b);
+        }
+        Ok(())
+    }
 }
 
 #[tauri::command]
@@ -331,10 +599,10 @@ pub fn web_blanket_tab_create(
     state: tauri::State<WebBlanketState>,
     tab_id: String,
     url: Option<String>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     #[cfg(target_os = "macos")]
     {
-        let mut inner = state.inner.lock().map_err(|e| e.to_string())?;
+        let mut inner = state.inner.lock()?;
         
         if inner.tabs.contains_key(&tab_id) {
             return Ok(());
@@ -343,21 +611,19 @@ pub fn web_blanket_tab_create(
         ensure_container(&window, &mut inner)?;
 
         let container = inner.container_view
-            .ok_or("Container view not initialized")?
+            .ok_or(AppError::WebBlanketUnavailable)?
             .as_id();
         
         // Create WKWebView
         unsafe {
             let config: id = msg_send![class!(WKWebViewConfiguration), new];
 
-            // Inject WhatsApp Script
-            let script_source = NSString::alloc(nil).init_str(WHATSAPP_SCRIPT);
+            // Inject user scripts
             let user_content_controller: id = msg_send![config, userContentController];
-            let user_script: id = msg_send![class!(WKUserScript), alloc];
-            let _: () = msg_send![user_script, initWithSource:script_source injectionTime:1isize forMainFrameOnly:false];
-            let _: () = msg_send![user_content_controller, addUserScript: user_script];
-            let _: () = msg_send![user_script, release];
-            let _: () = msg_send![script_source, release];
+            install_user_scripts(user_content_controller, &window.state::<UserScripts>().enabled());
+            if let Some(rules) = inner.content_rules {
+                let _: () = msg_send![user_content_controller, addContentRuleList: rules.as_id()];
+            }
             
             // Enforce Mobile Content Mode
             let prefs: id = msg_send![config, defaultWebpagePreferences];
@@ -401,8 +667,18 @@ pub fn web_blanket_tab_create(
             let window_clone = window.clone();
             let window_ptr = Box::into_raw(Box::new(window_clone));
             (*delegate).set_ivar("window_ptr", window_ptr as *mut c_void);
+            let tab_id_ptr = Box::into_raw(Box::new(tab_id.clone()));
+            (*delegate).set_ivar("tab_id_ptr", tab_id_ptr as *mut c_void);
 
             let _: () = msg_send![webview, setUIDelegate: delegate];
+            let _: () = msg_send![webview, setNavigationDelegate: delegate];
+            add_tab_observers(webview, delegate);
+
+            // Calls through `window.myDrawer`. The controller retains the delegate
+            // until detach_tab_delegate removes it.
+            let handler_name = NSString::alloc(nil).init_str(page_bridge::MESSAGE_HANDLER);
+            let _: () = msg_send![user_content_controller, addScriptMessageHandler:delegate name:handler_name];
+            let _: () = msg_send![handler_name, release];
             
             // Keep delegate alive
             objc_setAssociatedObject(webview, &ASSOCIATED_DELEGATE_KEY as *const u8 as *const c_void, delegate, OBJC_ASSOCIATION_RETAIN_NONATOMIC);
@@ -411,21 +687,31 @@ pub fn web_blanket_tab_create(
 
             // Store in map
             inner.tabs.insert(tab_id.clone(), SafeId(webview));
+            inner.tab_order.push(tab_id.clone());
             
             // If url provided, load it
             if let Some(u) = url {
                 load_url(webview, &u);
             }
-            
-            // TODO: Implement WKNavigationDelegate or Polling Timer here to emit events:
-            // - web-blanket:navigation (url, canGoBack, canGoForward, isLoading)
-            // - web-blanket:title
         }
         
         Ok(())
     }
     #[cfg(not(target_os = "macos"))]
-    Err("Not supported on this OS".into())
+    {
+        let mut inner = state.inner.lock()?;
+
+        if inner.tabs.contains_key(&tab_id) {
+            return Ok(());
+        }
+
+        let url = url.unwrap_or_else(|| "about:blank".to_string());
+        let tab = create_child_tab(&window, &inner, &tab_id, &url, false, 0.8)?;
+        inner.tabs.insert(tab_id.clone(), tab);
+        inner.tab_order.push(tab_id);
+
+        Ok(())
+    }
 }
 
 #[tauri::command]
@@ -433,10 +719,10 @@ pub fn web_blanket_tab_activate(
     _window: WebviewWindow,
     state: tauri::State<WebBlanketState>,
     tab_id: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     #[cfg(target_os = "macos")]
     {
-        let mut inner = state.inner.lock().map_err(|e| e.to_string())?;
+        let mut inner = state.inner.lock()?;
         
         // Hide current active tab
         if let Some(current_id) = &inner.active_tab_id {
@@ -454,28 +740,52 @@ pub fn web_blanket_tab_activate(
             }
             inner.active_tab_id = Some(tab_id);
         } else {
-            return Err("Tab not found".into());
+            return Err(AppError::TabNotFound { tab_id });
         }
         
         Ok(())
     }
     #[cfg(not(target_os = "macos"))]
-    Err("Not supported on this OS".into())
+    {
+        let mut inner = state.inner.lock()?;
+
+        // Hide current active tab
+        if let Some(tab) = inner.active_tab_id.as_ref().and_then(|tid| inner.tabs.get(tid)) {
+            tab.webview.hide().unwrap_or(());
+        }
+
+        // Show new tab
+        if let Some(tab) = inner.tabs.get(&tab_id) {
+            if inner.visible {
+                tab.webview.show().unwrap_or(());
+            }
+            inner.active_tab_id = Some(tab_id);
+        } else {
+            return Err(AppError::TabNotFound { tab_id });
+        }
+
+        Ok(())
+    }
 }
 
 #[tauri::command]
 pub fn web_blanket_tab_close(
     _window: WebviewWindow,
     state: tauri::State<WebBlanketState>,
+    bridge: tauri::State<crate::page_bridge::PageBridge>,
     tab_id: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
+    // Calls the page was still waiting on can no longer be answered
+    bridge.forget_tab(&tab_id);
+
     #[cfg(target_os = "macos")]
     {
-        let mut inner = state.inner.lock().map_err(|e| e.to_string())?;
+        let mut inner = state.inner.lock()?;
         
         if let Some(webview) = inner.tabs.remove(&tab_id) {
             unsafe {
                 let wv = webview.as_id();
+                detach_tab_delegate(wv);
                 
                 // Pause all media explicitly (fire and forget)
                 let script = NSString::alloc(nil).init_str("document.querySelectorAll('video, audio').forEach(e => e.pause());");
@@ -491,6 +801,7 @@ pub fn web_blanket_tab_close(
                 let _: () = msg_send![wv, release];
             }
             
+            inner.tab_order.retain(|tid| tid != &tab_id);
             if inner.active_tab_id.as_ref() == Some(&tab_id) {
                 inner.active_tab_id = None;
             }
@@ -499,7 +810,21 @@ pub fn web_blanket_tab_close(
         Ok(())
     }
     #[cfg(not(target_os = "macos"))]
-    Err("Not supported on this OS".into())
+    {
+        let mut inner = state.inner.lock()?;
+
+        if let Some(tab) = inner.tabs.remove(&tab_id) {
+            // Closing the webview tears down its media as well
+            tab.webview.close().unwrap_or(());
+            inner.tab_order.retain(|tid| tid != &tab_id);
+
+            if inner.active_tab_id.as_ref() == Some(&tab_id) {
+                inner.active_tab_id = None;
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[tauri::command]
@@ -508,10 +833,10 @@ pub fn web_blanket_navigate(
     state: tauri::State<WebBlanketState>,
     tab_id: String,
     url: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     #[cfg(target_os = "macos")]
     {
-        let inner = state.inner.lock().map_err(|e| e.to_string())?;
+        let inner = state.inner.lock()?;
         
         if let Some(webview) = inner.tabs.get(&tab_id) {
             unsafe {
@@ -521,17 +846,25 @@ pub fn web_blanket_navigate(
         Ok(())
     }
     #[cfg(not(target_os = "macos"))]
-    Err("Not supported on this OS".into())
+    {
+        let inner = state.inner.lock()?;
+
+        if let Some(tab) = inner.tabs.get(&tab_id) {
+            let parsed = url.parse::<tauri::Url>().map_err(|e| AppError::InvalidUrl { url: url.to_string(), reason: e.to_string() })?;
+            tab.webview.navigate(parsed)?;
+        }
+        Ok(())
+    }
 }
 
 #[tauri::command]
 pub fn web_blanket_go_back(
     _window: WebviewWindow,
     state: tauri::State<WebBlanketState>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     #[cfg(target_os = "macos")]
     {
-        let inner = state.inner.lock().map_err(|e| e.to_string())?;
+        let inner = state.inner.lock()?;
         if let Some(tid) = &inner.active_tab_id {
             if let Some(webview) = inner.tabs.get(tid) {
                 unsafe {
@@ -542,17 +875,24 @@ pub fn web_blanket_go_back(
         Ok(())
     }
     #[cfg(not(target_os = "macos"))]
-    Err("Not supported on this OS".into())
+    {
+        let inner = state.inner.lock()?;
+        if let Some(tab) = inner.active_tab_id.as_ref().and_then(|tid| inner.tabs.get(tid)) {
+            tab.page.lock().unwrap().go_back();
+            tab.webview.eval("history.back()").unwrap_or(());
+        }
+        Ok(())
+    }
 }
 
 #[tauri::command]
 pub fn web_blanket_go_forward(
     _window: WebviewWindow,
     state: tauri::State<WebBlanketState>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     #[cfg(target_os = "macos")]
     {
-        let inner = state.inner.lock().map_err(|e| e.to_string())?;
+        let inner = state.inner.lock()?;
         if let Some(tid) = &inner.active_tab_id {
             if let Some(webview) = inner.tabs.get(tid) {
                 unsafe {
@@ -563,17 +903,24 @@ pub fn web_blanket_go_forward(
         Ok(())
     }
     #[cfg(not(target_os = "macos"))]
-    Err("Not supported on this OS".into())
+    {
+        let inner = state.inner.lock()?;
+        if let Some(tab) = inner.active_tab_id.as_ref().and_then(|tid| inner.tabs.get(tid)) {
+            tab.page.lock().unwrap().go_forward();
+            tab.webview.eval("history.forward()").unwrap_or(());
+        }
+        Ok(())
+    }
 }
 
 #[tauri::command]
 pub fn web_blanket_reload(
     _window: WebviewWindow,
     state: tauri::State<WebBlanketState>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     #[cfg(target_os = "macos")]
     {
-        let inner = state.inner.lock().map_err(|e| e.to_string())?;
+        let inner = state.inner.lock()?;
         if let Some(tid) = &inner.active_tab_id {
             if let Some(webview) = inner.tabs.get(tid) {
                 unsafe {
@@ -584,17 +931,23 @@ pub fn web_blanket_reload(
         Ok(())
     }
     #[cfg(not(target_os = "macos"))]
-    Err("Not supported on this OS".into())
+    {
+        let inner = state.inner.lock()?;
+        if let Some(tab) = inner.active_tab_id.as_ref().and_then(|tid| inner.tabs.get(tid)) {
+            tab.webview.reload().unwrap_or(());
+        }
+        Ok(())
+    }
 }
 
 #[tauri::command]
 pub fn web_blanket_stop_loading(
     _window: WebviewWindow,
     state: tauri::State<WebBlanketState>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     #[cfg(target_os = "macos")]
     {
-        let inner = state.inner.lock().map_err(|e| e.to_string())?;
+        let inner = state.inner.lock()?;
         if let Some(tid) = &inner.active_tab_id {
             if let Some(webview) = inner.tabs.get(tid) {
                 unsafe {
@@ -605,7 +958,14 @@ pub fn web_blanket_stop_loading(
         Ok(())
     }
     #[cfg(not(target_os = "macos"))]
-    Err("Not supported on this OS".into())
+    {
+        let inner = state.inner.lock()?;
+        if let Some(tab) = inner.active_tab_id.as_ref().and_then(|tid| inner.tabs.get(tid)) {
+            tab.webview.eval("window.stop()").unwrap_or(());
+            tab.page.lock().unwrap().loading = false;
+        }
+        Ok(())
+    }
 }
 
 #[tauri::command]
@@ -613,10 +973,10 @@ pub fn web_blanket_set_theme(
     _window: WebviewWindow,
     state: tauri::State<WebBlanketState>,
     theme: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     #[cfg(target_os = "macos")]
     {
-        let inner = state.inner.lock().map_err(|e| e.to_string())?;
+        let inner = state.inner.lock()?;
         if let Some(container) = inner.container_view {
             unsafe {
                 let name = if theme == "dark" {
@@ -640,19 +1000,32 @@ pub fn web_blanket_set_theme(
         Ok(())
     }
     #[cfg(not(target_os = "macos"))]
-    Err("Not supported on this OS".into())
+    {
+        // Child webviews follow the system (GTK) theme; only the page background is set
+        let inner = state.inner.lock()?;
+        let color = if theme == "dark" {
+            tauri::window::Color(28, 25, 23, 255)
+        } else {
+            tauri::window::Color(255, 255, 255, 255)
+        };
+        for tab in inner.tabs.values() {
+            tab.webview.set_background_color(Some(color)).unwrap_or(());
+        }
+        Ok(())
+    }
 }
 
 #[tauri::command]
 pub fn web_blanket_set_user_agent(
-    _window: WebviewWindow,
+    window: WebviewWindow,
     state: tauri::State<WebBlanketState>,
     tab_id: String,
     mode: String,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     #[cfg(target_os = "macos")]
     {
-        let inner = state.inner.lock().map_err(|e| e.to_string())?;
+        let _ = window;
+        let inner = state.inner.lock()?;
         
         if let Some(webview) = inner.tabs.get(&tab_id) {
             unsafe {
@@ -683,24 +1056,51 @@ pub fn web_blanket_set_user_agent(
         Ok(())
     }
     #[cfg(not(target_os = "macos"))]
-    Err("Not supported on this OS".into())
+    {
+        let mut inner = state.inner.lock()?;
+        let desktop = mode == "desktop";
+
+        let Some(old) = inner.tabs.remove(&tab_id) else {
+            return Ok(());
+        };
+        if old.desktop == desktop {
+            inner.tabs.insert(tab_id, old);
+            return Ok(());
+        }
+
+        // The user agent is fixed at creation time, so the tab is rebuilt at its current URL
+        let url = old
+            .webview
+            .url()
+            .map(|u| u.to_string())
+            .unwrap_or_else(|_| "about:blank".to_string());
+        let zoom = old.zoom;
+        old.webview.close().unwrap_or(());
+
+        let tab = create_child_tab(&window, &inner, &tab_id, &url, desktop, zoom)?;
+        if inner.visible && inner.active_tab_id.as_ref() == Some(&tab_id) {
+            tab.webview.show().unwrap_or(());
+        }
+        inner.tabs.insert(tab_id, tab);
+        Ok(())
+    }
 }
 
 #[tauri::command]
 pub fn web_blanket_zoom_in(
     _window: WebviewWindow,
     state: tauri::State<WebBlanketState>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     #[cfg(target_os = "macos")]
     {
-        let inner = state.inner.lock().map_err(|e| e.to_string())?;
+        let inner = state.inner.lock()?;
         if let Some(tid) = &inner.active_tab_id {
             if let Some(webview) = inner.tabs.get(tid) {
                 unsafe {
                     let wv = webview.as_id();
                     // CGFloat is f64 on 64-bit macOS
                     let current_zoom: f64 = msg_send![wv, pageZoom];
-                    let new_zoom = current_zoom + 0.1;
+                    let new_zoom = (current_zoom + 0.1).min(MAX_ZOOM);
                     let _: () = msg_send![wv, setPageZoom: new_zoom];
                 }
             }
@@ -708,23 +1108,33 @@ pub fn web_blanket_zoom_in(
         Ok(())
     }
     #[cfg(not(target_os = "macos"))]
-    Err("Not supported on this OS".into())
+    {
+        let mut inner = state.inner.lock()?;
+        let Some(tid) = inner.active_tab_id.clone() else {
+            return Ok(());
+        };
+        if let Some(tab) = inner.tabs.get_mut(&tid) {
+            tab.zoom = (tab.zoom + 0.1).min(MAX_ZOOM);
+            tab.webview.set_zoom(tab.zoom).unwrap_or(());
+        }
+        Ok(())
+    }
 }
 
 #[tauri::command]
 pub fn web_blanket_zoom_out(
     _window: WebviewWindow,
     state: tauri::State<WebBlanketState>,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     #[cfg(target_os = "macos")]
     {
-        let inner = state.inner.lock().map_err(|e| e.to_string())?;
+        let inner = state.inner.lock()?;
         if let Some(tid) = &inner.active_tab_id {
             if let Some(webview) = inner.tabs.get(tid) {
                 unsafe {
                     let wv = webview.as_id();
                     let current_zoom: f64 = msg_send![wv, pageZoom];
-                    let new_zoom = (current_zoom - 0.1).max(0.5);
+                    let new_zoom = (current_zoom - 0.1).max(MIN_ZOOM);
                     let _: () = msg_send![wv, setPageZoom: new_zoom];
                 }
             }
@@ -732,7 +1142,17 @@ pub fn web_blanket_zoom_out(
         Ok(())
     }
     #[cfg(not(target_os = "macos"))]
-    Err("Not supported on this OS".into())
+    {
+        let mut inner = state.inner.lock()?;
+        let Some(tid) = inner.active_tab_id.clone() else {
+            return Ok(());
+        };
+        if let Some(tab) = inner.tabs.get_mut(&tid) {
+            tab.zoom = (tab.zoom - 0.1).max(MIN_ZOOM);
+            tab.webview.set_zoom(tab.zoom).unwrap_or(());
+        }
+        Ok(())
+    }
 }
 
 #[derive(serde::Serialize, Clone, Debug)]
@@ -752,33 +1172,17 @@ pub fn web_blanket_get_tab_state(
     _window: WebviewWindow,
     state: tauri::State<WebBlanketState>,
     tab_id: String,
-) -> Result<TabStatePayload, String> {
+) -> Result<TabStatePayload, AppError> {
     #[cfg(target_os = "macos")]
     {
-        let inner = state.inner.lock().map_err(|e| e.to_string())?;
+        let inner = state.inner.lock()?;
         
         if let Some(webview) = inner.tabs.get(&tab_id) {
             unsafe {
                 let wv = webview.as_id();
                 
-                let title_ns: id = msg_send![wv, title];
-                let title = if title_ns != nil {
-                    nsstring_to_string(title_ns)
-                } else {
-                    String::new()
-                };
-
-                let url_ns: id = msg_send![wv, URL]; // NSURL
-                let url = if url_ns != nil {
-                    let abs_str: id = msg_send![url_ns, absoluteString];
-                    if abs_str != nil {
-                        nsstring_to_string(abs_str)
-                    } else {
-                        String::new()
-                    }
-                } else {
-                    String::new()
-                };
+                let title = webview_title(wv);
+                let url = webview_url(wv);
 
                 let loading: bool = msg_send![wv, isLoading];
                 let can_go_back: bool = msg_send![wv, canGoBack];
@@ -795,11 +1199,59 @@ pub fn web_blanket_get_tab_state(
                 })
             }
         } else {
-            Err("Tab not found".into())
+            Err(AppError::TabNotFound { tab_id })
         }
     }
     #[cfg(not(target_os = "macos"))]
-    Err("Not supported on this OS".into())
+    {
+        let inner = state.inner.lock()?;
+
+        if let Some(tab) = inner.tabs.get(&tab_id) {
+            let url = tab.webview.url().map(|u| u.to_string()).unwrap_or_default();
+            let page = tab.page.lock().unwrap();
+
+            Ok(TabStatePayload {
+                url,
+                title: page.title.clone(),
+                loading: page.loading,
+                can_go_back: page.can_go_back(),
+                can_go_forward: page.can_go_forward(),
+                current_zoom: tab.zoom,
+            })
+        } else {
+            Err(AppError::TabNotFound { tab_id })
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+unsafe fn webview_title(wv: id) -> String {
+    let title_ns: id = msg_send![wv, title];
+    if title_ns != nil {
+        nsstring_to_string(title_ns)
+    } else {
+        String::new()
+    }
+}
+
+#[cfg(target_os = "macos")]
+unsafe fn webview_url(wv: id) -> String {
+    let url_ns: id = msg_send![wv, URL]; // NSURL
+    if url_ns == nil {
+        return String::new();
+    }
+    let abs_str: id = msg_send![url_ns, absoluteString];
+    if abs_str != nil {
+        nsstring_to_string(abs_str)
+    } else {
+        String::new()
+    }
+}
+
+#[cfg(target_os = "macos")]
+unsafe fn webview_is_desktop(wv: id) -> bool {
+    let ua_ns: id = msg_send![wv, customUserAgent];
+    ua_ns != nil && nsstring_to_string(ua_ns) == DESKTOP_USER_AGENT
 }
 
 #[cfg(target_os = "macos")]
@@ -816,17 +1268,169 @@ unsafe fn nsstring_to_string(ns_string: id) -> String {
 // Helpers
 // -----------------------------------------------------------------------------
 
+#[cfg(target_os = "macos")]
+unsafe fn install_user_scripts(controller: id, scripts: &[UserScript]) {
+    // The bridge goes first so user scripts can call `window.myDrawer`
+    let source = NSString::alloc(nil).init_str(page_bridge::BRIDGE_SCRIPT);
+    let bridge: id = msg_send![class!(WKUserScript), alloc];
+    let bridge: id = msg_send![bridge, initWithSource:source injectionTime:0isize forMainFrameOnly:true];
+    let _: () = msg_send![controller, addUserScript: bridge];
+    let _: () = msg_send![bridge, release];
+    let _: () = msg_send![source, release];
+
+    for script in scripts {
+        // WKUserScriptInjectionTimeAtDocumentStart = 0, AtDocumentEnd = 1
+        let injection_time: isize = match script.run_at {
+            RunAt::DocumentStart => 0,
+            RunAt::DocumentEnd => 1,
+        };
+        let source = NSString::alloc(nil).init_str(&script.injected_source());
+        let user_script: id = msg_send![class!(WKUserScript), alloc];
+        let user_script: id = msg_send![user_script, initWithSource:source injectionTime:injection_time forMainFrameOnly:script.main_frame_only];
+        let _: () = msg_send![controller, addUserScript: user_script];
+        let _: () = msg_send![user_script, release];
+        let _: () = msg_send![source, release];
+    }
+}
+
+/// Swaps the scripts of the open tabs for the current ones, taking effect on
+/// their next page load. Child webviews take their scripts at creation only,
+/// so elsewhere the change reaches new tabs.
+pub fn refresh_user_scripts(app: &AppHandle) {
+    #[cfg(target_os = "macos")]
+    {
+        let app_handle = app.clone();
+        let _ = app.run_on_main_thread(move || {
+            let scripts = app_handle.state::<UserScripts>().enabled();
+            let state = app_handle.state::<WebBlanketState>();
+            let Ok(inner) = state.inner.lock() else {
+                return;
+            };
+            for webview in inner.tabs.values() {
+                unsafe {
+                    let config: id = msg_send![webview.as_id(), configuration];
+                    let controller: id = msg_send![config, userContentController];
+                    let _: () = msg_send![controller, removeAllUserScripts];
+                    install_user_scripts(controller, &scripts);
+                }
+            }
+        });
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = app;
+    }
+}
+
+/// Runs `script` in the page of tab `tab_id`, ignoring the result. Only
+/// WKWebView tabs have the page bridge this serves, elsewhere it does nothing.
+pub fn evaluate_in_tab(app: &AppHandle, tab_id: &str, script: String) {
+    #[cfg(target_os = "macos")]
+    {
+        let app_handle = app.clone();
+        let tab_id = tab_id.to_string();
+        let _ = app.run_on_main_thread(move || {
+            let state = app_handle.state::<WebBlanketState>();
+            let Ok(inner) = state.inner.lock() else {
+                return;
+            };
+            if let Some(webview) = inner.tabs.get(&tab_id) {
+                unsafe {
+                    let source = NSString::alloc(nil).init_str(&script);
+                    let _: () = msg_send![webview.as_id(), evaluateJavaScript:source completionHandler:nil];
+                    let _: () = msg_send![source, release];
+                }
+            }
+        });
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (app, tab_id, script);
+    }
+}
+
+#[cfg(target_os = "macos")]
+const CONTENT_RULE_LIST_ID: &str = "my-drawer-content-blocker";
+
+/// Replaces the content blocking rules on all tabs, and on tabs opened later.
+/// `encoded` is a WebKit content rule list in JSON, `None` turns blocking off.
+/// Only WKWebView tabs are filtered, elsewhere this does nothing.
+pub fn set_content_rules(app: &AppHandle, encoded: Option<String>) {
+    #[cfg(target_os = "macos")]
+    {
+        let app_handle = app.clone();
+        let _ = app.run_on_main_thread(move || {
+            let Some(encoded) = encoded else {
+                swap_content_rules(&app_handle, None);
+                return;
+            };
+
+            unsafe {
+                let store: id = msg_send![class!(WKContentRuleListStore), defaultStore];
+                let identifier = NSString::alloc(nil).init_str(CONTENT_RULE_LIST_ID);
+                let json = NSString::alloc(nil).init_str(&encoded);
+
+                let completion_app = app_handle.clone();
+                let completion = block::ConcreteBlock::new(move |list: id, error: id| {
+                    if list != nil {
+                        swap_content_rules(&completion_app, Some(list));
+                    } else if error != nil {
+                        let description: id = msg_send![error, localizedDescription];
+                        eprintln!("Failed to compile content rules: {}", nsstring_to_string(description));
+                    }
+                })
+                .copy();
+                let _: () = msg_send![store, compileContentRuleListForIdentifier:identifier encodedContentRuleList:json completionHandler:&*completion];
+
+                let _: () = msg_send![identifier, release];
+                let _: () = msg_send![json, release];
+            }
+        });
+    }
+    #[cfg(not(target_os = "macos"))]
+    {
+        let _ = (app, encoded);
+    }
+}
+
+/// Attaches `rules` to every tab in place of the previous list. Main thread only.
+#[cfg(target_os = "macos")]
+fn swap_content_rules(app: &AppHandle, rules: Option<id>) {
+    let state = app.state::<WebBlanketState>();
+    let Ok(mut inner) = state.inner.lock() else {
+        return;
+    };
+
+    unsafe {
+        if let Some(rules) = rules {
+            let _: () = msg_send![rules, retain];
+        }
+        for webview in inner.tabs.values() {
+            let config: id = msg_send![webview.as_id(), configuration];
+            let controller: id = msg_send![config, userContentController];
+            let _: () = msg_send![controller, removeAllContentRuleLists];
+            if let Some(rules) = rules {
+                let _: () = msg_send![controller, addContentRuleList: rules];
+            }
+        }
+        if let Some(previous) = inner.content_rules.take() {
+            let _: () = msg_send![previous.as_id(), release];
+        }
+    }
+    inner.content_rules = rules.map(SafeId::from);
+}
+
 #[cfg(target_os = "macos")]
 fn ensure_container(
     window: &WebviewWindow,
     inner: &mut WebBlanketInner,
-) -> Result<(), String> {
+) -> Result<(), AppError> {
     if inner.container_view.is_some() {
         return Ok(());
     }
 
     unsafe {
-        let ns_window = window.ns_window().map_err(|e| e.to_string())? as id;
+        let ns_window = window.ns_window()? as id;
         let content_view: id = msg_send![ns_window, contentView];
         
         let container: id = msg_send![class!(NSView), alloc];
@@ -907,3 +1511,199 @@ unsafe fn load_url(webview: id, url: &str) {
     
     let _: () = msg_send![url_string, release];
 }
+
+// -----------------------------------------------------------------------------
+// Child webview backend (Linux and other non-macOS desktops)
+// -----------------------------------------------------------------------------
+
+#[cfg(not(target_os = "macos"))]
+struct ChildTab {
+    webview: tauri::Webview,
+    zoom: f64,
+    desktop: bool,
+    page: Arc<Mutex<ChildPageState>>,
+}
+
+// Child webviews expose no title/history getters, so the page state is
+// collected from load and title callbacks instead.
+#[cfg(not(target_os = "macos"))]
+#[derive(Default)]
+struct ChildPageState {
+    title: String,
+    loading: bool,
+    history: Vec<String>,
+    history_index: usize,
+}
+
+#[cfg(not(target_os = "macos"))]
+impl ChildPageState {
+    fn record_visit(&mut self, url: String) {
+        if self.history.get(self.history_index) == Some(&url) {
+            return;
+        }
+        if !self.history.is_empty() {
+            self.history.truncate(self.history_index + 1);
+        }
+        self.history.push(url);
+        self.history_index = self.history.len() - 1;
+    }
+
+    fn go_back(&mut self) {
+        if self.can_go_back() {
+            self.history_index -= 1;
+        }
+    }
+
+    fn go_forward(&mut self) {
+        if self.can_go_forward() {
+            self.history_index += 1;
+        }
+    }
+
+    fn can_go_back(&self) -> bool {
+        self.history_index > 0
+    }
+
+    fn can_go_forward(&self) -> bool {
+        self.history_index + 1 < self.history.len()
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+static CHILD_TAB_COUNTER: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+
+#[cfg(not(target_os = "macos"))]
+fn create_child_tab(
+    window: &WebviewWindow,
+    inner: &WebBlanketInner,
+    tab_id: &str,
+    url: &str,
+    desktop: bool,
+    zoom: f64,
+) -> Result<ChildTab, AppError> {
+    use tauri::webview::{PageLoadEvent, WebviewBuilder};
+    use tauri::{LogicalPosition, LogicalSize, WebviewUrl};
+
+    let parsed = url.parse::<tauri::Url>().map_err(|e| AppError::InvalidUrl { url: url.to_string(), reason: e.to_string() })?;
+
+    // Webview labels must be unique for the app's lifetime and only allow [a-zA-Z0-9-/:_]
+    let safe_id: String = tab_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' { c } else { '_' })
+        .collect();
+    let n = CHILD_TAB_COUNTER.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let label = format!("web-blanket-{}-{}", safe_id, n);
+
+    let page = Arc::new(Mutex::new(ChildPageState::default()));
+    let page_for_load = Arc::clone(&page);
+    let page_for_title = Arc::clone(&page);
+    let window_for_popup = window.clone();
+    let window_for_load = window.clone();
+    let window_for_title = window.clone();
+    let tab_for_load = tab_id.to_string();
+    let tab_for_title = tab_id.to_string();
+
+    // Child webviews only report load start and end, so there are no
+    // committed, failed or progress events here
+    let builder = WebviewBuilder::new(label, WebviewUrl::External(parsed))
+        .user_agent(if desktop { DESKTOP_USER_AGENT } else { MOBILE_USER_AGENT })
+        .on_page_load(move |_webview, payload| {
+            let url = payload.url().to_string();
+            let mut page = page_for_load.lock().unwrap();
+            match payload.event() {
+                PageLoadEvent::Started => {
+                    page.loading = true;
+                    drop(page);
+                    emit_tab_event(&window_for_load, &tab_for_load, TabEventKind::Started { url });
+                }
+                PageLoadEvent::Finished => {
+                    page.loading = false;
+                    page.record_visit(url.clone());
+                    let history = TabEventKind::History {
+                        can_go_back: page.can_go_back(),
+                        can_go_forward: page.can_go_forward(),
+                    };
+                    drop(page);
+                    emit_tab_event(&window_for_load, &tab_for_load, TabEventKind::Finished { url });
+                    emit_tab_event(&window_for_load, &tab_for_load, history);
+                }
+            }
+        })
+        .on_document_title_changed(move |_webview, title| {
+            page_for_title.lock().unwrap().title = title.clone();
+            emit_tab_event(&window_for_title, &tab_for_title, TabEventKind::Title { title });
+        })
+        .on_new_window(move |url, _features| {
+            let _ = window_for_popup.emit("web-blanket-new-window", serde_json::json!({ "url": url.to_string() }));
+            tauri::webview::NewWindowResponse::Deny
+        });
+
+    let builder = window
+        .state::<UserScripts>()
+        .enabled()
+        .iter()
+        .fold(builder, |builder, script| {
+            if script.main_frame_only {
+                builder.initialization_script(script.injected_source())
+            } else {
+                builder.initialization_script_for_all_frames(script.injected_source())
+            }
+        });
+
+    let (position, size) = match inner.last_bounds {
+        Some(b) => (LogicalPosition::new(b.x, b.y), LogicalSize::new(b.width, b.height)),
+        None => (LogicalPosition::new(0.0, 0.0), LogicalSize::new(0.0, 0.0)),
+    };
+
+    let webview = window
+        .as_ref()
+        .window()
+        .add_child(builder, position, size)?;
+
+    // Hide initially, the frontend activates the tab explicitly
+    webview.hide().unwrap_or(());
+    webview.set_zoom(zoom).unwrap_or(());
+
+    Ok(ChildTab {
+        webview,
+        zoom,
+        desktop,
+        page,
+    })
+}
+
+#[cfg(not(target_os = "macos"))]
+fn update_child_frame(webview: &tauri::Webview, bounds: &Bounds) {
+    // Child webviews use top-left window coordinates, same as the CSS bounds
+    webview
+        .set_position(tauri::LogicalPosition::new(bounds.x, bounds.y))
+        .unwrap_or(());
+    webview
+        .set_size(tauri::LogicalSize::new(bounds.width, bounds.height))
+        .unwrap_or(());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tab_events_serialize_flat_with_a_type() {
+        let json = |kind: TabEventKind| serde_json::to_value(TabEvent { tab_id: "t1".into(), kind }).unwrap();
+
+        assert_eq!(
+            json(TabEventKind::Failed { url: "https://example.com/".into(), code: -1003, message: "Host not found".into() }),
+            serde_json::json!({
+                "tabId": "t1",
+                "type": "failed",
+                "url": "https://example.com/",
+                "code": -1003,
+                "message": "Host not found"
+            })
+        );
+        assert_eq!(
+            json(TabEventKind::History { can_go_back: true, can_go_forward: false }),
+            serde_json::json!({ "tabId": "t1", "type": "history", "canGoBack": true, "canGoForward": false })
+        );
+    }
+}
@@ -0,0 +1,138 @@
+//! In-memory `WindowBackend` used by the layout tests.
+
+use super::{DisplayInfo, WindowBackend, WindowError};
+use crate::layout_manager::{WindowInfo, WindowRect, WindowState};
+use std::sync::Mutex;
+
+pub struct FakeBackend {
+    pub windows: Mutex<Vec<WindowInfo>>,
+    pub focused: Mutex<Option<u32>>,
+    pub displays: Vec<DisplayInfo>,
+    /// Every `set_frame` call, in order, as (window id, frame)
+    pub moves: Mutex<Vec<(u32, WindowRect)>>,
+    pub minimized: Mutex<Vec<u32>>,
+    /// Returned by every move, focus and minimize while set, e.g. to act as
+    /// if accessibility permission was missing
+    pub refuse: Mutex<Option<WindowError>>,
+}
+
+pub fn rect(x: f64, y: f64, width: f64, height: f64) -> WindowRect {
+    WindowRect { x, y, width, height }
+}
+
+pub fn window(id: u32, pid: i32, app_name: &str, title: &str, frame: WindowRect) -> WindowInfo {
+    WindowInfo {
+        id,
+        pid,
+        title: title.to_string(),
+        app_name: app_name.to_string(),
+        frame,
+        app_path: None,
+        display_id: None,
+        relative_frame: None,
+        bundle_id: None,
+        role: None,
+        state: WindowState {
+            on_screen: true,
+            ..Default::default()
+        },
+    }
+}
+
+/// A 1000x800 display whose top 25px are taken by a menu bar.
+pub fn display(id: u32, x: f64, y: f64) -> DisplayInfo {
+    DisplayInfo {
+        id,
+        frame: rect(x, y, 1000.0, 800.0),
+        work_area: rect(x, y + 25.0, 1000.0, 775.0),
+        is_main: id == 1,
+    }
+}
+
+impl FakeBackend {
+    pub fn new(windows: Vec<WindowInfo>, displays: Vec<DisplayInfo>) -> Self {
+        let focused = windows.first().map(|w| w.id);
+        Self {
+            windows: Mutex::new(windows),
+            focused: Mutex::new(focused),
+            displays,
+            moves: Mutex::new(Vec::new()),
+            minimized: Mutex::new(Vec::new()),
+            refuse: Mutex::new(None),
+        }
+    }
+
+    pub fn frame_of(&self, id: u32) -> Option<WindowRect> {
+        self.windows
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|w| w.id == id)
+            .map(|w| w.frame.clone())
+    }
+
+    pub fn moved_ids(&self) -> Vec<u32> {
+        self.moves.lock().unwrap().iter().map(|(id, _)| *id).collect()
+    }
+
+    fn check_refused(&self) -> Result<(), WindowError> {
+        match self.refuse.lock().unwrap().clone() {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+}
+
+impl WindowBackend for FakeBackend {
+    fn list_windows(&self) -> Vec<WindowInfo> {
+        self.list_all_windows()
+            .into_iter()
+            .filter(|w| w.state.on_screen && w.state.layer == 0)
+            .collect()
+    }
+
+    fn list_all_windows(&self) -> Vec<WindowInfo> {
+        self.windows.lock().unwrap().clone()
+    }
+
+    fn focused_window(&self) -> Option<WindowInfo> {
+        let focused = (*self.focused.lock().unwrap())?;
+        self.list_windows().into_iter().find(|w| w.id == focused)
+    }
+
+    fn set_frame(&self, window: &WindowInfo, frame: &WindowRect) -> Result<(), WindowError> {
+        self.check_refused()?;
+        let mut windows = self.windows.lock().unwrap();
+        let target = windows
+            .iter_mut()
+            .find(|w| w.id == window.id)
+            .ok_or(WindowError::WindowNotFound { id: Some(window.id) })?;
+        target.frame = frame.clone();
+        self.moves.lock().unwrap().push((window.id, frame.clone()));
+        Ok(())
+    }
+
+    fn focus(&self, window: &WindowInfo) -> Result<(), WindowError> {
+        self.check_refused()?;
+        let mut windows = self.windows.lock().unwrap();
+        let index = windows
+            .iter()
+            .position(|w| w.id == window.id)
+            .ok_or(WindowError::WindowNotFound { id: Some(window.id) })?;
+        // Focusing brings the window to the front of the stacking order
+        let w = windows.remove(index);
+        windows.insert(0, w);
+        *self.focused.lock().unwrap() = Some(window.id);
+        Ok(())
+    }
+
+    fn minimize(&self, window: &WindowInfo) -> Result<(), WindowError> {
+        self.check_refused()?;
+        self.minimized.lock().unwrap().push(window.id);
+        Ok(())
+    }
+
+    fn displays(&self) -> Vec<DisplayInfo> {
+        self.displays.clone()
+    }
+}
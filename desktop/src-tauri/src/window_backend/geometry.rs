@@ -0,0 +1,163 @@
+//! Screen geometry shared by the backends. Everything here works in the
+//! top-left origin, y-down global space used by `WindowInfo::frame`.
+
+use crate::layout_manager::WindowRect;
+
+/// Converts between Cocoa's bottom-left, y-up screen space and the top-left,
+/// y-down space used by CoreGraphics and AX. `primary_height` is the height of
+/// the screen holding the menu bar. The conversion is its own inverse.
+pub fn flip_y(rect: &WindowRect, primary_height: f64) -> WindowRect {
+    WindowRect {
+        x: rect.x,
+        y: primary_height - rect.y - rect.height,
+        width: rect.width,
+        height: rect.height,
+    }
+}
+
+pub fn intersect(a: &WindowRect, b: &WindowRect) -> Option<WindowRect> {
+    let x = a.x.max(b.x);
+    let y = a.y.max(b.y);
+    let right = (a.x + a.width).min(b.x + b.width);
+    let bottom = (a.y + a.height).min(b.y + b.height);
+    if right <= x || bottom <= y {
+        return None;
+    }
+    Some(WindowRect {
+        x,
+        y,
+        width: right - x,
+        height: bottom - y,
+    })
+}
+
+/// Removes the space reserved by panels and docks from `monitor`.
+///
+/// Each strut is an EWMH `_NET_WM_STRUT_PARTIAL` (left, right, top, bottom,
+/// left_start_y, left_end_y, right_start_y, right_end_y, top_start_x,
+/// top_end_x, bottom_start_x, bottom_end_x), measured from the edges of the
+/// whole root window. A strut only applies to monitors it reaches into.
+pub fn apply_struts(monitor: &WindowRect, root_width: f64, root_height: f64, struts: &[[u32; 12]]) -> WindowRect {
+    let mut left = monitor.x;
+    let mut top = monitor.y;
+    let mut right = monitor.x + monitor.width;
+    let mut bottom = monitor.y + monitor.height;
+
+    let spans = |start: f64, end: f64, lo: f64, hi: f64| start < hi && end >= lo;
+
+    for strut in struts {
+        let s = strut.map(f64::from);
+
+        if s[0] > monitor.x && spans(s[4], s[5], monitor.y, monitor.y + monitor.height) {
+            left = left.max(s[0]);
+        }
+        if s[1] > 0.0
+            && root_width - s[1] < monitor.x + monitor.width
+            && spans(s[6], s[7], monitor.y, monitor.y + monitor.height)
+        {
+            right = right.min(root_width - s[1]);
+        }
+        if s[2] > monitor.y && spans(s[8], s[9], monitor.x, monitor.x + monitor.width) {
+            top = top.max(s[2]);
+        }
+        if s[3] > 0.0
+            && root_height - s[3] < monitor.y + monitor.height
+            && spans(s[10], s[11], monitor.x, monitor.x + monitor.width)
+        {
+            bottom = bottom.min(root_height - s[3]);
+        }
+    }
+
+    WindowRect {
+        x: left,
+        y: top,
+        width: (right - left).max(0.0),
+        height: (bottom - top).max(0.0),
+    }
+}
+
+/// Widens a legacy `_NET_WM_STRUT` (left, right, top, bottom) to a partial
+/// strut that spans the full edge.
+pub fn full_strut(strut: [u32; 4]) -> [u32; 12] {
+    let [left, right, top, bottom] = strut;
+    [left, right, top, bottom, 0, u32::MAX, 0, u32::MAX, 0, u32::MAX, 0, u32::MAX]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_backend::fake::rect;
+
+    #[test]
+    fn flip_y_converts_visible_frame_below_menu_bar() {
+        // 1440x900 primary, 25pt menu bar, 70pt Dock at the bottom
+        let visible = rect(0.0, 70.0, 1440.0, 805.0);
+        assert_eq!(flip_y(&visible, 900.0), rect(0.0, 25.0, 1440.0, 805.0));
+    }
+
+    #[test]
+    fn flip_y_places_screen_above_primary_at_negative_y() {
+        let above = rect(-200.0, 900.0, 1920.0, 1080.0);
+        assert_eq!(flip_y(&above, 900.0), rect(-200.0, -1080.0, 1920.0, 1080.0));
+
+        let below = rect(0.0, -1080.0, 1920.0, 1080.0);
+        assert_eq!(flip_y(&below, 900.0), rect(0.0, 900.0, 1920.0, 1080.0));
+    }
+
+    #[test]
+    fn flip_y_round_trips() {
+        let r = rect(12.0, 34.0, 560.0, 780.0);
+        assert_eq!(flip_y(&flip_y(&r, 1117.0), 1117.0), r);
+    }
+
+    #[test]
+    fn intersect_clips_and_rejects_disjoint_rects() {
+        let a = rect(0.0, 0.0, 100.0, 100.0);
+        assert_eq!(intersect(&a, &rect(50.0, 20.0, 100.0, 100.0)), Some(rect(50.0, 20.0, 50.0, 80.0)));
+        assert_eq!(intersect(&a, &rect(100.0, 0.0, 10.0, 10.0)), None);
+    }
+
+    #[test]
+    fn struts_only_affect_the_monitor_they_reach() {
+        let left_monitor = rect(0.0, 0.0, 1920.0, 1080.0);
+        let right_monitor = rect(1920.0, 0.0, 1920.0, 1080.0);
+        // 48px dock along the left edge of the left monitor
+        let dock = [48, 0, 0, 0, 0, 1079, 0, 0, 0, 0, 0, 0];
+
+        assert_eq!(
+            apply_struts(&left_monitor, 3840.0, 1080.0, &[dock]),
+            rect(48.0, 0.0, 1872.0, 1080.0)
+        );
+        assert_eq!(apply_struts(&right_monitor, 3840.0, 1080.0, &[dock]), right_monitor);
+    }
+
+    #[test]
+    fn partial_struts_respect_their_span() {
+        let left_monitor = rect(0.0, 0.0, 1920.0, 1080.0);
+        let right_monitor = rect(1920.0, 0.0, 1920.0, 1080.0);
+        // 32px top bar across the right monitor only, 40px bottom panel across both
+        let top_bar = [0, 0, 32, 0, 0, 0, 0, 0, 1920, 3839, 0, 0];
+        let bottom_panel = [0, 0, 0, 40, 0, 0, 0, 0, 0, 0, 0, 3839];
+
+        assert_eq!(
+            apply_struts(&left_monitor, 3840.0, 1080.0, &[top_bar, bottom_panel]),
+            rect(0.0, 0.0, 1920.0, 1040.0)
+        );
+        assert_eq!(
+            apply_struts(&right_monitor, 3840.0, 1080.0, &[top_bar, bottom_panel]),
+            rect(1920.0, 32.0, 1920.0, 1008.0)
+        );
+    }
+
+    #[test]
+    fn right_strut_on_shorter_monitor_is_measured_from_root_edge() {
+        // Right monitor is 1280 wide; root spans 3200
+        let right_monitor = rect(1920.0, 0.0, 1280.0, 1024.0);
+        let panel = full_strut([0, 60, 0, 0]);
+
+        assert_eq!(
+            apply_struts(&right_monitor, 3200.0, 1080.0, &[panel]),
+            rect(1920.0, 0.0, 1220.0, 1024.0)
+        );
+    }
+}
@@ -0,0 +1,576 @@
+use super::geometry::flip_y;
+use super::{closest_match, DisplayInfo, WindowBackend, WindowError};
+use crate::layout_manager::{WindowInfo, WindowRect, WindowState};
+use accessibility_sys::{
+    kAXErrorAPIDisabled, kAXErrorActionUnsupported, kAXErrorAttributeUnsupported, kAXErrorCannotComplete,
+    kAXErrorInvalidUIElement, kAXErrorNotImplemented, kAXErrorSuccess, AXError, AXIsProcessTrusted,
+    AXUIElementCopyAttributeValue, AXUIElementCreateApplication,
+    AXUIElementCreateSystemWide, AXUIElementGetPid, AXUIElementPerformAction, AXUIElementRef,
+    AXUIElementSetAttributeValue, AXValueCreate, AXValueGetValue, AXValueRef,
+};
+use core_foundation::array::{CFArray, CFArrayGetValueAtIndex, CFArrayRef};
+use core_foundation::base::{CFRelease, TCFType};
+use core_foundation::boolean::{CFBoolean, CFBooleanRef};
+use core_foundation::dictionary::{CFDictionary, CFDictionaryRef};
+use core_foundation::number::{CFNumber, CFNumberRef};
+use core_foundation::string::{CFString, CFStringRef};
+use core_graphics::geometry::{CGPoint, CGSize};
+use core_graphics::window::{
+    kCGNullWindowID, kCGWindowListExcludeDesktopElements, kCGWindowListOptionAll,
+    kCGWindowListOptionOnScreenOnly, CGWindowListCopyWindowInfo,
+};
+use std::collections::HashMap;
+use std::ffi::c_void;
+use std::ptr;
+
+// kAXValueCGPointType / kAXValueCGSizeType
+const AX_VALUE_CG_POINT: u32 = 1;
+const AX_VALUE_CG_SIZE: u32 = 2;
+
+#[link(name = "ApplicationServices", kind = "framework")]
+extern "C" {
+    // Private, but stable since 10.5: the CGWindowID behind an AX window
+    fn _AXUIElementGetWindow(element: AXUIElementRef, window_id: *mut u32) -> AXError;
+}
+
+/// CoreGraphics for enumeration, the Accessibility API for moving windows.
+#[derive(Default)]
+pub struct MacBackend;
+
+impl MacBackend {
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Windows as CoreGraphics reports them: on-screen layer 0 only, or with
+    /// `all` every window of every layer, on any Space or minimized.
+    fn cg_windows(&self, all: bool) -> Vec<WindowInfo> {
+        let mut windows = Vec::new();
+
+        unsafe {
+            let options = if all {
+                kCGWindowListOptionAll | kCGWindowListExcludeDesktopElements
+            } else {
+                kCGWindowListOptionOnScreenOnly | kCGWindowListExcludeDesktopElements
+            };
+            let window_list_ref = CGWindowListCopyWindowInfo(options, kCGNullWindowID);
+
+            if window_list_ref.is_null() {
+                return windows;
+            }
+
+            let window_list: CFArray<*const c_void> = CFArray::wrap_under_create_rule(window_list_ref);
+            let count = window_list.len();
+
+            for i in 0..count {
+                let dict_ref =
+                    CFArrayGetValueAtIndex(window_list.as_concrete_TypeRef(), i) as CFDictionaryRef;
+                let dict = CFDictionary::wrap_under_get_rule(dict_ref);
+
+                let layer = get_number_from_dict(&dict, "kCGWindowLayer").unwrap_or(0);
+                if layer != 0 && !all {
+                    continue;
+                }
+
+                let pid = get_number_from_dict(&dict, "kCGWindowOwnerPID").unwrap_or(0) as i32;
+                let app_name = get_string_from_dict(&dict, "kCGWindowOwnerName").unwrap_or_default();
+                let title = get_string_from_dict(&dict, "kCGWindowName").unwrap_or_default();
+                let id = get_number_from_dict(&dict, "kCGWindowNumber").unwrap_or(0) as u32;
+                // Only present (and true) for windows on screen
+                let on_screen = !all || get_bool_from_dict(&dict, "kCGWindowIsOnscreen").unwrap_or(false);
+
+                let bounds_key = CFString::new("kCGWindowBounds");
+                let bounds_key_ptr = bounds_key.as_concrete_TypeRef() as *const c_void;
+
+                if let Some(bounds_ptr_ref) = dict.find(bounds_key_ptr) {
+                    let bounds_dict_ref = *bounds_ptr_ref as CFDictionaryRef;
+                    let bounds_dict = CFDictionary::wrap_under_get_rule(bounds_dict_ref);
+
+                    let x = get_number_from_dict_float(&bounds_dict, "X").unwrap_or(0.0);
+                    let y = get_number_from_dict_float(&bounds_dict, "Y").unwrap_or(0.0);
+                    let width = get_number_from_dict_float(&bounds_dict, "Width").unwrap_or(0.0);
+                    let height = get_number_from_dict_float(&bounds_dict, "Height").unwrap_or(0.0);
+
+                    windows.push(WindowInfo {
+                        id,
+                        pid,
+                        app_name,
+                        title,
+                        frame: WindowRect {
+                            x,
+                            y,
+                            width,
+                            height,
+                        },
+                        app_path: None,
+                        display_id: None,
+                        relative_frame: None,
+                        bundle_id: None,
+                        role: None,
+                        state: WindowState {
+                            on_screen,
+                            layer: layer as i32,
+                            ..Default::default()
+                        },
+                    });
+                }
+            }
+        }
+
+        windows
+    }
+}
+
+impl WindowBackend for MacBackend {
+    fn list_windows(&self) -> Vec<WindowInfo> {
+        self.cg_windows(false)
+    }
+
+    fn list_all_windows(&self) -> Vec<WindowInfo> {
+        let mut windows = self.cg_windows(true);
+
+        // One AX and one NSRunningApplication lookup per app
+        let mut apps: HashMap<i32, (Option<String>, HashMap<u32, AxDetails>)> = HashMap::new();
+        for window in &mut windows {
+            let (bundle_id, details) = apps
+                .entry(window.pid)
+                .or_insert_with(|| unsafe { (bundle_id(window.pid), ax_details(window.pid)) });
+
+            window.bundle_id = bundle_id.clone();
+            if let Some(details) = details.get(&window.id) {
+                window.role = details.subrole.clone();
+                window.state.minimized = details.minimized;
+                window.state.fullscreen = details.fullscreen;
+            }
+        }
+        windows
+    }
+
+    fn focused_window(&self) -> Option<WindowInfo> {
+        let (pid, window_id, title, frame) = unsafe {
+            let system = AXUIElementCreateSystemWide();
+            let app = copy_ax_attribute(system, "AXFocusedApplication");
+            CFRelease(system as *const c_void);
+            let app = app? as AXUIElementRef;
+
+            let mut pid = 0;
+            let has_pid = AXUIElementGetPid(app, &mut pid) == kAXErrorSuccess;
+            let window = copy_ax_attribute(app, "AXFocusedWindow");
+            CFRelease(app as *const c_void);
+            let window = window? as AXUIElementRef;
+
+            let window_id = ax_window_id(window);
+            let title = ax_string(window, "AXTitle").unwrap_or_default();
+            let frame = ax_frame(window);
+            CFRelease(window as *const c_void);
+            if !has_pid {
+                return None;
+            }
+            (pid, window_id, title, frame)
+        };
+
+        let candidates: Vec<WindowInfo> = self
+            .list_windows()
+            .into_iter()
+            .filter(|w| w.pid == pid)
+            .collect();
+        let index = candidates
+            .iter()
+            .position(|w| Some(w.id) == window_id)
+            .or_else(|| {
+                closest_match(
+                    &title,
+                    frame.as_ref(),
+                    candidates.iter().map(|w| (w.title.as_str(), Some(&w.frame))),
+                )
+            })
+            // Still the focused app: its front-most window is the best guess
+            .unwrap_or(0);
+        let mut window = candidates.into_iter().nth(index)?;
+        window.state.focused = true;
+        Some(window)
+    }
+
+    fn set_frame(&self, window: &WindowInfo, frame: &WindowRect) -> Result<(), WindowError> {
+        unsafe {
+            let ax_window = ax_window_for(window)?;
+
+            let pos = CGPoint {
+                x: frame.x,
+                y: frame.y,
+            };
+            let size = CGSize {
+                width: frame.width,
+                height: frame.height,
+            };
+
+            let pos_val = AXValueCreate(AX_VALUE_CG_POINT, &pos as *const _ as *const c_void);
+            let size_val = AXValueCreate(AX_VALUE_CG_SIZE, &size as *const _ as *const c_void);
+
+            let pos_attr = CFString::new("AXPosition");
+            let size_attr = CFString::new("AXSize");
+
+            let moved = AXUIElementSetAttributeValue(ax_window, pos_attr.as_concrete_TypeRef(), pos_val as *const c_void);
+            let resized = AXUIElementSetAttributeValue(ax_window, size_attr.as_concrete_TypeRef(), size_val as *const c_void);
+
+            CFRelease(pos_val as *const c_void);
+            CFRelease(size_val as *const c_void);
+            CFRelease(ax_window as *const c_void);
+
+            ax_result(moved, window)?;
+            ax_result(resized, window)
+        }
+    }
+
+    fn focus(&self, window: &WindowInfo) -> Result<(), WindowError> {
+        use cocoa::base::{id, nil};
+        use objc::{class, msg_send, sel, sel_impl};
+
+        unsafe {
+            let ax_window = ax_window_for(window)?;
+            let raise = CFString::new("AXRaise");
+            // Apps without AXRaise still come forward when activated below
+            AXUIElementPerformAction(ax_window, raise.as_concrete_TypeRef());
+            CFRelease(ax_window as *const c_void);
+
+            let app: id = msg_send![class!(NSRunningApplication), runningApplicationWithProcessIdentifier: window.pid];
+            if app != nil {
+                // NSApplicationActivateIgnoringOtherApps
+                let _: bool = msg_send![app, activateWithOptions: 1u64 << 1];
+            }
+        }
+        Ok(())
+    }
+
+    fn minimize(&self, window: &WindowInfo) -> Result<(), WindowError> {
+        unsafe {
+            let ax_window = ax_window_for(window)?;
+            let minimized = CFString::new("AXMinimized");
+            let result = AXUIElementSetAttributeValue(
+                ax_window,
+                minimized.as_concrete_TypeRef(),
+                CFBoolean::true_value().as_concrete_TypeRef() as *const c_void,
+            );
+            CFRelease(ax_window as *const c_void);
+            ax_result(result, window)
+        }
+    }
+
+    fn app_path(&self, pid: i32) -> Option<String> {
+        use cocoa::base::{id, nil};
+        use objc::{msg_send, sel, sel_impl};
+
+        unsafe {
+            let app = running_app(pid)?;
+            let url: id = msg_send![app, bundleURL];
+            if url == nil {
+                return None;
+            }
+            nsstring(msg_send![url, path])
+        }
+    }
+
+    fn displays(&self) -> Vec<DisplayInfo> {
+        use cocoa::appkit::NSScreen;
+        use cocoa::base::{id, nil};
+        use cocoa::foundation::{NSArray, NSAutoreleasePool, NSDictionary, NSRect, NSString};
+        use objc::{msg_send, sel, sel_impl};
+
+        let to_rect = |r: NSRect| WindowRect {
+            x: r.origin.x,
+            y: r.origin.y,
+            width: r.size.width,
+            height: r.size.height,
+        };
+
+        unsafe {
+            // Called from the watcher threads, which have no pool of their own
+            let pool = NSAutoreleasePool::new(nil);
+            let screens: id = NSScreen::screens(nil);
+            let count = NSArray::count(screens);
+            if count == 0 {
+                pool.drain();
+                return Vec::new();
+            }
+
+            // Cocoa measures y upwards from the bottom of the menu bar screen
+            let primary_height = NSScreen::frame(screens.objectAtIndex(0)).size.height;
+            let number_key = NSString::alloc(nil).init_str("NSScreenNumber").autorelease();
+
+            let displays: Vec<DisplayInfo> = (0..count)
+                .map(|i| {
+                    let screen = screens.objectAtIndex(i);
+                    let number: id = NSScreen::deviceDescription(screen).objectForKey_(number_key);
+                    let display_id: u32 = if number != nil { msg_send![number, unsignedIntValue] } else { 0 };
+
+                    // visibleFrame excludes the menu bar (including the notch
+                    // area) and a visible Dock, wherever it is docked
+                    DisplayInfo {
+                        id: display_id,
+                        frame: flip_y(&to_rect(NSScreen::frame(screen)), primary_height),
+                        work_area: flip_y(&to_rect(NSScreen::visibleFrame(screen)), primary_height),
+                        is_main: i == 0,
+                    }
+                })
+                .collect();
+            pool.drain();
+            displays
+        }
+    }
+}
+
+struct AxDetails {
+    minimized: bool,
+    fullscreen: bool,
+    subrole: Option<String>,
+}
+
+unsafe fn running_app(pid: i32) -> Option<cocoa::base::id> {
+    use cocoa::base::{id, nil};
+    use objc::{class, msg_send, sel, sel_impl};
+
+    let app: id = msg_send![class!(NSRunningApplication), runningApplicationWithProcessIdentifier: pid];
+    (app != nil).then_some(app)
+}
+
+unsafe fn nsstring(string: cocoa::base::id) -> Option<String> {
+    use objc::{msg_send, sel, sel_impl};
+
+    if string.is_null() {
+        return None;
+    }
+    let utf8: *const std::ffi::c_char = msg_send![string, UTF8String];
+    if utf8.is_null() {
+        return None;
+    }
+    Some(std::ffi::CStr::from_ptr(utf8).to_string_lossy().into_owned())
+}
+
+unsafe fn bundle_id(pid: i32) -> Option<String> {
+    use objc::{msg_send, sel, sel_impl};
+
+    let app = running_app(pid)?;
+    nsstring(msg_send![app, bundleIdentifier])
+}
+
+/// State the window server doesn't report, for each of `pid`'s AX windows by
+/// CG window id. Windows on other Spaces are usually missing from AXWindows.
+unsafe fn ax_details(pid: i32) -> HashMap<u32, AxDetails> {
+    let mut details = HashMap::new();
+    let app_ref = AXUIElementCreateApplication(pid);
+    if app_ref.is_null() {
+        return details;
+    }
+    let windows_ref = copy_ax_attribute(app_ref, "AXWindows");
+    CFRelease(app_ref as *const c_void);
+    let Some(windows_ref) = windows_ref else {
+        return details;
+    };
+
+    let windows: CFArray<AXUIElementRef> = CFArray::wrap_under_create_rule(windows_ref as CFArrayRef);
+    for i in 0..windows.len() {
+        let element = CFArrayGetValueAtIndex(windows.as_concrete_TypeRef(), i) as AXUIElementRef;
+        if let Some(id) = ax_window_id(element) {
+            details.insert(
+                id,
+                AxDetails {
+                    minimized: ax_bool(element, "AXMinimized").unwrap_or(false),
+                    fullscreen: ax_bool(element, "AXFullScreen").unwrap_or(false),
+                    subrole: ax_string(element, "AXSubrole"),
+                },
+            );
+        }
+    }
+    details
+}
+
+/// Returns a +1 retained attribute value, or `None` if it is missing.
+unsafe fn copy_ax_attribute(element: AXUIElementRef, name: &str) -> Option<*const c_void> {
+    let attr = CFString::new(name);
+    let mut value: *const c_void = ptr::null();
+    let result = AXUIElementCopyAttributeValue(element, attr.as_concrete_TypeRef(), &mut value);
+    if result != kAXErrorSuccess || value.is_null() {
+        return None;
+    }
+    Some(value)
+}
+
+unsafe fn ax_string(element: AXUIElementRef, name: &str) -> Option<String> {
+    let value = copy_ax_attribute(element, name)?;
+    Some(CFString::wrap_under_create_rule(value as CFStringRef).to_string())
+}
+
+unsafe fn ax_bool(element: AXUIElementRef, name: &str) -> Option<bool> {
+    let value = copy_ax_attribute(element, name)?;
+    Some(CFBoolean::wrap_under_create_rule(value as CFBooleanRef).into())
+}
+
+unsafe fn ax_frame(element: AXUIElementRef) -> Option<WindowRect> {
+    let mut pos = CGPoint { x: 0.0, y: 0.0 };
+    let mut size = CGSize {
+        width: 0.0,
+        height: 0.0,
+    };
+
+    let pos_val = copy_ax_attribute(element, "AXPosition")?;
+    let got_pos = AXValueGetValue(pos_val as AXValueRef, AX_VALUE_CG_POINT, &mut pos as *mut _ as *mut c_void);
+    CFRelease(pos_val);
+
+    let size_val = copy_ax_attribute(element, "AXSize")?;
+    let got_size = AXValueGetValue(size_val as AXValueRef, AX_VALUE_CG_SIZE, &mut size as *mut _ as *mut c_void);
+    CFRelease(size_val);
+
+    if !got_pos || !got_size {
+        return None;
+    }
+    Some(WindowRect {
+        x: pos.x,
+        y: pos.y,
+        width: size.width,
+        height: size.height,
+    })
+}
+
+unsafe fn ax_window_id(element: AXUIElementRef) -> Option<u32> {
+    let mut window_id = 0;
+    if _AXUIElementGetWindow(element, &mut window_id) != kAXErrorSuccess || window_id == 0 {
+        return None;
+    }
+    Some(window_id)
+}
+
+/// What an Accessibility error means for the caller.
+#[allow(non_upper_case_globals)]
+fn ax_result(result: AXError, window: &WindowInfo) -> Result<(), WindowError> {
+    match result {
+        kAXErrorSuccess => Ok(()),
+        kAXErrorAPIDisabled => Err(WindowError::PermissionDenied),
+        kAXErrorInvalidUIElement => Err(WindowError::WindowNotFound { id: Some(window.id) }),
+        kAXErrorCannotComplete | kAXErrorNotImplemented | kAXErrorAttributeUnsupported | kAXErrorActionUnsupported => {
+            Err(WindowError::AppNotScriptable {
+                app_name: window.app_name.clone(),
+            })
+        }
+        other => Err(format!("Accessibility error {}", other).into()),
+    }
+}
+
+/// Returns a +1 retained reference to the AX window behind `window`. Matches on
+/// the CG window id, falling back to title and frame when the id is unavailable.
+unsafe fn ax_window_for(window: &WindowInfo) -> Result<AXUIElementRef, WindowError> {
+    // Without permission every AX call fails; say why rather than "not found"
+    if !AXIsProcessTrusted() {
+        return Err(WindowError::PermissionDenied);
+    }
+    let not_found = || WindowError::WindowNotFound { id: Some(window.id) };
+
+    let app_ref = AXUIElementCreateApplication(window.pid);
+    if app_ref.is_null() {
+        return Err(not_found());
+    }
+
+    let attr = CFString::new("AXWindows");
+    let mut windows_ref: *const c_void = ptr::null();
+    let result = AXUIElementCopyAttributeValue(app_ref, attr.as_concrete_TypeRef(), &mut windows_ref);
+    CFRelease(app_ref as *const c_void);
+    ax_result(result, window)?;
+    if windows_ref.is_null() {
+        return Err(not_found());
+    }
+
+    let windows: CFArray<AXUIElementRef> = CFArray::wrap_under_create_rule(windows_ref as CFArrayRef);
+    let elements: Vec<AXUIElementRef> = (0..windows.len())
+        .map(|i| CFArrayGetValueAtIndex(windows.as_concrete_TypeRef(), i) as AXUIElementRef)
+        .collect();
+
+    let index = elements
+        .iter()
+        .position(|e| ax_window_id(*e) == Some(window.id))
+        .or_else(|| {
+            let described: Vec<(String, Option<WindowRect>)> = elements
+                .iter()
+                .map(|e| (ax_string(*e, "AXTitle").unwrap_or_default(), ax_frame(*e)))
+                .collect();
+            closest_match(
+                &window.title,
+                Some(&window.frame),
+                described.iter().map(|(t, f)| (t.as_str(), f.as_ref())),
+            )
+        })
+        .ok_or_else(not_found)?;
+
+    let window_ref = elements[index];
+    core_foundation::base::CFRetain(window_ref as *const c_void);
+    Ok(window_ref)
+}
+
+unsafe fn get_number_from_dict(
+    dict: &CFDictionary<*const c_void, *const c_void>,
+    key: &str,
+) -> Option<i64> {
+    let key_cf = CFString::new(key);
+    let key_ptr = key_cf.as_concrete_TypeRef() as *const c_void;
+
+    if let Some(val_ref) = dict.find(key_ptr) {
+        let val_ptr: *const c_void = *val_ref;
+        if val_ptr.is_null() {
+            return None;
+        }
+        let num = CFNumber::wrap_under_get_rule(val_ptr as CFNumberRef);
+        num.to_i64()
+    } else {
+        None
+    }
+}
+
+unsafe fn get_number_from_dict_float(
+    dict: &CFDictionary<*const c_void, *const c_void>,
+    key: &str,
+) -> Option<f64> {
+    let key_cf = CFString::new(key);
+    let key_ptr = key_cf.as_concrete_TypeRef() as *const c_void;
+
+    if let Some(val_ref) = dict.find(key_ptr) {
+        let val_ptr: *const c_void = *val_ref;
+        if val_ptr.is_null() {
+            return None;
+        }
+        let num = CFNumber::wrap_under_get_rule(val_ptr as CFNumberRef);
+        num.to_f64()
+    } else {
+        None
+    }
+}
+
+unsafe fn get_bool_from_dict(
+    dict: &CFDictionary<*const c_void, *const c_void>,
+    key: &str,
+) -> Option<bool> {
+    let key_cf = CFString::new(key);
+    let key_ptr = key_cf.as_concrete_TypeRef() as *const c_void;
+
+    let val_ptr: *const c_void = *dict.find(key_ptr)?;
+    if val_ptr.is_null() {
+        return None;
+    }
+    Some(CFBoolean::wrap_under_get_rule(val_ptr as CFBooleanRef).into())
+}
+
+unsafe fn get_string_from_dict(
+    dict: &CFDictionary<*const c_void, *const c_void>,
+    key: &str,
+) -> Option<String> {
+    let key_cf = CFString::new(key);
+    let key_ptr = key_cf.as_concrete_TypeRef() as *const c_void;
+
+    if let Some(val_ref) = dict.find(key_ptr) {
+        let val_ptr: *const c_void = *val_ref;
+        if val_ptr.is_null() {
+            return None;
+        }
+        let str_ref = CFString::wrap_under_get_rule(val_ptr as CFStringRef);
+        Some(str_ref.to_string())
+    } else {
+        None
+    }
+}
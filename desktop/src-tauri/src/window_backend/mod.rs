@@ -0,0 +1,240 @@
+//! Platform abstraction for external window management.
+//!
+//! `layout_manager` only talks to a `WindowBackend`, so every layout routine can
+//! be exercised against the in-memory `FakeBackend` in tests.
+
+use crate::layout_manager::{WindowInfo, WindowRect};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub mod geometry;
+#[cfg(target_os = "macos")]
+mod macos;
+#[cfg(target_os = "linux")]
+mod x11;
+#[cfg(test)]
+pub mod fake;
+
+#[cfg(target_os = "macos")]
+pub use macos::MacBackend;
+#[cfg(target_os = "linux")]
+pub use x11::X11Backend;
+
+/// A physical display. `frame` is the full screen, `work_area` excludes the
+/// menu bar, Dock and panels. Both use the same top-left global coordinates as
+/// `WindowInfo::frame`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DisplayInfo {
+    pub id: u32,
+    pub frame: WindowRect,
+    pub work_area: WindowRect,
+    pub is_main: bool,
+}
+
+/// Why a window could not be moved, focused or minimized. Commands send it to
+/// the frontend as e.g. `{ "kind": "permissionDenied" }` so it can tell the
+/// user what to do instead of the action silently doing nothing.
+#[derive(Debug, Serialize, Clone, PartialEq, thiserror::Error)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum WindowError {
+    /// Accessibility access hasn't been granted to the app
+    #[error("Accessibility permission is required to manage windows")]
+    PermissionDenied,
+    /// The app doesn't let its windows be moved or resized
+    #[error("{app_name} doesn't allow its windows to be moved")]
+    AppNotScriptable { app_name: String },
+    /// No such window, or none to act on. `id` is `None` when there was no
+    /// window to target at all.
+    #[error("Window not found")]
+    WindowNotFound { id: Option<u32> },
+    #[error("{message}")]
+    Failed { message: String },
+}
+
+impl WindowError {
+    /// The serialized `kind` tag.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            WindowError::PermissionDenied => "permissionDenied",
+            WindowError::AppNotScriptable { .. } => "appNotScriptable",
+            WindowError::WindowNotFound { .. } => "windowNotFound",
+            WindowError::Failed { .. } => "failed",
+        }
+    }
+}
+
+impl From<String> for WindowError {
+    fn from(message: String) -> Self {
+        WindowError::Failed { message }
+    }
+}
+
+impl From<&str> for WindowError {
+    fn from(message: &str) -> Self {
+        message.to_string().into()
+    }
+}
+
+impl From<WindowError> for String {
+    fn from(error: WindowError) -> Self {
+        error.to_string()
+    }
+}
+
+pub trait WindowBackend: Send + Sync {
+    /// Normal (layer 0) on-screen windows, front-most first.
+    fn list_windows(&self) -> Vec<WindowInfo>;
+
+    /// Every window the backend can see, front-most first: also minimized
+    /// ones, those on other Spaces or desktops and other layers, with their
+    /// `state`, `role` and `bundle_id` filled in. Slower than `list_windows`.
+    fn list_all_windows(&self) -> Vec<WindowInfo> {
+        self.list_windows()
+    }
+
+    /// The window that currently has keyboard focus, if any.
+    fn focused_window(&self) -> Option<WindowInfo>;
+
+    /// Moves and resizes `window` to `frame`.
+    fn set_frame(&self, window: &WindowInfo, frame: &WindowRect) -> Result<(), WindowError>;
+
+    /// Raises `window` and activates its application.
+    fn focus(&self, window: &WindowInfo) -> Result<(), WindowError>;
+
+    fn minimize(&self, window: &WindowInfo) -> Result<(), WindowError>;
+
+    fn displays(&self) -> Vec<DisplayInfo>;
+
+    /// Path that relaunches the app owning `pid`, if it can be found.
+    fn app_path(&self, _pid: i32) -> Option<String> {
+        None
+    }
+}
+
+pub type SharedBackend = Arc<dyn WindowBackend>;
+
+// Frames reported by different APIs for the same window can differ by rounding
+const FRAME_TOLERANCE: f64 = 1.0;
+
+pub(crate) fn frames_match(a: &WindowRect, b: &WindowRect) -> bool {
+    (a.x - b.x).abs() <= FRAME_TOLERANCE
+        && (a.y - b.y).abs() <= FRAME_TOLERANCE
+        && (a.width - b.width).abs() <= FRAME_TOLERANCE
+        && (a.height - b.height).abs() <= FRAME_TOLERANCE
+}
+
+/// Index of the candidate `(title, frame)` that best describes the same window
+/// when no window id is available: same frame and title, then same frame, then
+/// same non-empty title.
+#[cfg(any(target_os = "macos", test))]
+pub(crate) fn closest_match<'a>(
+    title: &str,
+    frame: Option<&WindowRect>,
+    candidates: impl Iterator<Item = (&'a str, Option<&'a WindowRect>)>,
+) -> Option<usize> {
+    let candidates: Vec<_> = candidates.collect();
+    let same_frame = |f: Option<&WindowRect>| matches!((frame, f), (Some(a), Some(b)) if frames_match(a, b));
+
+    candidates
+        .iter()
+        .position(|(t, f)| same_frame(*f) && *t == title)
+        .or_else(|| candidates.iter().position(|(_, f)| same_frame(*f)))
+        .or_else(|| candidates.iter().position(|(t, _)| !title.is_empty() && *t == title))
+}
+
+/// Backend for the current OS. Falls back to a no-op backend when none is
+/// available (unsupported OS, or no X server on Linux).
+pub fn platform_backend() -> SharedBackend {
+    #[cfg(target_os = "macos")]
+    {
+        Arc::new(MacBackend::new())
+    }
+    #[cfg(target_os = "linux")]
+    {
+        match X11Backend::connect() {
+            Ok(backend) => Arc::new(backend),
+            Err(e) => {
+                eprintln!("Window management unavailable: {}", e);
+                Arc::new(NullBackend)
+            }
+        }
+    }
+    #[cfg(not(any(target_os = "macos", target_os = "linux")))]
+    {
+        Arc::new(NullBackend)
+    }
+}
+
+#[cfg(not(target_os = "macos"))]
+struct NullBackend;
+
+#[cfg(not(target_os = "macos"))]
+impl WindowBackend for NullBackend {
+    fn list_windows(&self) -> Vec<WindowInfo> {
+        Vec::new()
+    }
+
+    fn focused_window(&self) -> Option<WindowInfo> {
+        None
+    }
+
+    fn set_frame(&self, _window: &WindowInfo, _frame: &WindowRect) -> Result<(), WindowError> {
+        Err("Window management is not supported on this system".into())
+    }
+
+    fn focus(&self, _window: &WindowInfo) -> Result<(), WindowError> {
+        Err("Window management is not supported on this system".into())
+    }
+
+    fn minimize(&self, _window: &WindowInfo) -> Result<(), WindowError> {
+        Err("Window management is not supported on this system".into())
+    }
+
+    fn displays(&self) -> Vec<DisplayInfo> {
+        Vec::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fake::rect;
+
+    #[test]
+    fn closest_match_prefers_frame_and_title() {
+        let a = rect(0.0, 0.0, 500.0, 500.0);
+        let b = rect(600.0, 0.0, 500.0, 500.0);
+        let candidates = [("Inbox", Some(&a)), ("Docs", Some(&b)), ("Docs", Some(&a))];
+
+        let found = closest_match("Docs", Some(&rect(0.5, 0.0, 500.0, 499.5)), candidates.into_iter());
+        assert_eq!(found, Some(2));
+    }
+
+    #[test]
+    fn closest_match_falls_back_to_frame_then_title() {
+        let a = rect(0.0, 0.0, 500.0, 500.0);
+        let b = rect(600.0, 0.0, 500.0, 500.0);
+        let candidates = [("Inbox", Some(&a)), ("Docs", None), ("Renamed", Some(&b))];
+
+        assert_eq!(closest_match("Docs", Some(&b), candidates.into_iter()), Some(2));
+        assert_eq!(closest_match("Docs", Some(&rect(9.0, 9.0, 9.0, 9.0)), candidates.into_iter()), Some(1));
+        assert_eq!(closest_match("", None, candidates.into_iter()), None);
+    }
+
+    #[test]
+    fn window_errors_serialize_with_a_kind() {
+        let json = |e: WindowError| serde_json::to_value(e).unwrap();
+
+        assert_eq!(json(WindowError::PermissionDenied), serde_json::json!({ "kind": "permissionDenied" }));
+        assert_eq!(
+            json(WindowError::AppNotScriptable { app_name: "Finder".into() }),
+            serde_json::json!({ "kind": "appNotScriptable", "appName": "Finder" })
+        );
+        assert_eq!(
+            json(WindowError::WindowNotFound { id: None }),
+            serde_json::json!({ "kind": "windowNotFound", "id": null })
+        );
+        assert_eq!(String::from(WindowError::from("boom")), "boom");
+        assert_eq!(WindowError::PermissionDenied.kind(), "permissionDenied");
+    }
+}
@@ -0,0 +1,454 @@
+use super::geometry::{apply_struts, full_strut, intersect};
+use super::{DisplayInfo, WindowBackend, WindowError};
+use crate::layout_manager::{WindowInfo, WindowRect, WindowState};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use x11rb::connection::Connection;
+use x11rb::protocol::randr::ConnectionExt as _;
+use x11rb::protocol::xproto::{
+    Atom, AtomEnum, ClientMessageEvent, ConnectionExt as _, EventMask, Window,
+};
+use x11rb::rust_connection::RustConnection;
+use x11rb::CURRENT_TIME;
+
+// _NET_WM_STATE actions
+const NET_WM_STATE_REMOVE: u32 = 0;
+// Source indication for EWMH client messages: 2 = pager / direct user action
+const SOURCE_PAGER: u32 = 2;
+// Window gravity for _NET_MOVERESIZE_WINDOW. Static puts the client window
+// itself at x/y, which is what `frame` reads; NorthWest would put the WM's
+// decorations there and shift the window by their size.
+const STATIC_GRAVITY: u32 = 10;
+// ICCCM WM_CHANGE_STATE
+const ICONIC_STATE: u32 = 3;
+// _NET_WM_DESKTOP value for windows shown on all desktops
+const ALL_DESKTOPS: u32 = 0xFFFF_FFFF;
+// Stand-ins for CoreGraphics window levels of kept-above / kept-below windows
+const LAYER_ABOVE: i32 = 3;
+const LAYER_BELOW: i32 = -1;
+
+const WINDOW_TYPES: [(&str, &str); 8] = [
+    ("_NET_WM_WINDOW_TYPE_NORMAL", "normal"),
+    ("_NET_WM_WINDOW_TYPE_DIALOG", "dialog"),
+    ("_NET_WM_WINDOW_TYPE_UTILITY", "utility"),
+    ("_NET_WM_WINDOW_TYPE_TOOLBAR", "toolbar"),
+    ("_NET_WM_WINDOW_TYPE_MENU", "menu"),
+    ("_NET_WM_WINDOW_TYPE_SPLASH", "splash"),
+    ("_NET_WM_WINDOW_TYPE_DOCK", "dock"),
+    ("_NET_WM_WINDOW_TYPE_DESKTOP", "desktop"),
+];
+// Types `list_windows` reports: like CoreGraphics' layer 0, normal windows and
+// their dialogs, so window rules can tell the two apart
+const LISTED_TYPES: [&str; 2] = ["normal", "dialog"];
+
+/// EWMH window management over a plain X11 connection. Needs an EWMH-compliant
+/// window manager (GNOME, KDE, Xfwm, i3, ...).
+pub struct X11Backend {
+    conn: RustConnection,
+    screen: usize,
+    root: Window,
+    atoms: Mutex<HashMap<&'static str, Atom>>,
+}
+
+impl X11Backend {
+    pub fn connect() -> Result<Self, String> {
+        let (conn, screen_num) = x11rb::connect(None).map_err(|e| e.to_string())?;
+        let root = conn.setup().roots[screen_num].root;
+        Ok(Self {
+            conn,
+            screen: screen_num,
+            root,
+            atoms: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn atom(&self, name: &'static str) -> Atom {
+        let mut atoms = self.atoms.lock().unwrap();
+        if let Some(atom) = atoms.get(name) {
+            return *atom;
+        }
+        let atom = self
+            .conn
+            .intern_atom(false, name.as_bytes())
+            .ok()
+            .and_then(|c| c.reply().ok())
+            .map(|r| r.atom)
+            .unwrap_or(x11rb::NONE);
+        atoms.insert(name, atom);
+        atom
+    }
+
+    fn atom_name(&self, atom: Atom) -> Option<String> {
+        let reply = self.conn.get_atom_name(atom).ok()?.reply().ok()?;
+        Some(String::from_utf8_lossy(&reply.name).into_owned())
+    }
+
+    fn property_u32s(&self, window: Window, name: &'static str, type_: impl Into<Atom>) -> Vec<u32> {
+        self.conn
+            .get_property(false, window, self.atom(name), type_.into(), 0, u32::MAX / 4)
+            .ok()
+            .and_then(|c| c.reply().ok())
+            .and_then(|r| r.value32().map(|v| v.collect()))
+            .unwrap_or_default()
+    }
+
+    fn property_string(&self, window: Window, name: &'static str, type_: impl Into<Atom>) -> Option<String> {
+        let reply = self
+            .conn
+            .get_property(false, window, self.atom(name), type_.into(), 0, 1024)
+            .ok()?
+            .reply()
+            .ok()?;
+        if reply.value.is_empty() {
+            return None;
+        }
+        Some(String::from_utf8_lossy(&reply.value).into_owned())
+    }
+
+    fn title(&self, window: Window) -> String {
+        let utf8 = self.atom("UTF8_STRING");
+        self.property_string(window, "_NET_WM_NAME", utf8)
+            .or_else(|| self.property_string(window, "WM_NAME", AtomEnum::STRING))
+            .unwrap_or_default()
+    }
+
+    /// WM_CLASS is "instance\0Class\0": the class is the application name, the
+    /// instance (usually the binary name) serves as its bundle id.
+    fn class_hint(&self, window: Window) -> (String, Option<String>) {
+        let Some(class) = self.property_string(window, "WM_CLASS", AtomEnum::STRING) else {
+            return (String::new(), None);
+        };
+        let parts: Vec<&str> = class.split('\0').filter(|s| !s.is_empty()).collect();
+        let app_name = parts.last().copied().unwrap_or_default().to_string();
+        let instance = parts.first().map(|s| s.to_string());
+        (app_name, instance)
+    }
+
+    fn frame(&self, window: Window) -> Option<WindowRect> {
+        let geometry = self.conn.get_geometry(window).ok()?.reply().ok()?;
+        let origin = self
+            .conn
+            .translate_coordinates(window, self.root, 0, 0)
+            .ok()?
+            .reply()
+            .ok()?;
+
+        Some(WindowRect {
+            x: origin.dst_x as f64,
+            y: origin.dst_y as f64,
+            width: geometry.width as f64,
+            height: geometry.height as f64,
+        })
+    }
+
+    fn current_desktop(&self) -> Option<u32> {
+        self.property_u32s(self.root, "_NET_CURRENT_DESKTOP", AtomEnum::CARDINAL)
+            .first()
+            .copied()
+    }
+
+    fn role(&self, window: Window) -> Option<String> {
+        let types = self.property_u32s(window, "_NET_WM_WINDOW_TYPE", AtomEnum::ATOM);
+        if types.is_empty() {
+            return Some("normal".into());
+        }
+        // Types are listed in order of preference; the first one we know wins
+        types.iter().find_map(|t| {
+            WINDOW_TYPES
+                .iter()
+                .find(|(atom, _)| self.atom(atom) == *t)
+                .map(|(_, role)| role.to_string())
+        })
+    }
+
+    fn state(&self, window: Window, current_desktop: Option<u32>) -> WindowState {
+        let states = self.property_u32s(window, "_NET_WM_STATE", AtomEnum::ATOM);
+        let has = |name: &'static str| states.contains(&self.atom(name));
+
+        let minimized = has("_NET_WM_STATE_HIDDEN");
+        let desktop = self.property_u32s(window, "_NET_WM_DESKTOP", AtomEnum::CARDINAL);
+        let on_desktop = match (current_desktop, desktop.first()) {
+            (Some(current), Some(desktop)) => *desktop == current || *desktop == ALL_DESKTOPS,
+            _ => true,
+        };
+        let layer = if has("_NET_WM_STATE_ABOVE") {
+            LAYER_ABOVE
+        } else if has("_NET_WM_STATE_BELOW") {
+            LAYER_BELOW
+        } else {
+            0
+        };
+
+        WindowState {
+            minimized,
+            fullscreen: has("_NET_WM_STATE_FULLSCREEN"),
+            focused: false,
+            on_screen: on_desktop && !minimized,
+            layer,
+        }
+    }
+
+    fn window_info(&self, window: Window, current_desktop: Option<u32>) -> Option<WindowInfo> {
+        let pid = self
+            .property_u32s(window, "_NET_WM_PID", AtomEnum::CARDINAL)
+            .first()
+            .copied()
+            .unwrap_or(0) as i32;
+        let (app_name, bundle_id) = self.class_hint(window);
+
+        Some(WindowInfo {
+            id: window,
+            pid,
+            title: self.title(window),
+            app_name,
+            frame: self.frame(window)?,
+            app_path: None,
+            display_id: None,
+            relative_frame: None,
+            bundle_id,
+            role: self.role(window),
+            state: self.state(window, current_desktop),
+        })
+    }
+
+    /// Asks the window manager to act on `window`, which must still be one of
+    /// its clients: messages about windows that are gone are silently dropped.
+    fn send_root_message(&self, window: Window, message: &'static str, data: [u32; 5]) -> Result<(), WindowError> {
+        let clients = self.property_u32s(self.root, "_NET_CLIENT_LIST", AtomEnum::WINDOW);
+        if !clients.is_empty() && !clients.contains(&window) {
+            return Err(WindowError::WindowNotFound { id: Some(window) });
+        }
+
+        let event = ClientMessageEvent::new(32, window, self.atom(message), data);
+        self.conn
+            .send_event(
+                false,
+                self.root,
+                EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+                event,
+            )
+            .map_err(|e| e.to_string())?;
+        self.conn.flush().map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn work_area(&self) -> Option<WindowRect> {
+        let current = self.current_desktop().unwrap_or(0) as usize;
+        let areas = self.property_u32s(self.root, "_NET_WORKAREA", AtomEnum::CARDINAL);
+        let area = areas.chunks(4).nth(current).or_else(|| areas.chunks(4).next())?;
+        if area.len() < 4 {
+            return None;
+        }
+        Some(WindowRect {
+            x: area[0] as f64,
+            y: area[1] as f64,
+            width: area[2] as f64,
+            height: area[3] as f64,
+        })
+    }
+
+    /// Space reserved by panels and docks, as partial struts.
+    fn struts(&self) -> Vec<[u32; 12]> {
+        self.property_u32s(self.root, "_NET_CLIENT_LIST", AtomEnum::WINDOW)
+            .into_iter()
+            .filter_map(|w| {
+                let partial = self.property_u32s(w, "_NET_WM_STRUT_PARTIAL", AtomEnum::CARDINAL);
+                if let Ok(strut) = <[u32; 12]>::try_from(partial.as_slice()) {
+                    return Some(strut);
+                }
+                let legacy = self.property_u32s(w, "_NET_WM_STRUT", AtomEnum::CARDINAL);
+                <[u32; 4]>::try_from(legacy.as_slice()).ok().map(full_strut)
+            })
+            .collect()
+    }
+}
+
+/// A display id that stays the same across sessions: the 32-bit FNV-1a hash of
+/// the monitor's connector name (e.g. "DP-1").
+fn display_id(name: &str) -> u32 {
+    name.bytes()
+        .fold(0x811c_9dc5, |hash, byte| (hash ^ u32::from(byte)).wrapping_mul(0x0100_0193))
+}
+
+/// `_NET_MOVERESIZE_WINDOW` data putting the client window at `frame`.
+fn moveresize_data(frame: &WindowRect) -> [u32; 5] {
+    // Gravity, x/y/width/height present (bits 8-11), pager source (bits 12-13)
+    let flags = STATIC_GRAVITY | (0b1111 << 8) | (SOURCE_PAGER << 12);
+    [
+        flags,
+        frame.x.round() as i32 as u32,
+        frame.y.round() as i32 as u32,
+        frame.width.round().max(1.0) as u32,
+        frame.height.round().max(1.0) as u32,
+    ]
+}
+
+impl WindowBackend for X11Backend {
+    fn list_windows(&self) -> Vec<WindowInfo> {
+        // Equivalent of CoreGraphics' on-screen layer 0 windows
+        self.list_all_windows()
+            .into_iter()
+            .filter(|w| w.role.as_deref().is_some_and(|role| LISTED_TYPES.contains(&role)) && w.state.on_screen)
+            .collect()
+    }
+
+    fn list_all_windows(&self) -> Vec<WindowInfo> {
+        let current_desktop = self.current_desktop();
+
+        // Stacking order is bottom to top; callers expect front-most first
+        let mut clients = self.property_u32s(self.root, "_NET_CLIENT_LIST_STACKING", AtomEnum::WINDOW);
+        if clients.is_empty() {
+            clients = self.property_u32s(self.root, "_NET_CLIENT_LIST", AtomEnum::WINDOW);
+        }
+
+        clients
+            .into_iter()
+            .rev()
+            .filter_map(|w| self.window_info(w, current_desktop))
+            .collect()
+    }
+
+    fn focused_window(&self) -> Option<WindowInfo> {
+        let active = *self
+            .property_u32s(self.root, "_NET_ACTIVE_WINDOW", AtomEnum::WINDOW)
+            .first()?;
+        if active == x11rb::NONE {
+            return None;
+        }
+        let mut window = self.window_info(active, self.current_desktop())?;
+        window.state.focused = true;
+        Some(window)
+    }
+
+    fn set_frame(&self, window: &WindowInfo, frame: &WindowRect) -> Result<(), WindowError> {
+        // A maximized window ignores move/resize requests on most window managers
+        self.send_root_message(
+            window.id,
+            "_NET_WM_STATE",
+            [
+                NET_WM_STATE_REMOVE,
+                self.atom("_NET_WM_STATE_MAXIMIZED_VERT"),
+                self.atom("_NET_WM_STATE_MAXIMIZED_HORZ"),
+                SOURCE_PAGER,
+                0,
+            ],
+        )?;
+
+        self.send_root_message(window.id, "_NET_MOVERESIZE_WINDOW", moveresize_data(frame))
+    }
+
+    fn focus(&self, window: &WindowInfo) -> Result<(), WindowError> {
+        self.send_root_message(
+            window.id,
+            "_NET_ACTIVE_WINDOW",
+            [SOURCE_PAGER, CURRENT_TIME, 0, 0, 0],
+        )
+    }
+
+    fn minimize(&self, window: &WindowInfo) -> Result<(), WindowError> {
+        self.send_root_message(window.id, "WM_CHANGE_STATE", [ICONIC_STATE, 0, 0, 0, 0])
+    }
+
+    fn app_path(&self, pid: i32) -> Option<String> {
+        if pid <= 0 {
+            return None;
+        }
+        let exe = std::fs::read_link(format!("/proc/{}/exe", pid)).ok()?;
+        Some(exe.to_string_lossy().into_owned())
+    }
+
+    fn displays(&self) -> Vec<DisplayInfo> {
+        let work_area = self.work_area();
+        let struts = self.struts();
+        let screen = &self.conn.setup().roots[self.screen];
+        let (root_width, root_height) = (screen.width_in_pixels as f64, screen.height_in_pixels as f64);
+
+        let monitors = self
+            .conn
+            .randr_get_monitors(self.root, true)
+            .ok()
+            .and_then(|c| c.reply().ok())
+            .map(|r| r.monitors)
+            .unwrap_or_default();
+
+        if monitors.is_empty() {
+            // No RandR 1.5: treat the whole root window as one display
+            let Some(frame) = self.frame(self.root) else {
+                return Vec::new();
+            };
+            let work_area = work_area.unwrap_or_else(|| apply_struts(&frame, root_width, root_height, &struts));
+            return vec![DisplayInfo {
+                id: 0,
+                frame,
+                work_area,
+                is_main: true,
+            }];
+        }
+
+        let has_primary = monitors.iter().any(|m| m.primary);
+        monitors
+            .iter()
+            .enumerate()
+            .map(|(i, m)| {
+                let frame = WindowRect {
+                    x: m.x as f64,
+                    y: m.y as f64,
+                    width: m.width as f64,
+                    height: m.height as f64,
+                };
+                // Struts give per-monitor reservations. _NET_WORKAREA spans all
+                // monitors but also covers panels that are not client windows
+                // (e.g. the GNOME top bar), so clip to it as well.
+                let available = apply_struts(&frame, root_width, root_height, &struts);
+                let work_area = work_area
+                    .as_ref()
+                    .and_then(|wa| intersect(&available, wa))
+                    .unwrap_or(available);
+
+                // Atom values change between X sessions; the connector name doesn't
+                let name = self.atom_name(m.name).unwrap_or_else(|| format!("monitor-{}", i));
+                DisplayInfo {
+                    id: display_id(&name),
+                    frame,
+                    work_area,
+                    is_main: if has_primary { m.primary } else { i == 0 },
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_backend::fake::rect;
+
+    /// A window manager with `left`/`top` decorations handling the request the
+    /// way EWMH says, returning where the client window ends up.
+    fn fake_wm(data: [u32; 5], left: f64, top: f64) -> WindowRect {
+        let (x, y) = (data[1] as i32 as f64, data[2] as i32 as f64);
+        let (x, y) = match data[0] & 0xff {
+            STATIC_GRAVITY => (x, y),
+            // NorthWest: x/y is the outer frame's corner
+            _ => (x + left, y + top),
+        };
+        rect(x, y, data[3] as f64, data[4] as f64)
+    }
+
+    #[test]
+    fn display_ids_come_from_connector_names() {
+        assert_eq!(display_id(""), 0x811c_9dc5);
+        assert_eq!(display_id("a"), 0xe40c_292c);
+        assert_ne!(display_id("DP-1"), display_id("DP-2"));
+    }
+
+    #[test]
+    fn frames_round_trip_without_drifting() {
+        let start = rect(-20.0, 64.0, 800.0, 600.0);
+        let mut frame = start.clone();
+        for _ in 0..3 {
+            frame = fake_wm(moveresize_data(&frame), 4.0, 28.0);
+        }
+        assert_eq!(frame, start);
+    }
+}
@@ -0,0 +1,284 @@
+//! Matches saved windows to live ones for layout restore.
+//!
+//! Every saved/live pair from the same app gets a score built from the window
+//! id, title similarity, size and display; the assignment with the highest total
+//! score wins, so each saved window gets at most one live window and vice versa.
+
+use crate::layout_manager::{display_for, WindowInfo, WindowRect};
+use crate::window_backend::DisplayInfo;
+use serde::Serialize;
+
+// Ids survive while the app keeps running, so an id match outweighs everything else
+const ID_WEIGHT: f64 = 10.0;
+const TITLE_WEIGHT: f64 = 4.0;
+const SIZE_WEIGHT: f64 = 2.0;
+const DISPLAY_WEIGHT: f64 = 1.0;
+// Any window of the right app beats leaving the saved frame unused
+const APP_WEIGHT: f64 = 1.0;
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchScore {
+    pub total: f64,
+    pub same_id: bool,
+    /// 0..=1, how alike the titles are
+    pub title: f64,
+    /// 0..=1, how alike the sizes are
+    pub size: f64,
+    pub same_display: bool,
+}
+
+/// How well `live` fits `saved`, or `None` when they belong to different apps.
+pub fn score(saved: &WindowInfo, live: &WindowInfo, displays: &[DisplayInfo]) -> Option<MatchScore> {
+    if saved.app_name != live.app_name {
+        return None;
+    }
+
+    let same_id = saved.id == live.id;
+    let title = title_similarity(&saved.title, &live.title);
+    let size = size_similarity(&saved.frame, &live.frame);
+    let saved_display = saved
+        .display_id
+        .or_else(|| display_for(displays, &saved.frame).map(|d| d.id));
+    let same_display = match (saved_display, display_for(displays, &live.frame)) {
+        (Some(a), Some(b)) => a == b.id,
+        _ => false,
+    };
+
+    let total = APP_WEIGHT
+        + if same_id { ID_WEIGHT } else { 0.0 }
+        + title * TITLE_WEIGHT
+        + size * SIZE_WEIGHT
+        + if same_display { DISPLAY_WEIGHT } else { 0.0 };
+
+    Some(MatchScore {
+        total,
+        same_id,
+        title,
+        size,
+        same_display,
+    })
+}
+
+/// Dice coefficient over lowercase character bigrams. Titles usually change by
+/// a suffix or counter ("Inbox (3)" -> "Inbox (4)"), which this tolerates.
+pub fn title_similarity(a: &str, b: &str) -> f64 {
+    let a = a.trim().to_lowercase();
+    let b = b.trim().to_lowercase();
+    if a == b {
+        return 1.0;
+    }
+
+    let bigrams = |s: &str| -> Vec<(char, char)> {
+        let chars: Vec<char> = s.chars().collect();
+        chars.windows(2).map(|w| (w[0], w[1])).collect()
+    };
+    let a = bigrams(&a);
+    let mut b = bigrams(&b);
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    let total = a.len() + b.len();
+    let mut shared = 0;
+    for pair in &a {
+        if let Some(i) = b.iter().position(|p| p == pair) {
+            b.swap_remove(i);
+            shared += 1;
+        }
+    }
+    2.0 * shared as f64 / total as f64
+}
+
+fn size_similarity(a: &WindowRect, b: &WindowRect) -> f64 {
+    let ratio = |x: f64, y: f64| {
+        let max = x.max(y);
+        if max <= 0.0 {
+            1.0
+        } else {
+            x.min(y).max(0.0) / max
+        }
+    };
+    (ratio(a.width, b.width) + ratio(a.height, b.height)) / 2.0
+}
+
+/// For each saved window, the index of the live window it goes to and how well
+/// it scored.
+pub fn assign(saved: &[WindowInfo], live: &[WindowInfo], displays: &[DisplayInfo]) -> Vec<Option<(usize, MatchScore)>> {
+    let scores: Vec<Vec<Option<MatchScore>>> = saved
+        .iter()
+        .map(|s| live.iter().map(|l| score(s, l, displays)).collect())
+        .collect();
+    let weights: Vec<Vec<f64>> = scores
+        .iter()
+        .map(|row| row.iter().map(|s| s.as_ref().map_or(0.0, |s| s.total)).collect())
+        .collect();
+
+    max_weight_assignment(&weights, live.len())
+        .into_iter()
+        .zip(scores)
+        .map(|(column, mut row)| {
+            let column = column?;
+            row[column].take().map(|score| (column, score))
+        })
+        .collect()
+}
+
+/// Hungarian algorithm on the square matrix padded with zero weights. Returns
+/// the column given to each row; zero-weight pairs count as unassigned.
+fn max_weight_assignment(weights: &[Vec<f64>], columns: usize) -> Vec<Option<usize>> {
+    let rows = weights.len();
+    let n = rows.max(columns);
+    if n == 0 {
+        return Vec::new();
+    }
+
+    let max = weights.iter().flatten().cloned().fold(0.0, f64::max);
+    let cost = |i: usize, j: usize| -> f64 {
+        let weight = if i < rows && j < columns { weights[i][j] } else { 0.0 };
+        max - weight
+    };
+
+    // 1-based potentials and matching, as in the classic O(n^3) formulation
+    let mut u = vec![0.0; n + 1];
+    let mut v = vec![0.0; n + 1];
+    let mut row_of = vec![0usize; n + 1];
+    let mut way = vec![0usize; n + 1];
+
+    for i in 1..=n {
+        row_of[0] = i;
+        let mut j0 = 0;
+        let mut min_to = vec![f64::INFINITY; n + 1];
+        let mut used = vec![false; n + 1];
+        loop {
+            used[j0] = true;
+            let i0 = row_of[j0];
+            let mut delta = f64::INFINITY;
+            let mut j1 = 0;
+            for j in 1..=n {
+                if used[j] {
+                    continue;
+                }
+                let reduced = cost(i0 - 1, j - 1) - u[i0] - v[j];
+                if reduced < min_to[j] {
+                    min_to[j] = reduced;
+                    way[j] = j0;
+                }
+                if min_to[j] < delta {
+                    delta = min_to[j];
+                    j1 = j;
+                }
+            }
+            for j in 0..=n {
+                if used[j] {
+                    u[row_of[j]] += delta;
+                    v[j] -= delta;
+                } else {
+                    min_to[j] -= delta;
+                }
+            }
+            j0 = j1;
+            if row_of[j0] == 0 {
+                break;
+            }
+        }
+        loop {
+            let j1 = way[j0];
+            row_of[j0] = row_of[j1];
+            j0 = j1;
+            if j0 == 0 {
+                break;
+            }
+        }
+    }
+
+    let mut assigned = vec![None; rows];
+    for j in 1..=n {
+        let i = row_of[j];
+        if i >= 1 && i <= rows && j <= columns && weights[i - 1][j - 1] > 0.0 {
+            assigned[i - 1] = Some(j - 1);
+        }
+    }
+    assigned
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_backend::fake::{display, rect, window};
+
+    #[test]
+    fn title_similarity_tolerates_small_changes() {
+        assert_eq!(title_similarity("Inbox", "inbox"), 1.0);
+        assert_eq!(title_similarity("", ""), 1.0);
+        assert_eq!(title_similarity("Inbox", ""), 0.0);
+        assert!(title_similarity("Inbox (3)", "Inbox (4)") > 0.7);
+        assert!(title_similarity("Inbox (3)", "Calendar") < 0.2);
+    }
+
+    #[test]
+    fn windows_of_other_apps_never_match() {
+        let saved = window(1, 10, "Safari", "Docs", rect(0.0, 0.0, 500.0, 500.0));
+        let live = window(1, 10, "Notes", "Docs", rect(0.0, 0.0, 500.0, 500.0));
+        assert_eq!(score(&saved, &live, &[]), None);
+    }
+
+    #[test]
+    fn score_weighs_each_signal() {
+        let displays = [display(1, 0.0, 0.0), display(2, 1000.0, 0.0)];
+        let saved = window(1, 10, "Safari", "Docs", rect(0.0, 0.0, 500.0, 500.0));
+
+        let exact = score(&saved, &saved, &displays).unwrap();
+        assert!(exact.same_id && exact.same_display);
+        assert_eq!(exact.total, APP_WEIGHT + ID_WEIGHT + TITLE_WEIGHT + SIZE_WEIGHT + DISPLAY_WEIGHT);
+
+        let moved = window(2, 10, "Safari", "Docs", rect(1200.0, 0.0, 250.0, 500.0));
+        let moved = score(&saved, &moved, &displays).unwrap();
+        assert!(!moved.same_id && !moved.same_display);
+        assert_eq!(moved.size, 0.75);
+        assert_eq!(moved.total, APP_WEIGHT + TITLE_WEIGHT + 0.75 * SIZE_WEIGHT);
+    }
+
+    #[test]
+    fn two_windows_of_one_app_get_separate_frames() {
+        let live = [
+            window(10, 1, "Safari", "News", rect(0.0, 0.0, 800.0, 600.0)),
+            window(11, 1, "Safari", "Docs", rect(0.0, 0.0, 400.0, 300.0)),
+        ];
+        let saved = [
+            window(90, 1, "Safari", "Docs — Reference", rect(0.0, 0.0, 400.0, 300.0)),
+            window(91, 1, "Safari", "News Today", rect(0.0, 0.0, 800.0, 600.0)),
+        ];
+
+        let assigned: Vec<_> = assign(&saved, &live, &[]).into_iter().map(|a| a.map(|(j, _)| j)).collect();
+        assert_eq!(assigned, vec![Some(1), Some(0)]);
+    }
+
+    #[test]
+    fn assignment_maximises_the_total_not_each_row() {
+        // Row 0 prefers column 0 slightly, but row 1 only fits column 0
+        let weights = vec![vec![5.0, 4.5], vec![3.0, 0.0]];
+        assert_eq!(max_weight_assignment(&weights, 2), vec![Some(1), Some(0)]);
+    }
+
+    #[test]
+    fn extra_saved_or_live_windows_stay_unassigned() {
+        let live = [window(10, 1, "Safari", "News", rect(0.0, 0.0, 800.0, 600.0))];
+        let saved = [
+            window(90, 1, "Safari", "Mail", rect(0.0, 0.0, 800.0, 600.0)),
+            window(10, 1, "Safari", "News", rect(0.0, 0.0, 800.0, 600.0)),
+            window(92, 1, "Music", "", rect(0.0, 0.0, 800.0, 600.0)),
+        ];
+
+        let assigned: Vec<_> = assign(&saved, &live, &[]).into_iter().map(|a| a.map(|(j, _)| j)).collect();
+        assert_eq!(assigned, vec![None, Some(0), None]);
+
+        let saved = [window(10, 1, "Safari", "News", rect(0.0, 0.0, 800.0, 600.0))];
+        let live = [
+            window(11, 1, "Safari", "Mail", rect(0.0, 0.0, 800.0, 600.0)),
+            window(10, 1, "Safari", "News", rect(0.0, 0.0, 800.0, 600.0)),
+        ];
+        let assigned: Vec<_> = assign(&saved, &live, &[]).into_iter().map(|a| a.map(|(j, _)| j)).collect();
+        assert_eq!(assigned, vec![Some(1)]);
+    }
+}
@@ -0,0 +1,284 @@
+//! Keyboard-sized window moves: nudging, growing or shrinking around the
+//! centre and hopping between displays. Moves are recorded in the
+//! `LayoutHistory`, so they undo like any other layout change.
+
+use crate::error::AppError;
+use crate::layout_history::LayoutHistory;
+use crate::layout_manager::{
+    display_for, displays_left_to_right, get_open_windows, target_window, FocusTracker, IgnoredApps, WindowInfo, WindowRect, MIN_WINDOW_SIZE,
+};
+use crate::permissions::AccessibilityPermission;
+use crate::window_backend::{DisplayInfo, SharedBackend, WindowBackend, WindowError};
+use serde::Deserialize;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, ShortcutState};
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum MoveAction {
+    /// Move by `dx`/`dy` pixels
+    Nudge { dx: f64, dy: f64 },
+    /// Grow (positive) or shrink (negative) width and height by `amount`
+    /// pixels, keeping the centre in place
+    Resize { amount: f64 },
+    NextDisplay,
+    PreviousDisplay,
+    /// Undo the last layout change, e.g. the previous move
+    Undo,
+}
+
+pub fn nudged(frame: &WindowRect, dx: f64, dy: f64) -> WindowRect {
+    WindowRect {
+        x: frame.x + dx,
+        y: frame.y + dy,
+        ..frame.clone()
+    }
+}
+
+/// `frame` grown or shrunk around its centre. Never smaller than a manageable
+/// window; when growing, never larger than `area` and pushed back inside it.
+pub fn resized(frame: &WindowRect, amount: f64, area: &WindowRect) -> WindowRect {
+    let size = |current: f64, max: f64| {
+        let size = (current + amount).max(MIN_WINDOW_SIZE.min(current));
+        if amount > 0.0 {
+            size.min(max.max(current))
+        } else {
+            size
+        }
+    };
+    let width = size(frame.width, area.width);
+    let height = size(frame.height, area.height);
+    let (cx, cy) = frame.center();
+    let mut x = cx - width / 2.0;
+    let mut y = cy - height / 2.0;
+
+    if amount > 0.0 {
+        let keep_inside = |v: f64, size: f64, start: f64, extent: f64| {
+            v.min(start + extent - size).max(start)
+        };
+        x = keep_inside(x, width, area.x, area.width);
+        y = keep_inside(y, height, area.y, area.height);
+    }
+
+    WindowRect {
+        x: x.round(),
+        y: y.round(),
+        width: width.round(),
+        height: height.round(),
+    }
+}
+
+/// `frame` on the next (or previous) display in left-to-right order, at the
+/// same position relative to the work area. `None` with a single display.
+pub fn on_adjacent_display(frame: &WindowRect, displays: &[DisplayInfo], forward: bool) -> Option<WindowRect> {
+    if displays.len() < 2 {
+        return None;
+    }
+    let ordered = displays_left_to_right(displays);
+
+    let current = display_for(displays, frame)?;
+    let index = ordered.iter().position(|d| d.id == current.id)?;
+    let next = if forward {
+        (index + 1) % ordered.len()
+    } else {
+        (index + ordered.len() - 1) % ordered.len()
+    };
+
+    Some(frame.relative_to(&current.work_area).scaled_to(&ordered[next].work_area))
+}
+
+/// Applies `action` to the target window (see `target_window`), recording the
+/// move in `history` so it can be undone.
+pub fn move_active_window(
+    backend: &dyn WindowBackend,
+    history: &LayoutHistory,
+    action: MoveAction,
+    remembered: Option<&WindowInfo>,
+    ignored: &[String],
+    my_pid: i32,
+) -> Result<(), WindowError> {
+    if action == MoveAction::Undo {
+        return history.undo(backend).map(|_| ());
+    }
+
+    let windows = get_open_windows(backend, ignored, my_pid);
+    let window = target_window(backend, &windows, remembered, ignored, my_pid).ok_or(WindowError::WindowNotFound { id: None })?;
+
+    let displays = backend.displays();
+    let new_frame = match action {
+        MoveAction::Nudge { dx, dy } => Some(nudged(&window.frame, dx, dy)),
+        MoveAction::Resize { amount } => {
+            display_for(&displays, &window.frame).map(|d| resized(&window.frame, amount, &d.work_area))
+        }
+        MoveAction::NextDisplay => on_adjacent_display(&window.frame, &displays, true),
+        MoveAction::PreviousDisplay => on_adjacent_display(&window.frame, &displays, false),
+        MoveAction::Undo => None,
+    };
+    let Some(new_frame) = new_frame else {
+        return Ok(());
+    };
+
+    history.record(backend, |backend| backend.set_frame(&window, &new_frame))
+}
+
+fn run_action(app: &AppHandle, action: MoveAction) -> Result<(), WindowError> {
+    app.state::<AccessibilityPermission>().require()?;
+    let backend = app.state::<SharedBackend>();
+    let focus = app.state::<FocusTracker>();
+    let history = app.state::<LayoutHistory>();
+    let ignored = app.state::<IgnoredApps>().get();
+    move_active_window(
+        backend.as_ref(),
+        &history,
+        action,
+        focus.last().as_ref(),
+        &ignored,
+        std::process::id() as i32,
+    )
+}
+
+#[tauri::command]
+pub fn move_window(app: AppHandle, action: MoveAction) -> Result<(), AppError> {
+    run_action(&app, action).map_err(AppError::from)
+}
+
+/// Runs `action` whenever the global `shortcut` (e.g. "Control+Alt+Right") is
+/// pressed, replacing whatever it was bound to before.
+#[tauri::command]
+pub fn bind_window_shortcut(app: AppHandle, shortcut: String, action: MoveAction) -> Result<(), AppError> {
+    let shortcut_error = |e: tauri_plugin_global_shortcut::Error| AppError::Shortcut {
+        shortcut: shortcut.clone(),
+        reason: e.to_string(),
+    };
+    let shortcuts = app.global_shortcut();
+    if shortcuts.is_registered(shortcut.as_str()) {
+        shortcuts.unregister(shortcut.as_str()).map_err(shortcut_error)?;
+    }
+    shortcuts
+        .on_shortcut(shortcut.as_str(), move |app, _, event| {
+            if event.state == ShortcutState::Pressed {
+                if let Err(e) = run_action(app, action) {
+                    eprintln!("Window shortcut failed: {}", e);
+                }
+            }
+        })
+        .map_err(shortcut_error)
+}
+
+#[tauri::command]
+pub fn unbind_window_shortcut(app: AppHandle, shortcut: String) -> Result<(), AppError> {
+    app.global_shortcut()
+        .unregister(shortcut.as_str())
+        .map_err(|e| AppError::Shortcut {
+            shortcut: shortcut.clone(),
+            reason: e.to_string(),
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_backend::fake::{display, rect, window, FakeBackend};
+
+    const MY_PID: i32 = 1;
+
+    fn backend(frame: WindowRect) -> FakeBackend {
+        FakeBackend::new(
+            vec![window(10, 100, "Safari", "Home", frame)],
+            vec![display(1, 0.0, 0.0), display(2, 1000.0, 0.0)],
+        )
+    }
+
+    fn frame_of(fake: &FakeBackend) -> WindowRect {
+        fake.windows.lock().unwrap()[0].frame.clone()
+    }
+
+    fn apply(fake: &FakeBackend, history: &LayoutHistory, action: MoveAction) {
+        move_active_window(fake, history, action, None, &[], MY_PID).unwrap();
+    }
+
+    #[test]
+    fn resize_keeps_the_centre() {
+        let area = rect(0.0, 25.0, 1000.0, 775.0);
+        let frame = rect(300.0, 225.0, 400.0, 300.0);
+
+        assert_eq!(resized(&frame, 40.0, &area), rect(280.0, 205.0, 440.0, 340.0));
+        assert_eq!(resized(&frame, -40.0, &area), rect(320.0, 245.0, 360.0, 260.0));
+    }
+
+    #[test]
+    fn resize_stays_within_limits() {
+        let area = rect(0.0, 25.0, 1000.0, 775.0);
+
+        // Shrinking stops at the minimum window size
+        let small = resized(&rect(100.0, 100.0, 120.0, 120.0), -100.0, &area);
+        assert_eq!((small.width, small.height), (MIN_WINDOW_SIZE, MIN_WINDOW_SIZE));
+
+        // Growing near an edge pushes the window back inside the work area
+        let grown = resized(&rect(0.0, 25.0, 400.0, 300.0), 100.0, &area);
+        assert_eq!(grown, rect(0.0, 25.0, 500.0, 400.0));
+
+        // ...and never past its size
+        let full = resized(&rect(50.0, 50.0, 950.0, 700.0), 200.0, &area);
+        assert_eq!(full, rect(0.0, 25.0, 1000.0, 775.0));
+    }
+
+    #[test]
+    fn display_hop_keeps_relative_position_and_wraps() {
+        let displays = [display(2, 1000.0, 0.0), display(1, 0.0, 0.0)];
+        let frame = rect(100.0, 25.0 + 155.0, 500.0, 310.0);
+
+        let next = on_adjacent_display(&frame, &displays, true).unwrap();
+        assert_eq!(next, rect(1100.0, 180.0, 500.0, 310.0));
+        assert_eq!(on_adjacent_display(&next, &displays, true).unwrap(), frame);
+        assert_eq!(on_adjacent_display(&frame, &displays, false).unwrap(), next);
+
+        assert_eq!(on_adjacent_display(&frame, &displays[..1], true), None);
+    }
+
+    #[test]
+    fn moves_can_be_undone_step_by_step() {
+        let start = rect(100.0, 100.0, 400.0, 300.0);
+        let fake = backend(start.clone());
+        let history = LayoutHistory::new();
+
+        apply(&fake, &history, MoveAction::Nudge { dx: 10.0, dy: -5.0 });
+        assert_eq!(frame_of(&fake), rect(110.0, 95.0, 400.0, 300.0));
+        apply(&fake, &history, MoveAction::NextDisplay);
+        assert_eq!(frame_of(&fake).x, 1110.0);
+
+        apply(&fake, &history, MoveAction::Undo);
+        assert_eq!(frame_of(&fake), rect(110.0, 95.0, 400.0, 300.0));
+        apply(&fake, &history, MoveAction::Undo);
+        assert_eq!(frame_of(&fake), start);
+
+        // Nothing left to undo
+        apply(&fake, &history, MoveAction::Undo);
+        assert_eq!(frame_of(&fake), start);
+    }
+
+    #[test]
+    fn undone_moves_can_be_redone() {
+        let start = rect(100.0, 100.0, 400.0, 300.0);
+        let fake = backend(start.clone());
+        let history = LayoutHistory::new();
+
+        apply(&fake, &history, MoveAction::Resize { amount: 40.0 });
+        let resized = frame_of(&fake);
+        apply(&fake, &history, MoveAction::Undo);
+        assert_eq!(frame_of(&fake), start);
+
+        // The undo isn't a move of its own, so it is what redo brings back
+        assert_eq!(history.redo(&fake), Ok(true));
+        assert_eq!(frame_of(&fake), resized);
+        assert_eq!(history.redo(&fake), Ok(false));
+    }
+
+    #[test]
+    fn actions_deserialize_from_tagged_json() {
+        let nudge: MoveAction = serde_json::from_str(r#"{"kind":"nudge","dx":-10,"dy":0}"#).unwrap();
+        assert_eq!(nudge, MoveAction::Nudge { dx: -10.0, dy: 0.0 });
+        let next: MoveAction = serde_json::from_str(r#"{"kind":"nextDisplay"}"#).unwrap();
+        assert_eq!(next, MoveAction::NextDisplay);
+    }
+}
@@ -0,0 +1,608 @@
+//! Per-app window rules ("Slack goes to the right third of display 2", "never
+//! tile Finder dialogs"), applied when a matching window appears.
+//!
+//! Rules are stored in the database. A watcher diffs the open windows on every
+//! tick and evaluates the rules against the ones it hasn't seen before, so a
+//! window the user moves afterwards is left alone. `NoTile` rules are the
+//! exception: tiling checks them on every retile, for every open window.
+
+use crate::error::AppError;
+use crate::layout_history::LayoutHistory;
+use crate::layout_manager::{display_for, displays_left_to_right, get_open_windows, IgnoredApps, WindowInfo, WindowRect};
+use crate::settings::open_db;
+use crate::snap::{SnapConfig, SnapSettings, SnapTarget};
+use crate::window_backend::{DisplayInfo, SharedBackend, WindowBackend, WindowError};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+// How often the watcher looks for new windows
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Which windows a rule applies to. Every field that is set must match.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleMatch {
+    /// App name or bundle id, case-insensitive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub app: Option<String>,
+    /// Regular expression searched for in the title, case-insensitive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Window role, e.g. "dialog": the AX subrole on macOS, where the "AX"
+    /// prefix is optional, or the EWMH window type on X11.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub role: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum RuleAction {
+    /// Snap to `target` (any name `SnapTarget` parses) on the `display`-th
+    /// display from the left, counting from 1, or on the display it opened on
+    Snap {
+        target: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        display: Option<usize>,
+    },
+    Minimize,
+    /// Keep the window out of auto-tiling
+    NoTile,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowRule {
+    /// Generated on save when empty
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+    #[serde(rename = "match")]
+    pub matcher: RuleMatch,
+    pub action: RuleAction,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+/// A rule with its title pattern and snap target parsed.
+#[derive(Debug, Clone)]
+pub struct CompiledRule {
+    rule: WindowRule,
+    title: Option<Regex>,
+    target: Option<SnapTarget>,
+}
+
+/// "AXDialog" and "dialog" are the same role, and a macOS standard window is
+/// an X11 "normal" one.
+fn normalize_role(role: &str) -> String {
+    let role = role.trim();
+    let role = role.strip_prefix("AX").unwrap_or(role).to_ascii_lowercase();
+    if role == "standardwindow" {
+        "normal".into()
+    } else {
+        role
+    }
+}
+
+impl CompiledRule {
+    pub fn new(rule: WindowRule) -> Result<Self, String> {
+        let matcher = &rule.matcher;
+        if matcher.app.is_none() && matcher.title.is_none() && matcher.role.is_none() {
+            return Err("A rule needs an app, title or role to match".into());
+        }
+        let title = matcher
+            .title
+            .as_deref()
+            .map(|pattern| {
+                RegexBuilder::new(pattern)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|e| format!("Invalid title pattern: {}", e))
+            })
+            .transpose()?;
+        let target = match &rule.action {
+            RuleAction::Snap { target, display } => {
+                if *display == Some(0) {
+                    return Err("Displays are counted from 1".into());
+                }
+                Some(target.parse::<SnapTarget>()?)
+            }
+            _ => None,
+        };
+        Ok(Self { rule, title, target })
+    }
+
+    pub fn matches(&self, window: &WindowInfo) -> bool {
+        let matcher = &self.rule.matcher;
+        let app_matches = matcher.app.as_deref().is_none_or(|app| {
+            let app = app.trim();
+            window.app_name.eq_ignore_ascii_case(app)
+                || window.bundle_id.as_deref().is_some_and(|id| id.eq_ignore_ascii_case(app))
+        });
+        let title_matches = self.title.as_ref().is_none_or(|re| re.is_match(&window.title));
+        let role_matches = matcher.role.as_deref().is_none_or(|role| {
+            window
+                .role
+                .as_deref()
+                .is_some_and(|actual| normalize_role(actual) == normalize_role(role))
+        });
+        app_matches && title_matches && role_matches
+    }
+
+    /// Where a snap rule puts `window`. `None` for other actions, or when the
+    /// display it names isn't connected.
+    fn frame_for(&self, window: &WindowInfo, displays: &[DisplayInfo], settings: &SnapSettings) -> Option<WindowRect> {
+        let target = self.target?;
+        let display = match &self.rule.action {
+            RuleAction::Snap { display: Some(n), .. } => *displays_left_to_right(displays).get(n - 1)?,
+            _ => display_for(displays, &window.frame)?,
+        };
+        Some(target.cell().frame_in(&display.work_area, settings))
+    }
+}
+
+/// A rule that applies to a window, and the frame it would give it.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleFiring {
+    pub rule_id: String,
+    pub rule_name: String,
+    pub window: WindowInfo,
+    pub action: RuleAction,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frame: Option<WindowRect>,
+}
+
+/// Every enabled rule that applies to each of `windows`, in rule order. Snap
+/// rules naming a display that isn't connected don't fire.
+pub fn evaluate(
+    rules: &[CompiledRule],
+    windows: &[WindowInfo],
+    displays: &[DisplayInfo],
+    settings: &SnapSettings,
+) -> Vec<RuleFiring> {
+    let mut firings = Vec::new();
+    for window in windows {
+        for rule in rules.iter().filter(|r| r.rule.enabled && r.matches(window)) {
+            let frame = rule.frame_for(window, displays, settings);
+            if rule.target.is_some() && frame.is_none() {
+                continue;
+            }
+            firings.push(RuleFiring {
+                rule_id: rule.rule.id.clone(),
+                rule_name: rule.rule.name.clone(),
+                window: window.clone(),
+                action: rule.rule.action.clone(),
+                frame,
+            });
+        }
+    }
+    firings
+}
+
+/// Moves or minimizes the window. `NoTile` is enforced by tiling itself.
+pub fn apply_firing(backend: &dyn WindowBackend, firing: &RuleFiring) -> Result<(), WindowError> {
+    match (&firing.action, &firing.frame) {
+        (RuleAction::Snap { .. }, Some(frame)) => backend.set_frame(&firing.window, frame),
+        (RuleAction::Minimize, _) => backend.minimize(&firing.window),
+        _ => Ok(()),
+    }
+}
+
+/// `windows` with the role and bundle id only `list_all_windows` reports.
+pub fn with_details(backend: &dyn WindowBackend, mut windows: Vec<WindowInfo>) -> Vec<WindowInfo> {
+    if windows.is_empty() {
+        return windows;
+    }
+    let detailed = backend.list_all_windows();
+    for window in &mut windows {
+        if let Some(details) = detailed.iter().find(|d| d.id == window.id) {
+            window.role = window.role.take().or_else(|| details.role.clone());
+            window.bundle_id = window.bundle_id.take().or_else(|| details.bundle_id.clone());
+        }
+    }
+    windows
+}
+
+/// Reports open windows it hasn't seen before. A window stays seen until it
+/// no longer exists, so one coming back from another Space or desktop, or
+/// from being minimized, isn't new. The first observation only records what
+/// is already there.
+#[derive(Debug, Default)]
+pub struct NewWindowWatch {
+    seen: Option<HashSet<u32>>,
+}
+
+impl NewWindowWatch {
+    /// `open` are the windows rules may apply to, `existing` the ids of every
+    /// window, hidden ones included.
+    pub fn observe(&mut self, open: Vec<WindowInfo>, existing: &HashSet<u32>) -> Vec<WindowInfo> {
+        let Some(seen) = &mut self.seen else {
+            self.seen = Some(existing.iter().copied().chain(open.iter().map(|w| w.id)).collect());
+            return Vec::new();
+        };
+        seen.retain(|id| existing.contains(id));
+        let new: Vec<WindowInfo> = open.into_iter().filter(|w| !seen.contains(&w.id)).collect();
+        seen.extend(new.iter().map(|w| w.id));
+        new
+    }
+
+    pub fn reset(&mut self) {
+        self.seen = None;
+    }
+}
+
+#[derive(Default)]
+pub struct WindowRules {
+    rules: Mutex<Vec<CompiledRule>>,
+    watch: Mutex<NewWindowWatch>,
+}
+
+impl WindowRules {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the rules. Windows already open are not moved or minimized.
+    pub fn set(&self, rules: Vec<CompiledRule>) {
+        *self.rules.lock().unwrap() = rules;
+        self.watch.lock().unwrap().reset();
+    }
+
+    pub fn rules(&self) -> Vec<CompiledRule> {
+        self.rules.lock().unwrap().clone()
+    }
+
+    /// The enabled `NoTile` rules, which apply to every open window.
+    pub fn no_tile_rules(&self) -> Vec<CompiledRule> {
+        self.rules
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|r| r.rule.enabled && r.rule.action == RuleAction::NoTile)
+            .cloned()
+            .collect()
+    }
+
+    /// The rules that fire for windows opened since the last check.
+    pub fn check(
+        &self,
+        backend: &dyn WindowBackend,
+        settings: &SnapSettings,
+        ignored: &[String],
+        my_pid: i32,
+    ) -> Vec<RuleFiring> {
+        let rules = self.rules();
+        if rules.is_empty() {
+            return Vec::new();
+        }
+        let existing: HashSet<u32> = backend.list_all_windows().iter().map(|w| w.id).collect();
+        let new = self
+            .watch
+            .lock()
+            .unwrap()
+            .observe(get_open_windows(backend, ignored, my_pid), &existing);
+        let new = with_details(backend, new);
+        evaluate(&rules, &new, &backend.displays(), settings)
+    }
+}
+
+fn rule_from_row((data,): (String,)) -> Result<CompiledRule, String> {
+    let rule: WindowRule = serde_json::from_str(&data).map_err(|e| e.to_string())?;
+    CompiledRule::new(rule)
+}
+
+async fn load_rules(app: &AppHandle) -> Result<Vec<CompiledRule>, String> {
+    let pool = open_db(app).await?;
+    let rows: Vec<(String,)> = sqlx::query_as("SELECT data FROM window_rules ORDER BY created_at")
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    pool.close().await;
+
+    // A row that no longer compiles is skipped rather than disabling every rule
+    Ok(rows.into_iter().filter_map(|row| rule_from_row(row).ok()).collect())
+}
+
+async fn reload(app: &AppHandle) -> Result<(), String> {
+    let rules = load_rules(app).await?;
+    app.state::<WindowRules>().set(rules);
+    Ok(())
+}
+
+/// Loads the saved rules, then applies them to every window that opens.
+/// Emits `window-rules-applied` with the rules that fired.
+pub fn spawn_watcher(app: AppHandle) {
+    std::thread::spawn(move || {
+        if let Err(e) = tauri::async_runtime::block_on(reload(&app)) {
+            eprintln!("Failed to load window rules: {}", e);
+        }
+
+        let my_pid = std::process::id() as i32;
+        loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let backend = app.state::<SharedBackend>();
+            let settings = app.state::<SnapConfig>().get();
+            let ignored = app.state::<IgnoredApps>().get();
+            let firings = app.state::<WindowRules>().check(backend.as_ref(), &settings, &ignored, my_pid);
+            if firings.is_empty() {
+                continue;
+            }
+
+            app.state::<LayoutHistory>().record(backend.as_ref(), |backend| {
+                for firing in &firings {
+                    if let Err(e) = apply_firing(backend, firing) {
+                        eprintln!("Window rule \"{}\" failed: {}", firing.rule_name, e);
+                    }
+                }
+            });
+            let _ = app.emit("window-rules-applied", &firings);
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn list_window_rules(app: AppHandle) -> Result<Vec<WindowRule>, AppError> {
+    Ok(load_rules(&app).await?.into_iter().map(|r| r.rule).collect())
+}
+
+/// Creates or replaces a rule; a new id is generated when it has none.
+#[tauri::command]
+pub async fn save_window_rule(app: AppHandle, mut rule: WindowRule) -> Result<WindowRule, AppError> {
+    if rule.id.is_empty() {
+        rule.id = uuid::Uuid::new_v4().to_string();
+    }
+    rule.name = rule.name.trim().to_string();
+    if rule.name.is_empty() {
+        return Err(AppError::InvalidInput("Rule name cannot be empty".into()));
+    }
+    let rule = CompiledRule::new(rule).map_err(AppError::InvalidInput)?.rule;
+
+    let data = serde_json::to_string(&rule).map_err(|e| e.to_string())?;
+    let now = chrono::Utc::now().to_rfc3339();
+
+    let pool = open_db(&app).await?;
+    sqlx::query(
+        "INSERT INTO window_rules (id, name, data, created_at, updated_at) VALUES (?, ?, ?, ?, ?)
+         ON CONFLICT(id) DO UPDATE SET name = excluded.name, data = excluded.data, updated_at = excluded.updated_at",
+    )
+    .bind(&rule.id)
+    .bind(&rule.name)
+    .bind(&data)
+    .bind(&now)
+    .bind(&now)
+    .execute(&pool)
+    .await
+    .map_err(|e| e.to_string())?;
+    pool.close().await;
+
+    reload(&app).await?;
+    Ok(rule)
+}
+
+#[tauri::command]
+pub async fn delete_window_rule(app: AppHandle, id: String) -> Result<(), AppError> {
+    let pool = open_db(&app).await?;
+    sqlx::query("DELETE FROM window_rules WHERE id = ?")
+        .bind(&id)
+        .execute(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    pool.close().await;
+
+    reload(&app).await.map_err(AppError::from)
+}
+
+/// Dry run: which rules would fire for the windows open now, and where they
+/// would put them. Evaluates `rules` when given (e.g. a rule being edited),
+/// otherwise the saved ones. Nothing is moved.
+#[tauri::command]
+pub fn preview_window_rules(
+    backend: tauri::State<'_, SharedBackend>,
+    config: tauri::State<'_, SnapConfig>,
+    saved: tauri::State<'_, WindowRules>,
+    ignored: tauri::State<'_, IgnoredApps>,
+    rules: Option<Vec<WindowRule>>,
+) -> Result<Vec<RuleFiring>, AppError> {
+    let rules = match rules {
+        Some(rules) => rules
+            .into_iter()
+            .map(CompiledRule::new)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(AppError::InvalidInput)?,
+        None => saved.rules(),
+    };
+    let my_pid = std::process::id() as i32;
+    let windows = with_details(backend.as_ref(), get_open_windows(backend.as_ref(), &ignored.get(), my_pid));
+    Ok(evaluate(&rules, &windows, &backend.displays(), &config.get()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_backend::fake::{display, rect, window, FakeBackend};
+    use crate::window_backend::frames_match;
+
+    const MY_PID: i32 = 1;
+
+    fn rule(matcher: RuleMatch, action: RuleAction) -> CompiledRule {
+        CompiledRule::new(WindowRule {
+            id: "r".into(),
+            name: "Rule".into(),
+            enabled: true,
+            matcher,
+            action,
+        })
+        .unwrap()
+    }
+
+    fn app(name: &str) -> RuleMatch {
+        RuleMatch {
+            app: Some(name.into()),
+            ..Default::default()
+        }
+    }
+
+    fn snap(target: &str, display: Option<usize>) -> RuleAction {
+        RuleAction::Snap {
+            target: target.into(),
+            display,
+        }
+    }
+
+    #[test]
+    fn matches_app_title_and_role() {
+        let mut dialog = window(10, 100, "Finder", "Copy (3 items)", rect(0.0, 0.0, 400.0, 200.0));
+        dialog.role = Some("AXDialog".into());
+        dialog.bundle_id = Some("com.apple.finder".into());
+
+        assert!(rule(app("finder"), RuleAction::NoTile).matches(&dialog));
+        assert!(rule(app("com.apple.Finder"), RuleAction::NoTile).matches(&dialog));
+        assert!(!rule(app("Safari"), RuleAction::NoTile).matches(&dialog));
+
+        let finder_dialogs = RuleMatch {
+            app: Some("Finder".into()),
+            role: Some("dialog".into()),
+            ..Default::default()
+        };
+        assert!(rule(finder_dialogs.clone(), RuleAction::NoTile).matches(&dialog));
+        dialog.role = Some("AXStandardWindow".into());
+        assert!(!rule(finder_dialogs, RuleAction::NoTile).matches(&dialog));
+
+        // X11 window types match their macOS counterparts
+        let normal = RuleMatch {
+            role: Some("normal".into()),
+            ..Default::default()
+        };
+        assert!(rule(normal, RuleAction::NoTile).matches(&dialog));
+        let mut x11_dialog = window(11, 200, "nautilus", "Copying", rect(0.0, 0.0, 400.0, 200.0));
+        x11_dialog.role = Some("dialog".into());
+        let dialogs = RuleMatch {
+            role: Some("AXDialog".into()),
+            ..Default::default()
+        };
+        assert!(rule(dialogs, RuleAction::NoTile).matches(&x11_dialog));
+
+        let copying = RuleMatch {
+            title: Some(r"^copy \(\d+ items\)$".into()),
+            ..Default::default()
+        };
+        assert!(rule(copying, RuleAction::Minimize).matches(&dialog));
+    }
+
+    #[test]
+    fn invalid_rules_are_rejected() {
+        let compile = |matcher: RuleMatch, action: RuleAction| {
+            CompiledRule::new(WindowRule {
+                id: String::new(),
+                name: "Bad".into(),
+                enabled: true,
+                matcher,
+                action,
+            })
+        };
+        let bad_title = RuleMatch {
+            title: Some("(".into()),
+            ..Default::default()
+        };
+
+        assert!(compile(RuleMatch::default(), RuleAction::Minimize).is_err());
+        assert!(compile(bad_title, RuleAction::Minimize).is_err());
+        assert!(compile(app("Slack"), snap("sideways", None)).is_err());
+        assert!(compile(app("Slack"), snap("right-third", Some(0))).is_err());
+    }
+
+    #[test]
+    fn snap_rules_target_the_nth_display_from_the_left() {
+        let rules = [rule(app("Slack"), snap("right-third", Some(2)))];
+        let displays = [display(2, 1000.0, 0.0), display(1, 0.0, 0.0)];
+        let slack = window(10, 100, "Slack", "General", rect(0.0, 25.0, 500.0, 500.0));
+        let settings = SnapSettings::default();
+
+        let firings = evaluate(&rules, std::slice::from_ref(&slack), &displays, &settings);
+        let third = 1000.0 / 3.0;
+        assert_eq!(firings.len(), 1);
+        let frame = firings[0].frame.as_ref().unwrap();
+        assert!(frames_match(frame, &rect(1000.0 + third * 2.0, 25.0, third, 775.0)));
+
+        // Not fired while the second display is disconnected
+        assert!(evaluate(&rules, &[slack], &displays[1..], &settings).is_empty());
+    }
+
+    #[test]
+    fn disabled_rules_do_not_fire() {
+        let mut minimize = rule(app("Slack"), RuleAction::Minimize);
+        minimize.rule.enabled = false;
+        let slack = window(10, 100, "Slack", "General", rect(0.0, 0.0, 500.0, 500.0));
+
+        assert!(evaluate(&[minimize], &[slack], &[display(1, 0.0, 0.0)], &SnapSettings::default()).is_empty());
+    }
+
+    #[test]
+    fn only_windows_opened_since_the_last_check_are_handled() {
+        let fake = FakeBackend::new(
+            vec![window(10, 100, "Slack", "General", rect(0.0, 25.0, 500.0, 500.0))],
+            vec![display(1, 0.0, 0.0)],
+        );
+        let rules = WindowRules::new();
+        rules.set(vec![rule(app("Slack"), snap("left", None))]);
+        let settings = SnapSettings::default();
+
+        // Already open when the rules were set
+        assert!(rules.check(&fake, &settings, &[], MY_PID).is_empty());
+
+        fake.windows
+            .lock()
+            .unwrap()
+            .push(window(11, 100, "Slack", "Huddle", rect(300.0, 300.0, 400.0, 300.0)));
+        let firings = rules.check(&fake, &settings, &[], MY_PID);
+        assert_eq!(firings.len(), 1);
+        assert_eq!(firings[0].window.id, 11);
+
+        apply_firing(&fake, &firings[0]).unwrap();
+        assert_eq!(fake.windows.lock().unwrap()[1].frame, rect(0.0, 25.0, 500.0, 775.0));
+        assert!(rules.check(&fake, &settings, &[], MY_PID).is_empty());
+    }
+
+    #[test]
+    fn windows_coming_back_into_view_are_not_new() {
+        let fake = FakeBackend::new(
+            vec![window(10, 100, "Slack", "General", rect(0.0, 25.0, 500.0, 500.0))],
+            vec![display(1, 0.0, 0.0)],
+        );
+        let rules = WindowRules::new();
+        rules.set(vec![rule(app("Slack"), snap("left", None))]);
+        let settings = SnapSettings::default();
+        assert!(rules.check(&fake, &settings, &[], MY_PID).is_empty());
+
+        // Off to another Space and back
+        fake.windows.lock().unwrap()[0].state.on_screen = false;
+        assert!(rules.check(&fake, &settings, &[], MY_PID).is_empty());
+        fake.windows.lock().unwrap()[0].state.on_screen = true;
+        assert!(rules.check(&fake, &settings, &[], MY_PID).is_empty());
+
+        // Closed, then opened again
+        let closed = fake.windows.lock().unwrap().remove(0);
+        assert!(rules.check(&fake, &settings, &[], MY_PID).is_empty());
+        fake.windows.lock().unwrap().push(closed);
+        assert_eq!(rules.check(&fake, &settings, &[], MY_PID).len(), 1);
+    }
+
+    #[test]
+    fn rules_round_trip_through_json() {
+        let json = r#"{"name":"Slack","match":{"app":"Slack"},"action":{"kind":"snap","target":"right-third","display":2}}"#;
+        let rule: WindowRule = serde_json::from_str(json).unwrap();
+        assert!(rule.enabled);
+        assert_eq!(rule.action, snap("right-third", Some(2)));
+
+        let back: WindowRule = serde_json::from_str(&serde_json::to_string(&rule).unwrap()).unwrap();
+        assert_eq!(back, rule);
+    }
+}
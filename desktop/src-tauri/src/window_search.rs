@@ -0,0 +1,162 @@
+//! Fuzzy window search for the drawer's window switcher.
+//!
+//! Each whitespace-separated query term has to appear, in order but not
+//! necessarily contiguously, in the app name or the title. Matches at word
+//! starts and runs of consecutive characters score higher, gaps score lower.
+
+use crate::layout_manager::WindowInfo;
+use serde::Serialize;
+
+const MATCH_SCORE: i32 = 16;
+const CONSECUTIVE_BONUS: i32 = 15;
+const WORD_START_BONUS: i32 = 10;
+const GAP_PENALTY: i32 = 1;
+// Typing the app name is the usual way to pick a window
+const APP_BONUS: i32 = 4;
+
+#[derive(Debug, Serialize, Clone)]
+pub struct WindowMatch {
+    pub window: WindowInfo,
+    pub score: i32,
+}
+
+/// Best score for `query` as a case-insensitive subsequence of `text`, or
+/// `None` if it isn't one. An empty query matches everything with 0.
+pub fn fuzzy_score(query: &str, text: &str) -> Option<i32> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    if query.is_empty() {
+        return Some(0);
+    }
+    let original: Vec<char> = text.chars().collect();
+    let text: Vec<char> = original.iter().map(|c| c.to_lowercase().next().unwrap_or(*c)).collect();
+    if query.len() > text.len() {
+        return None;
+    }
+
+    let word_start = |j: usize| {
+        j == 0
+            || !original[j - 1].is_alphanumeric()
+            || (original[j - 1].is_lowercase() && original[j].is_uppercase())
+    };
+    let char_score = |j: usize| MATCH_SCORE + if word_start(j) { WORD_START_BONUS } else { 0 };
+
+    // best[j]: best score with the current query char matched at text[j]
+    let mut best: Vec<Option<i32>> = (0..text.len())
+        .map(|j| (text[j] == query[0]).then(|| char_score(j) - GAP_PENALTY * j as i32))
+        .collect();
+
+    for &wanted in &query[1..] {
+        let mut next = vec![None; text.len()];
+        // Best previous match at least one char before j - 1, gap already paid
+        let mut gapped: Option<i32> = None;
+        for j in 1..text.len() {
+            if j >= 2 {
+                let candidate = best[j - 2].map(|s| s - GAP_PENALTY);
+                gapped = gapped.map(|g| g - GAP_PENALTY).max(candidate);
+            }
+            if text[j] != wanted {
+                continue;
+            }
+            let consecutive = best[j - 1].map(|s| s + CONSECUTIVE_BONUS);
+            next[j] = consecutive.max(gapped).map(|s| s + char_score(j));
+        }
+        best = next;
+    }
+
+    best.into_iter().flatten().max()
+}
+
+/// How well `window` matches every term of `query`, or `None` if a term is in
+/// neither its app name nor its title.
+pub fn window_score(query: &str, window: &WindowInfo) -> Option<i32> {
+    query.split_whitespace().try_fold(0, |total, term| {
+        let app = fuzzy_score(term, &window.app_name).map(|s| s + APP_BONUS);
+        let title = fuzzy_score(term, &window.title);
+        Some(total + app.max(title)?)
+    })
+}
+
+/// Windows matching `query`, best first. Ties keep the given (front-most
+/// first) order, so an empty query lists windows in stacking order.
+pub fn search(windows: Vec<WindowInfo>, query: &str) -> Vec<WindowMatch> {
+    let mut matches: Vec<WindowMatch> = windows
+        .into_iter()
+        .filter_map(|window| {
+            let score = window_score(query, &window)?;
+            Some(WindowMatch { window, score })
+        })
+        .collect();
+    matches.sort_by_key(|m| std::cmp::Reverse(m.score));
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::window_backend::fake::{rect, window};
+
+    fn titles(matches: &[WindowMatch]) -> Vec<&str> {
+        matches.iter().map(|m| m.window.title.as_str()).collect()
+    }
+
+    #[test]
+    fn fuzzy_score_needs_every_char_in_order() {
+        assert!(fuzzy_score("sfr", "Safari").is_some());
+        assert!(fuzzy_score("SAF", "safari").is_some());
+        assert_eq!(fuzzy_score("rfs", "Safari"), None);
+        assert_eq!(fuzzy_score("safarii", "Safari"), None);
+        assert_eq!(fuzzy_score("", "Safari"), Some(0));
+    }
+
+    #[test]
+    fn fuzzy_score_prefers_word_starts_and_runs() {
+        let prefix = fuzzy_score("vis", "Visual Studio Code").unwrap();
+        let inner = fuzzy_score("vis", "Previsualise").unwrap();
+        assert!(prefix > inner);
+
+        let initials = fuzzy_score("vsc", "Visual Studio Code").unwrap();
+        let scattered = fuzzy_score("vsc", "Vivisection").unwrap();
+        assert!(initials > scattered);
+
+        let camel = fuzzy_score("gc", "GitCommit").unwrap();
+        let plain = fuzzy_score("gc", "Gitcommit").unwrap();
+        assert!(camel > plain);
+    }
+
+    #[test]
+    fn fuzzy_score_finds_the_best_alignment() {
+        // Greedy matching would take the first "a" and break up the run
+        let aligned = fuzzy_score("abc", "a xx abc").unwrap();
+        let run = fuzzy_score("abc", "abc").unwrap();
+        assert_eq!(aligned, run - 5 * GAP_PENALTY);
+    }
+
+    #[test]
+    fn terms_can_match_app_or_title() {
+        let windows = vec![
+            window(1, 10, "Safari", "Rust docs", rect(0.0, 0.0, 500.0, 500.0)),
+            window(2, 20, "Terminal", "cargo test", rect(0.0, 0.0, 500.0, 500.0)),
+            window(3, 10, "Safari", "News", rect(0.0, 0.0, 500.0, 500.0)),
+        ];
+
+        assert_eq!(titles(&search(windows.clone(), "saf docs")), vec!["Rust docs"]);
+        assert_eq!(titles(&search(windows.clone(), "term")), vec!["cargo test"]);
+        assert!(search(windows, "safari cargo").is_empty());
+    }
+
+    #[test]
+    fn results_rank_by_score_then_stacking_order() {
+        let windows = vec![
+            window(1, 10, "Notes", "Shopping list", rect(0.0, 0.0, 500.0, 500.0)),
+            window(2, 20, "Slack", "general", rect(0.0, 0.0, 500.0, 500.0)),
+            window(3, 30, "Safari", "Home", rect(0.0, 0.0, 500.0, 500.0)),
+        ];
+
+        let all = search(windows.clone(), "  ");
+        assert_eq!(titles(&all), vec!["Shopping list", "general", "Home"]);
+
+        // App name prefix beats a match inside a title
+        let ranked = search(windows, "sl");
+        assert_eq!(titles(&ranked), vec!["general", "Shopping list"]);
+    }
+}
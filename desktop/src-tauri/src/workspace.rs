@@ -0,0 +1,184 @@
+//! Workspaces: named snapshots of the external windows, the web blanket tabs
+//! and the drawer, saved in the `workspaces` table and restored together.
+
+use crate::drawer::{DrawerEdge, DrawerManager};
+use crate::error::AppError;
+use crate::layout_manager::{capture_windows, IgnoredApps, RestoreOptions, RestoreResult, WindowInfo};
+use crate::permissions::AccessibilityPermission;
+use crate::settings::open_db;
+use crate::web_blanket::{WebBlanketState, WebSession};
+use crate::window_backend::SharedBackend;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, WebviewWindow};
+
+/// Where the drawer sits and what it shows.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DrawerState {
+    /// Edge config, as in `set_drawer_config`
+    pub edge: String,
+    /// Side it last slid in from
+    pub side: u8,
+    pub open: bool,
+    pub active_view: String,
+}
+
+/// A whole working context: external windows, web blanket tabs and the drawer.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Workspace {
+    pub id: String,
+    pub name: String,
+    pub windows: Vec<WindowInfo>,
+    #[serde(default)]
+    pub web: WebSession,
+    pub drawer: DrawerState,
+    pub created_at: String,
+}
+
+/// Sent to the drawer so it can restore what only the frontend owns: the
+/// drawer position setting, the active view and the web blanket tabs.
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct WorkspaceRestorePayload {
+    drawer_id: String,
+    drawer: DrawerState,
+    web: WebSession,
+}
+
+async fn load(app: &AppHandle, id: &str) -> Result<Workspace, AppError> {
+    let pool = open_db(app).await?;
+    let row: Option<(String,)> = sqlx::query_as("SELECT data FROM workspaces WHERE id = ?")
+        .bind(id)
+        .fetch_optional(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    pool.close().await;
+
+    let (data,) = row.ok_or_else(|| AppError::WorkspaceNotFound {
+        workspace_id: id.to_string(),
+    })?;
+    serde_json::from_str(&data).map_err(|e| AppError::Other(e.to_string()))
+}
+
+#[tauri::command]
+pub async fn capture_workspace(
+    app: AppHandle,
+    window: WebviewWindow,
+    backend: tauri::State<'_, SharedBackend>,
+    blanket: tauri::State<'_, WebBlanketState>,
+    drawers: tauri::State<'_, DrawerManager>,
+    ignored: tauri::State<'_, IgnoredApps>,
+    name: String,
+    active_view: String,
+) -> Result<Workspace, AppError> {
+    let name = name.trim().to_string();
+    if name.is_empty() {
+        return Err(AppError::InvalidInput("Workspace name is empty".into()));
+    }
+    let drawer = drawers.drawer(window.label())?;
+
+    let workspace = Workspace {
+        id: uuid::Uuid::new_v4().to_string(),
+        name,
+        windows: capture_windows(backend.as_ref(), &ignored.get(), std::process::id() as i32),
+        web: blanket.session()?,
+        drawer: DrawerState {
+            edge: drawer.edge().as_config().to_string(),
+            side: drawer.last_side(),
+            open: drawer.is_open(),
+            active_view,
+        },
+        created_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    let data = serde_json::to_string(&workspace).map_err(|e| e.to_string())?;
+    let pool = open_db(&app).await?;
+    sqlx::query("INSERT INTO workspaces (id, name, data, created_at, updated_at) VALUES (?, ?, ?, ?, ?)")
+        .bind(&workspace.id)
+        .bind(&workspace.name)
+        .bind(&data)
+        .bind(&workspace.created_at)
+        .bind(&workspace.created_at)
+        .execute(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    pool.close().await;
+
+    Ok(workspace)
+}
+
+#[tauri::command]
+pub async fn list_workspaces(app: AppHandle) -> Result<Vec<Workspace>, AppError> {
+    let pool = open_db(&app).await?;
+    let rows: Vec<(String,)> = sqlx::query_as("SELECT data FROM workspaces ORDER BY created_at DESC")
+        .fetch_all(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    pool.close().await;
+
+    rows.into_iter()
+        .map(|(data,)| serde_json::from_str(&data).map_err(|e| AppError::Other(e.to_string())))
+        .collect()
+}
+
+#[tauri::command]
+pub async fn delete_workspace(app: AppHandle, id: String) -> Result<(), AppError> {
+    let pool = open_db(&app).await?;
+    sqlx::query("DELETE FROM workspaces WHERE id = ?")
+        .bind(&id)
+        .execute(&pool)
+        .await
+        .map_err(|e| e.to_string())?;
+    pool.close().await;
+    Ok(())
+}
+
+/// Puts the drawer back, hands the frontend its part, then moves the windows
+/// (relaunching apps that are closed) and reports the result per window.
+#[tauri::command]
+pub async fn restore_workspace(
+    app: AppHandle,
+    window: WebviewWindow,
+    backend: tauri::State<'_, SharedBackend>,
+    drawers: tauri::State<'_, DrawerManager>,
+    permission: tauri::State<'_, AccessibilityPermission>,
+    id: String,
+) -> Result<Vec<RestoreResult>, AppError> {
+    permission.require()?;
+    let workspace = load(&app, &id).await?;
+    let drawer = drawers.drawer(window.label())?;
+
+    // Another drawer may have taken the edge since; the drawer then stays put
+    match drawers.set_edge(&drawer.id(), DrawerEdge::from_config(&workspace.drawer.edge)) {
+        Ok(()) => {
+            if let Err(e) = crate::drawer::save_specs(&app).await {
+                eprintln!("Failed to save drawer edge: {}", e);
+            }
+        }
+        Err(e) => eprintln!("Workspace drawer edge not restored: {}", e),
+    }
+    if workspace.drawer.open {
+        drawer.show(&app, workspace.drawer.side);
+    } else {
+        drawer.hide(&app);
+    }
+
+    let _ = app.emit(
+        "workspace-restore",
+        WorkspaceRestorePayload {
+            drawer_id: drawer.id(),
+            drawer: DrawerState {
+                edge: drawer.edge().as_config().to_string(),
+                ..workspace.drawer
+            },
+            web: workspace.web,
+        },
+    );
+
+    let options = RestoreOptions {
+        launch_missing: true,
+        ..Default::default()
+    };
+    crate::run_restore(app, backend.inner().clone(), workspace.windows, options).await
+}
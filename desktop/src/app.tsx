@@ -5,21 +5,39 @@ import { useSettingsStore } from "@/stores/settings-store";
 import { useUpdateStore } from "@/stores/update-store";
 import { UpdatePoster } from "@/components/shared/update-poster";
 import { useEffect } from "react";
-import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import { getCurrentWindow } from "@tauri-apps/api/window";
+import { useWebBlanketStore } from "@/stores/web-blanket-store";
+import { WorkspaceDrawerState, WorkspaceWebSession } from "@/stores/workspace-store";
 import "./app.css";
 
 function App() {
   const { theme } = useUIStore();
-  const { drawerPosition } = useSettingsStore();
   const { checkForUpdates } = useUpdateStore();
 
   useEffect(() => {
-    // Sync drawer config on startup
-    invoke('set_drawer_config', { config: drawerPosition });
-    
     // Check for updates
     checkForUpdates();
-  }, [drawerPosition]);
+  }, []);
+
+  useEffect(() => {
+    // The backend restores windows and the drawer; the rest lives here
+    const unlisten = listen<{ drawerId: string; drawer: WorkspaceDrawerState; web: WorkspaceWebSession }>(
+      "workspace-restore",
+      (event) => {
+        const { drawerId, drawer, web } = event.payload;
+        if (drawerId !== getCurrentWindow().label) return;
+
+        // The backend has already moved the drawer to this edge
+        useSettingsStore.setState({ drawerPosition: drawer.edge as ReturnType<typeof useSettingsStore.getState>["drawerPosition"] });
+        useUIStore.getState().setActiveView(drawer.activeView as ReturnType<typeof useUIStore.getState>["activeView"]);
+        useWebBlanketStore.getState().restoreSession(web.tabs, web.activeTabId);
+      }
+    );
+    return () => {
+      unlisten.then(fn => fn());
+    };
+  }, []);
   
   return (
     <>
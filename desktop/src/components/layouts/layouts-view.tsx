@@ -1,6 +1,9 @@
 import { useState, useEffect } from 'react';
-import { useLayoutsStore, WindowInfo } from '@/stores/layouts-store';
+import { useLayoutsStore, WindowInfo, describeWindowError } from '@/stores/layouts-store';
+import { useWorkspaceStore } from '@/stores/workspace-store';
+import { WindowSwitcher } from './window-switcher';
 import { invoke } from '@tauri-apps/api/core';
+import { toast } from 'sonner';
 import {
     Plus,
     Trash2,
@@ -13,18 +16,22 @@ import {
     Rows2,
     Grid2X2,
     LayoutPanelLeft,
-    Layers as LayersIcon
+    Layers as LayersIcon,
+    Undo2,
+    Redo2
 } from 'lucide-react'; import { motion, AnimatePresence } from 'framer-motion';
 
 export const LayoutsView = () => {
-    const { layouts, loadLayouts, saveLayout, deleteLayout } = useLayoutsStore();
+    const { layouts, loadLayouts, saveLayout, deleteLayout, setAutoApply } = useLayoutsStore();
     const [isCapturing, setIsCapturing] = useState(false);
     const [captureName, setCaptureName] = useState('');
     const [capturedWindows, setCapturedWindows] = useState<WindowInfo[]>([]);
     const [previewMode, setPreviewMode] = useState(false);
+    const { workspaces, loadWorkspaces, captureWorkspace, restoreWorkspace, deleteWorkspace } = useWorkspaceStore();
 
     useEffect(() => {
         loadLayouts();
+        loadWorkspaces();
     }, []);
 
     const handleCaptureStart = async () => {
@@ -49,9 +56,10 @@ export const LayoutsView = () => {
 
     const handleRestore = async (windows: WindowInfo[]) => {
         try {
-            await invoke('restore_layout', { windows });
+            await invoke('restore_layout', { windows, launchMissing: true });
         } catch (err) {
             console.error('Failed to restore layout:', err);
+            toast.error('Could not restore layout', { description: describeWindowError(err) });
         }
     };
 
@@ -60,6 +68,16 @@ export const LayoutsView = () => {
             await invoke('apply_preset_layout', { layout });
         } catch (err) {
             console.error('Failed to apply preset:', err);
+            toast.error('Could not apply layout', { description: describeWindowError(err) });
+        }
+    };
+
+    const handleHistory = async (command: 'undo_layout' | 'redo_layout') => {
+        try {
+            await invoke<boolean>(command);
+        } catch (err) {
+            console.error(`Failed to ${command.replace('_layout', '')} layout:`, err);
+            toast.error(`Could not ${command.replace('_layout', '')} layout`, { description: describeWindowError(err) });
         }
     };
 
@@ -68,13 +86,29 @@ export const LayoutsView = () => {
             <div className="flex justify-between items-center mb-4 shrink-0">
                 <h2 className="text-xl font-light text-foreground">Window Flow</h2>
                 {!previewMode && (
-                    <button
-                        onClick={handleCaptureStart}
-                        className="flex items-center gap-1.5 bg-primary text-primary-foreground px-3 py-1.5 rounded-full text-xs font-medium hover:opacity-90 transition-colors shadow-lg shadow-primary/20"
-                    >
-                        <Plus size={14} />
-                        <span>Capture</span>
-                    </button>
+                    <div className="flex items-center gap-1">
+                        <button
+                            onClick={() => handleHistory('undo_layout')}
+                            title="Undo last layout change"
+                            className="p-1.5 text-muted-foreground hover:text-foreground hover:bg-muted rounded-md transition-colors"
+                        >
+                            <Undo2 size={14} />
+                        </button>
+                        <button
+                            onClick={() => handleHistory('redo_layout')}
+                            title="Redo layout change"
+                            className="p-1.5 text-muted-foreground hover:text-foreground hover:bg-muted rounded-md transition-colors"
+                        >
+                            <Redo2 size={14} />
+                        </button>
+                        <button
+                            onClick={handleCaptureStart}
+                            className="flex items-center gap-1.5 bg-primary text-primary-foreground px-3 py-1.5 rounded-full text-xs font-medium hover:opacity-90 transition-colors shadow-lg shadow-primary/20"
+                        >
+                            <Plus size={14} />
+                            <span>Capture</span>
+                        </button>
+                    </div>
                 )}
             </div>
 
@@ -133,6 +167,8 @@ export const LayoutsView = () => {
                         </motion.div>
                     ) : (
                         <div className="flex flex-col gap-6">
+                            <WindowSwitcher />
+
                             {/* Multi-Window Presets */}
                             <div>
                                 <h3 className="text-[10px] font-bold uppercase tracking-widest text-muted-foreground ml-1 mb-2">Workspace Layouts</h3>
@@ -194,6 +230,36 @@ export const LayoutsView = () => {
                                 </div>
                             </div>
 
+                            <div className="grid grid-cols-1 gap-3">
+                                <div className="flex justify-between items-center">
+                                    <h3 className="text-[10px] font-bold uppercase tracking-widest text-muted-foreground ml-1">Workspaces</h3>
+                                    <button
+                                        onClick={() => captureWorkspace(`Workspace ${new Date().toLocaleTimeString([], { hour: '2-digit', minute: '2-digit' })}`)}
+                                        title="Save windows, web tabs and drawer"
+                                        className="p-1 text-muted-foreground hover:text-foreground hover:bg-muted rounded-md transition-colors"
+                                    >
+                                        <Plus size={14} />
+                                    </button>
+                                </div>
+                                {workspaces.map((workspace) => (
+                                    <div
+                                        key={workspace.id}
+                                        className="bg-card px-4 py-3 rounded-md border border-border shadow-sm hover:shadow-md transition-all group flex items-center justify-between gap-3"
+                                    >
+                                        <button onClick={() => restoreWorkspace(workspace.id)} className="flex-1 text-left">
+                                            <h3 className="font-medium text-sm text-foreground">{workspace.name}</h3>
+                                            <p className="text-[10px] text-muted-foreground">{workspace.windows.length} windows • {workspace.web.tabs.length} tabs • {workspace.drawer.activeView}</p>
+                                        </button>
+                                        <button
+                                            onClick={() => deleteWorkspace(workspace.id)}
+                                            className="p-1.5 text-muted-foreground/50 hover:text-destructive hover:bg-destructive/10 rounded-md transition-colors opacity-0 group-hover:opacity-100"
+                                        >
+                                            <Trash2 size={14} />
+                                        </button>
+                                    </div>
+                                ))}
+                            </div>
+
                             <div className="grid grid-cols-1 gap-3">
                                 <h3 className="text-[10px] font-bold uppercase tracking-widest text-muted-foreground ml-1">Snapshots</h3>
                                 {layouts.map((layout) => (
@@ -211,12 +277,23 @@ export const LayoutsView = () => {
                                                 </div>
                                             </div>
 
-                                            <button
-                                                onClick={() => deleteLayout(layout.id)}
-                                                className="p-1.5 text-muted-foreground/50 hover:text-destructive hover:bg-destructive/10 rounded-md transition-colors opacity-0 group-hover:opacity-100"
-                                            >
-                                                <Trash2 size={14} />
-                                            </button>
+                                            <div className="flex items-center gap-1">
+                                                {layout.display_key && (
+                                                    <button
+                                                        onClick={() => setAutoApply(layout.id, !layout.auto_apply)}
+                                                        title="Apply automatically when this display setup is connected"
+                                                        className={`p-1.5 rounded-md transition-colors ${layout.auto_apply ? 'text-primary bg-primary/10' : 'text-muted-foreground/50 hover:bg-muted opacity-0 group-hover:opacity-100'}`}
+                                                    >
+                                                        <Monitor size={14} />
+                                                    </button>
+                                                )}
+                                                <button
+                                                    onClick={() => deleteLayout(layout.id)}
+                                                    className="p-1.5 text-muted-foreground/50 hover:text-destructive hover:bg-destructive/10 rounded-md transition-colors opacity-0 group-hover:opacity-100"
+                                                >
+                                                    <Trash2 size={14} />
+                                                </button>
+                                            </div>
                                         </div>
 
                                         <button
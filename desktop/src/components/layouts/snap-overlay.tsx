@@ -0,0 +1,43 @@
+import { useState, useEffect } from 'react';
+import { listen } from '@tauri-apps/api/event';
+import { WindowRect } from '@/stores/layouts-store';
+import '@/app.css';
+
+interface ZoneOverlay {
+    display: WindowRect;
+    zones: WindowRect[];
+    active: number | null;
+}
+
+// Drawn in the transparent overlay window while a window is dragged.
+// Zones are relative to the display the overlay covers.
+export const SnapOverlay = () => {
+    const [overlay, setOverlay] = useState<ZoneOverlay | null>(null);
+
+    useEffect(() => {
+        const unlisten = listen<ZoneOverlay>('snap-zones', (event) => setOverlay(event.payload));
+        return () => {
+            unlisten.then((fn) => fn());
+        };
+    }, []);
+
+    if (!overlay) return null;
+
+    const { display, zones, active } = overlay;
+    return (
+        <div className="relative w-full h-full">
+            {zones.map((zone, i) => (
+                <div
+                    key={i}
+                    className={`absolute rounded-lg border-2 transition-colors ${i === active ? 'bg-primary/30 border-primary' : 'bg-foreground/5 border-foreground/20'}`}
+                    style={{
+                        left: `${(zone.x / display.width) * 100}%`,
+                        top: `${(zone.y / display.height) * 100}%`,
+                        width: `${(zone.width / display.width) * 100}%`,
+                        height: `${(zone.height / display.height) * 100}%`,
+                    }}
+                />
+            ))}
+        </div>
+    );
+};
@@ -0,0 +1,78 @@
+import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { Search } from 'lucide-react';
+import { WindowInfo } from '@/stores/layouts-store';
+
+interface WindowMatch {
+    window: WindowInfo;
+    score: number;
+}
+
+export const WindowSwitcher = () => {
+    const [query, setQuery] = useState('');
+    const [matches, setMatches] = useState<WindowMatch[]>([]);
+    const [selected, setSelected] = useState(0);
+
+    useEffect(() => {
+        invoke<WindowMatch[]>('search_windows', { query })
+            .then((results) => {
+                setMatches(results);
+                setSelected(0);
+            })
+            .catch((err) => console.error('Failed to search windows:', err));
+    }, [query]);
+
+    const focus = async (id: number) => {
+        try {
+            await invoke('focus_window', { id });
+            setQuery('');
+        } catch (err) {
+            console.error('Failed to focus window:', err);
+        }
+    };
+
+    const handleKeyDown = (e: React.KeyboardEvent<HTMLInputElement>) => {
+        if (e.key === 'ArrowDown') {
+            e.preventDefault();
+            setSelected((i) => Math.min(i + 1, matches.length - 1));
+        } else if (e.key === 'ArrowUp') {
+            e.preventDefault();
+            setSelected((i) => Math.max(i - 1, 0));
+        } else if (e.key === 'Enter' && matches[selected]) {
+            focus(matches[selected].window.id);
+        }
+    };
+
+    return (
+        <div>
+            <div className="flex items-center gap-2 bg-input h-9 rounded-lg px-3 mb-2">
+                <Search size={14} className="text-muted-foreground" />
+                <input
+                    className="flex-1 bg-transparent text-sm text-foreground focus:outline-none"
+                    value={query}
+                    onChange={(e) => setQuery(e.target.value)}
+                    onKeyDown={handleKeyDown}
+                    placeholder="Switch to window..."
+                />
+            </div>
+            {query.trim() && (
+                <div className="bg-card rounded-md border border-border max-h-[200px] overflow-y-auto scrollbar-none">
+                    {matches.map(({ window }, i) => (
+                        <button
+                            key={window.id}
+                            onClick={() => focus(window.id)}
+                            onMouseEnter={() => setSelected(i)}
+                            className={`w-full flex items-center gap-2 px-3 py-1.5 text-left border-b border-border last:border-0 ${i === selected ? 'bg-muted' : ''}`}
+                        >
+                            <span className="text-[10px] font-bold text-foreground w-20 truncate">{window.app_name}</span>
+                            <span className="text-[10px] text-muted-foreground truncate flex-1">{window.title || 'Untitled'}</span>
+                        </button>
+                    ))}
+                    {matches.length === 0 && (
+                        <p className="text-[10px] text-muted-foreground px-3 py-2">No matching windows</p>
+                    )}
+                </div>
+            )}
+        </div>
+    );
+};
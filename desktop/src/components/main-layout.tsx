@@ -1,5 +1,6 @@
 import { useEffect, useRef, useState } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { errorMessage } from "@/lib/app-error";
 import { getCurrentWindow } from "@tauri-apps/api/window";
 import { useUIStore } from "@/stores/ui-store";
 import { useSettingsStore } from "@/stores/settings-store";
@@ -43,6 +44,12 @@ export const MainLayout = () => {
   const [isNavVisible, setIsNavVisible] = useState(true);
 
   useEffect(() => {
+    // Secondary drawers are opened with ?drawer=<id>&view=<view>
+    const initialView = new URLSearchParams(window.location.search).get("view");
+    if (initialView) {
+      setActiveView(initialView as typeof activeView);
+    }
+
     startMonitoring();
     useSettingsStore.getState().init();
     useShortcutsStore.getState().init();
@@ -110,7 +117,7 @@ export const MainLayout = () => {
     const appWindow = getCurrentWindow();
     const unlistenBlur = appWindow.listen("tauri://blur", () => {
       if (autoHideRef.current) {
-        invoke("hide_drawer");
+        invoke("hide_drawer", { drawerId: appWindow.label });
       }
     });
     return () => {
@@ -293,7 +300,7 @@ export const MainLayout = () => {
                       key={item.id}
                       onClick={() => {
                         if (activeView === "web" && item.id !== "web") {
-                          invoke("web_blanket_hide").catch(() => { });
+                          invoke("web_blanket_hide").catch((e) => console.warn("web_blanket_hide failed:", errorMessage(e)));
                         }
                         setActiveView(item.id as any);
                       }}
@@ -1,6 +1,7 @@
 import { useState, useEffect } from "react";
 import { motion, AnimatePresence } from "framer-motion";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { useSettingsStore } from "@/stores/settings-store";
 import { ArrowRight, Check, Shield, PanelLeft, PanelRight, MousePointer2 } from "lucide-react";
 import { clsx } from "clsx";
@@ -13,8 +14,8 @@ export const OnboardingView = () => {
 
   useEffect(() => {
     checkPermission();
-    // Check permission every second in case user grants it outside app
-    const interval = setInterval(checkPermission, 1000);
+    // The backend reports when the user grants it outside the app
+    const unlisten = listen<boolean>("accessibility-permission-changed", (event) => setHasPermission(event.payload));
     
     // Add focus listener to check immediately when app comes to foreground
     const handleFocus = () => {
@@ -23,7 +24,7 @@ export const OnboardingView = () => {
     window.addEventListener('focus', handleFocus);
 
     return () => {
-        clearInterval(interval);
+        unlisten.then((fn) => fn());
         window.removeEventListener('focus', handleFocus);
     };
   }, []);
@@ -1,10 +1,18 @@
 import { useSettingsStore } from '@/stores/settings-store';
 import { clsx } from 'clsx';
 import { EyeOff, PanelLeft, PanelRight, MousePointer2 } from 'lucide-react';
+import { toast } from 'sonner';
+import { errorMessage } from '@/lib/app-error';
 
 export const BehaviorSection = () => {
     const { autoHide, setAutoHide, drawerPosition, setDrawerPosition } = useSettingsStore();
 
+    const changePosition = (position: typeof drawerPosition) => {
+        setDrawerPosition(position).catch((e) => {
+            toast.error('Could not move the drawer', { description: errorMessage(e) });
+        });
+    };
+
     return (
         <div>
             <h2 className="text-xl font-light text-foreground mb-4">Behavior</h2>
@@ -57,7 +65,7 @@ export const BehaviorSection = () => {
 
                         <div className="grid grid-cols-2 gap-2">
                             <button
-                                onClick={() => setDrawerPosition('left')}
+                                onClick={() => changePosition('left')}
                                 className={clsx(
                                     "flex items-center justify-center gap-2 p-3 rounded-md border transition-all h-10",
                                     drawerPosition === 'left'
@@ -69,7 +77,7 @@ export const BehaviorSection = () => {
                                 <span className="text-[10px] font-medium uppercase tracking-wide">Left Edge</span>
                             </button>
                             <button
-                                onClick={() => setDrawerPosition('right')}
+                                onClick={() => changePosition('right')}
                                 className={clsx(
                                     "flex items-center justify-center gap-2 p-3 rounded-md border transition-all h-10",
                                     drawerPosition === 'right'
@@ -85,7 +93,7 @@ export const BehaviorSection = () => {
                         <div className="grid grid-cols-2 gap-2">
                             <div className="grid grid-cols-2 gap-2">
                                 <button
-                                    onClick={() => setDrawerPosition('top-left')}
+                                    onClick={() => changePosition('top-left')}
                                     className={clsx(
                                         "flex flex-col items-center justify-center gap-1 p-2 rounded-md border transition-all h-16",
                                         drawerPosition === 'top-left'
@@ -97,7 +105,7 @@ export const BehaviorSection = () => {
                                     <span className="text-[9px] font-medium uppercase tracking-wide">Top Left</span>
                                 </button>
                                 <button
-                                    onClick={() => setDrawerPosition('bottom-left')}
+                                    onClick={() => changePosition('bottom-left')}
                                     className={clsx(
                                         "flex flex-col items-center justify-center gap-1 p-2 rounded-md border transition-all h-16",
                                         drawerPosition === 'bottom-left'
@@ -111,7 +119,7 @@ export const BehaviorSection = () => {
                             </div>
                             <div className="grid grid-cols-2 gap-2">
                                 <button
-                                    onClick={() => setDrawerPosition('top-right')}
+                                    onClick={() => changePosition('top-right')}
                                     className={clsx(
                                         "flex flex-col items-center justify-center gap-1 p-2 rounded-md border transition-all h-16",
                                         drawerPosition === 'top-right'
@@ -123,7 +131,7 @@ export const BehaviorSection = () => {
                                     <span className="text-[9px] font-medium uppercase tracking-wide">Top Right</span>
                                 </button>
                                 <button
-                                    onClick={() => setDrawerPosition('bottom-right')}
+                                    onClick={() => changePosition('bottom-right')}
                                     className={clsx(
                                         "flex flex-col items-center justify-center gap-1 p-2 rounded-md border transition-all h-16",
                                         drawerPosition === 'bottom-right'
@@ -138,7 +146,7 @@ export const BehaviorSection = () => {
                         </div>
 
                         <button
-                            onClick={() => setDrawerPosition('hot-corners')}
+                            onClick={() => changePosition('hot-corners')}
                             className={clsx(
                                 "flex items-center justify-center gap-2 p-3 rounded-md border transition-all h-10 w-full",
                                 drawerPosition === 'hot-corners'
@@ -1,5 +1,6 @@
 import { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import { clsx } from 'clsx';
 import { Shield, Check } from 'lucide-react';
 
@@ -9,7 +10,8 @@ export const PermissionsSection = () => {
 
     useEffect(() => {
         checkPermission();
-        const interval = setInterval(checkPermission, 1000);
+        // The backend watches for the permission being granted or revoked
+        const unlisten = listen<boolean>('accessibility-permission-changed', (event) => setHasPermission(event.payload));
 
         // Add focus listener
         const handleFocus = () => {
@@ -18,7 +20,7 @@ export const PermissionsSection = () => {
         window.addEventListener('focus', handleFocus);
 
         return () => {
-            clearInterval(interval);
+            unlisten.then((fn) => fn());
             window.removeEventListener('focus', handleFocus);
         };
     }, []);
@@ -0,0 +1,86 @@
+import { useState, useEffect } from 'react';
+import { invoke } from '@tauri-apps/api/core';
+import { LayoutGrid } from 'lucide-react';
+import { toast } from 'sonner';
+import { errorMessage } from '@/lib/app-error';
+
+/** Spacing around snapped and tiled windows, see `SnapSettings` in `snap.rs`. */
+interface SnapSettings {
+    padding: number;
+    gap: number;
+}
+
+const FIELDS: { key: keyof SnapSettings; label: string }[] = [
+    { key: 'padding', label: 'Screen Padding' },
+    { key: 'gap', label: 'Window Gap' },
+];
+
+export const WindowSnappingSection = () => {
+    const [saved, setSaved] = useState<SnapSettings>({ padding: 0, gap: 0 });
+    const [draft, setDraft] = useState({ padding: '0', gap: '0' });
+
+    useEffect(() => {
+        invoke<SnapSettings>('get_snap_settings')
+            .then((settings) => {
+                setSaved(settings);
+                setDraft({ padding: String(settings.padding), gap: String(settings.gap) });
+            })
+            .catch((e) => console.error('Failed to load snap settings:', e));
+    }, []);
+
+    const commit = async (key: keyof SnapSettings) => {
+        const value = Number(draft[key]);
+        if (draft[key].trim() === '' || value === saved[key]) {
+            setDraft((d) => ({ ...d, [key]: String(saved[key]) }));
+            return;
+        }
+        const settings = { ...saved, [key]: value };
+        try {
+            await invoke('set_snap_settings', { settings });
+            setSaved(settings);
+        } catch (e) {
+            toast.error('Could not save spacing', { description: errorMessage(e) });
+            setDraft((d) => ({ ...d, [key]: String(saved[key]) }));
+        }
+    };
+
+    return (
+        <div>
+            <h2 className="text-xl font-light text-foreground mb-4">Window Snapping</h2>
+            <div className="bg-card rounded-md p-4 border border-border shadow-sm">
+                <div className="flex flex-col gap-4">
+                    <div className="flex items-center gap-3">
+                        <div className="w-10 h-10 rounded-md bg-secondary text-secondary-foreground flex items-center justify-center">
+                            <LayoutGrid size={20} />
+                        </div>
+                        <div className="flex flex-col">
+                            <span className="text-sm font-medium text-foreground">Spacing</span>
+                            <span className="text-xs text-muted-foreground">
+                                Points kept around snapped and tiled windows
+                            </span>
+                        </div>
+                    </div>
+
+                    <div className="grid grid-cols-2 gap-2">
+                        {FIELDS.map(({ key, label }) => (
+                            <div key={key}>
+                                <label className="block text-[10px] font-bold text-muted-foreground uppercase tracking-widest mb-2 ml-1">
+                                    {label}
+                                </label>
+                                <input
+                                    type="number"
+                                    min={0}
+                                    className="w-full bg-input h-9 rounded-lg px-3 text-foreground focus:outline-none focus:ring-2 focus:ring-ring transition-all font-mono text-xs"
+                                    value={draft[key]}
+                                    onChange={(e) => setDraft((d) => ({ ...d, [key]: e.target.value }))}
+                                    onBlur={() => commit(key)}
+                                    onKeyDown={(e) => e.key === 'Enter' && e.currentTarget.blur()}
+                                />
+                            </div>
+                        ))}
+                    </div>
+                </div>
+            </div>
+        </div>
+    );
+};
@@ -1,30 +1,18 @@
-import { useEffect } from 'react';
-import { invoke } from '@tauri-apps/api/core';
-import { useSettingsStore } from '@/stores/settings-store';
 import { useUIStore } from '@/stores/ui-store';
 import { BehaviorSection } from './sections/behavior-section';
 import { PermissionsSection } from './sections/permissions-section';
 import { IntelligenceSection } from './sections/intelligence-section';
 import { ThemeSection } from './sections/theme-section';
+import { WindowSnappingSection } from './sections/window-snapping-section';
 
 export const SettingsView = () => {
-    const { drawerPosition } = useSettingsStore();
     const { setActiveView } = useUIStore();
 
-    useEffect(() => {
-        // Sync drawer position with backend
-        invoke('set_drawer_config', { config: drawerPosition });
-    }, []);
-
-    // Sync drawer position when it changes
-    useEffect(() => {
-        invoke('set_drawer_config', { config: drawerPosition });
-    }, [drawerPosition]);
-
     return (
         <div className="h-full px-4 py-3 overflow-y-auto scrollbar-none space-y-6">
             <ThemeSection />
             <BehaviorSection />
+            <WindowSnappingSection />
             <IntelligenceSection />
             <PermissionsSection />
 
@@ -6,6 +6,7 @@ import { motion, AnimatePresence } from 'framer-motion';
 import { useShortcutsStore } from '@/stores/shortcuts-store';
 import { AppShortcut } from '@/types/shortcuts';
 import { clsx } from 'clsx';
+import { errorCode } from '@/lib/app-error';
 
 // Icon Component to fetch and display app icon
 const AppIcon = ({ path, className }: { path: string, className?: string }) => {
@@ -18,7 +19,8 @@ const AppIcon = ({ path, className }: { path: string, className?: string }) => {
         const src = await invoke<string>('get_app_icon', { path });
         if (active) setIconSrc(src);
       } catch (err) {
-        console.error("Failed to load icon:", err);
+        // Icons are only available on macOS, elsewhere the fallback is shown
+        if (errorCode(err) !== 'unsupported') console.error("Failed to load icon:", err);
       }
     };
     fetchIcon();
@@ -19,13 +19,13 @@ export function TabsStrip() {
                 ? "bg-primary/10 text-primary border-primary/20"
                 : "hover:bg-accent hover:text-accent-foreground text-muted-foreground"
             )}
-            title={tab.title || tab.url}
+            title={tab.loadError ? `${tab.title || tab.url}: ${tab.loadError.message}` : tab.title || tab.url}
           >
             {/* Favicon or fallback */}
             {tab.loading ? (
               <div className="w-3 h-3 rounded-full border border-current border-t-transparent animate-spin shrink-0" />
             ) : (
-              <div className="w-3 h-3 rounded-full bg-current/20 shrink-0" />
+              <div className={cn("w-3 h-3 rounded-full shrink-0", tab.loadError ? "bg-destructive/60" : "bg-current/20")} />
             )}
 
             <span className="truncate flex-1">
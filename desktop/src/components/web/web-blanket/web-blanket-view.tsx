@@ -1,5 +1,6 @@
 import { useEffect, useRef, useLayoutEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { errorMessage } from "@/lib/app-error";
 import { useWebBlanketStore } from "@/stores/web-blanket-store";
 import { useUIStore } from "@/stores/ui-store";
 import { TabsStrip } from "./tabs-strip";
@@ -52,7 +53,7 @@ export function WebBlanketView() {
     }).then(() => {
       // Ensure shown after bounds set if supposed to be visible
       if (shouldShowNative) {
-        invoke("web_blanket_show").catch(() => { });
+        invoke("web_blanket_show").catch((e) => console.warn("web_blanket_show failed:", errorMessage(e)));
       }
     }).catch(console.error);
   };
@@ -79,13 +80,13 @@ export function WebBlanketView() {
     if (shouldShowNative) {
       updateBounds();
       // Show is handled in updateBounds callback too, but good to have explicit trigger
-      invoke("web_blanket_show").catch(() => { });
+      invoke("web_blanket_show").catch((e) => console.warn("web_blanket_show failed:", errorMessage(e)));
     } else {
-      invoke("web_blanket_hide").catch(() => { });
+      invoke("web_blanket_hide").catch((e) => console.warn("web_blanket_hide failed:", errorMessage(e)));
     }
 
     return () => {
-      invoke("web_blanket_hide").catch(() => { });
+      invoke("web_blanket_hide").catch((e) => console.warn("web_blanket_hide failed:", errorMessage(e)));
     }
   }, [shouldShowNative]);
 
@@ -96,15 +97,10 @@ export function WebBlanketView() {
     }
   }, [mode, theme]);
 
-  // Polling for tab state (loading, title, url)
+  // Catch up on the active tab once; later changes arrive as `web-blanket:tab` events
   useEffect(() => {
     if (mode !== "browse" || !activeTabId) return;
-
-    const interval = setInterval(() => {
-      syncTabState(activeTabId);
-    }, 500);
-
-    return () => clearInterval(interval);
+    syncTabState(activeTabId);
   }, [mode, activeTabId]);
 
   // Keyboard Shortcuts
@@ -2,6 +2,7 @@ import { invoke } from "@tauri-apps/api/core";
 import { getProvider } from "@/core/infra/ai/provider-factory";
 import { streamText } from "ai";
 import { MODELS } from "@/core/domain/models";
+import { errorMessage } from "@/lib/app-error";
 
 export interface ScrapingResult {
   url: string;
@@ -78,7 +79,9 @@ If the answer is not in the content, state that clearly.
     }
 
     // Call Rust backend
-    const content = await invoke<string>("fetch_webpage", { url });
+    const content = await invoke<string>("fetch_webpage", { url }).catch((e) => {
+      throw new Error(errorMessage(e));
+    });
     
     if (!content || content.length < 50) {
         throw new Error("Failed to retrieve meaningful content from the URL.");
@@ -0,0 +1,45 @@
+/** Error returned by Rust commands, see `src-tauri/src/error.rs`. */
+export interface AppError {
+  code: AppErrorCode;
+  message: string;
+  details: Record<string, unknown>;
+}
+
+export type AppErrorCode =
+  | 'permissionDenied'
+  | 'appNotScriptable'
+  | 'windowNotFound'
+  | 'failed'
+  | 'drawerNotFound'
+  | 'drawerEdgeTaken'
+  | 'presetNotFound'
+  | 'workspaceNotFound'
+  | 'tilingOff'
+  | 'tabNotFound'
+  | 'webBlanketUnavailable'
+  | 'invalidUrl'
+  | 'invalidMatchPattern'
+  | 'bridgeRequestNotFound'
+  | 'invalidInput'
+  | 'launch'
+  | 'shortcut'
+  | 'webview'
+  | 'clipboard'
+  | 'fetch'
+  | 'unsupported'
+  | 'other';
+
+export function isAppError(err: unknown): err is AppError {
+  return typeof err === 'object' && err !== null && 'code' in err && 'message' in err;
+}
+
+/** The error's code, or `undefined` for anything that isn't an `AppError`. */
+export function errorCode(err: unknown): AppErrorCode | undefined {
+  return isAppError(err) ? err.code : undefined;
+}
+
+export function errorMessage(err: unknown): string {
+  if (isAppError(err)) return err.message;
+  if (err instanceof Error) return err.message;
+  return String(err);
+}
@@ -1,9 +1,13 @@
 import React from "react";
 import ReactDOM from "react-dom/client";
 import App from "./app";
+import { SnapOverlay } from "@/components/layouts/snap-overlay";
+
+// The drag-to-snap overlay window loads the same page with ?overlay=snap-overlay
+const isSnapOverlay = new URLSearchParams(window.location.search).get("overlay") === "snap-overlay";
 
 ReactDOM.createRoot(document.getElementById("root") as HTMLElement).render(
   <React.StrictMode>
-    <App />
+    {isSnapOverlay ? <SnapOverlay /> : <App />}
   </React.StrictMode>,
 );
@@ -1,6 +1,8 @@
 import { create } from 'zustand';
 import Database from '@tauri-apps/plugin-sql';
+import { invoke } from '@tauri-apps/api/core';
 import { v4 as uuidv4 } from 'uuid';
+import { errorCode, errorMessage } from '@/lib/app-error';
 
 export interface WindowRect {
   x: number;
@@ -15,13 +17,41 @@ export interface WindowInfo {
   app_name: string;
   title: string;
   frame: WindowRect;
+  app_path?: string;
+  display_id?: number;
+  relative_frame?: WindowRect;
+  bundle_id?: string;
+  role?: string;
+  state?: WindowState;
 }
 
+export interface WindowState {
+  minimized: boolean;
+  fullscreen: boolean;
+  focused: boolean;
+  on_screen: boolean;
+  layer: number;
+}
+
+/** User-facing explanation of an error from a window command such as `snap_active_window`. */
+export const describeWindowError = (err: unknown): string => {
+  switch (errorCode(err)) {
+    case 'permissionDenied':
+      return 'Grant Accessibility access in Settings > Permissions to manage windows.';
+    case 'windowNotFound':
+      return 'No window to arrange.';
+    default:
+      return errorMessage(err);
+  }
+};
+
 export interface SavedLayout {
   id: string;
   name: string;
   description?: string;
   layout_data: WindowInfo[];
+  display_key?: string;
+  auto_apply: boolean;
   created_at: string;
 }
 
@@ -31,6 +61,7 @@ interface LayoutsState {
   loadLayouts: () => Promise<void>;
   saveLayout: (name: string, windows: WindowInfo[]) => Promise<void>;
   deleteLayout: (id: string) => Promise<void>;
+  setAutoApply: (id: string, autoApply: boolean) => Promise<void>;
 }
 
 export const useLayoutsStore = create<LayoutsState>((set, get) => ({
@@ -48,6 +79,8 @@ export const useLayoutsStore = create<LayoutsState>((set, get) => ({
         name: row.name,
         description: row.description,
         layout_data: JSON.parse(row.layout_data),
+        display_key: row.display_key ?? undefined,
+        auto_apply: !!row.auto_apply,
         created_at: row.created_at,
       }));
 
@@ -65,10 +98,11 @@ export const useLayoutsStore = create<LayoutsState>((set, get) => ({
       const id = uuidv4();
       const now = new Date().toISOString();
       const layoutDataStr = JSON.stringify(windows);
+      const displayKey = await invoke<string>('get_display_arrangement');
 
       await db.execute(
-        'INSERT INTO window_layouts (id, name, layout_data, created_at, updated_at) VALUES ($1, $2, $3, $4, $5)',
-        [id, name, layoutDataStr, now, now]
+        'INSERT INTO window_layouts (id, name, layout_data, display_key, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6)',
+        [id, name, layoutDataStr, displayKey, now, now]
       );
 
       // Reload
@@ -90,4 +124,21 @@ export const useLayoutsStore = create<LayoutsState>((set, get) => ({
       console.error('Failed to delete layout:', error);
     }
   }
+,
+
+  setAutoApply: async (id: string, autoApply: boolean) => {
+    try {
+      const db = await Database.load('sqlite:mydrawer.db');
+      await db.execute(
+        'UPDATE window_layouts SET auto_apply = $1, updated_at = $2 WHERE id = $3',
+        [autoApply ? 1 : 0, new Date().toISOString(), id]
+      );
+
+      set(state => ({
+        layouts: state.layouts.map(l => l.id === id ? { ...l, auto_apply: autoApply } : l)
+      }));
+    } catch (error) {
+      console.error('Failed to update layout:', error);
+    }
+  }
 }));
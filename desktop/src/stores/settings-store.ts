@@ -1,4 +1,6 @@
 import { create } from "zustand";
+import { invoke } from "@tauri-apps/api/core";
+import { getCurrentWindow } from "@tauri-apps/api/window";
 import { AIConfiguration } from "@/types/ai";
 import { SettingsRepository } from "@/core/infra/repositories";
 
@@ -12,6 +14,7 @@ interface SettingsState {
   clipboardRetentionDays: number;
   hasCompletedOnboarding: boolean;
   autoHide: boolean;
+  /** Edge of the drawer this window belongs to; the backend keeps one per drawer */
   drawerPosition: 'left' | 'right' | 'hot-corners' | 'top-left' | 'bottom-left' | 'top-right' | 'bottom-right';
   todoDeleteOnComplete: boolean;
   enabledModels: string[];
@@ -72,7 +75,8 @@ export const useSettingsStore = create<SettingsState>((set, get) => ({
         settingsRepo.get<number>('clipboard_retention_days'),
         settingsRepo.get<boolean>('has_completed_onboarding'),
         settingsRepo.get<boolean>('auto_hide'),
-        settingsRepo.get<SettingsState['drawerPosition']>('drawer_position'),
+        invoke<{ id: string; edge: SettingsState['drawerPosition'] }[]>('list_drawers')
+          .then(drawers => drawers.find(d => d.id === getCurrentWindow().label)?.edge),
         settingsRepo.get<boolean>('todo_delete_on_complete'),
         settingsRepo.get<string[]>('enabled_models'),
       ]);
@@ -120,8 +124,8 @@ export const useSettingsStore = create<SettingsState>((set, get) => ({
   },
 
   setDrawerPosition: async (position) => {
+    await invoke('set_drawer_config', { drawerId: getCurrentWindow().label, config: position });
     set({ drawerPosition: position });
-    await settingsRepo.set('drawer_position', position);
   },
 
   setTodoDeleteOnComplete: async (enabled) => {
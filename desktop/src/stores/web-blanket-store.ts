@@ -1,9 +1,16 @@
 import { create } from "zustand";
 import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
+import { getCurrentWebviewWindow } from "@tauri-apps/api/webviewWindow";
 import { SettingsRepository } from "@/core/infra/repositories";
 import { normalizeUrl } from "@/lib/url";
+import { errorCode, errorMessage } from "@/lib/app-error";
 import { v4 as uuidv4 } from "uuid";
+import { toast } from "sonner";
+import { useChatStore } from "./chat-store";
+import { useTodoStore } from "./todo-store";
+import { useUIStore } from "./ui-store";
+import type { WorkspaceWebSession } from "./workspace-store";
 import { historyService, HistoryFilter, WebHistoryEntry } from "@/core/application/services/history-service";
 
 const settingsRepo = new SettingsRepository();
@@ -30,6 +37,36 @@ export type WebBlanketTab = {
   zoom?: number;
   userAgent?: "mobile" | "desktop";
   lastHistoryUrl?: string; // Track last URL added to history
+  progress?: number; // 0..1 while loading
+  loadError?: { code: number; message: string };
+};
+
+/** Pushed by Rust as `web-blanket:tab` whenever a tab's page changes. */
+export type WebBlanketTabEvent = { tabId: string } & (
+  | { type: "started"; url: string }
+  | { type: "committed"; url: string }
+  | { type: "finished"; url: string }
+  | { type: "failed"; url: string; code: number; message: string }
+  | { type: "progress"; progress: number }
+  | { type: "title"; title: string }
+  | { type: "urlChanged"; url: string }
+  | { type: "history"; canGoBack: boolean; canGoForward: boolean }
+);
+
+export type WebBlanketBridgeAction = "copyText" | "saveToNotes" | "sendToChat";
+
+/** Pushed by Rust as `web-blanket:bridge` for calls pages make through `window.myDrawer`. */
+export type WebBlanketBridgeEvent = { tabId: string; origin: string; url: string } & (
+  | { type: "permissionRequest"; requestId: number; action: WebBlanketBridgeAction }
+  | { type: "copiedText"; text: string }
+  | { type: "savedNote"; noteId: string; title: string }
+  | { type: "sendToChat"; text: string }
+);
+
+const BRIDGE_ACTION_LABELS: Record<WebBlanketBridgeAction, string> = {
+  copyText: "add text to your clipboard history",
+  saveToNotes: "save notes",
+  sendToChat: "send text to the chat",
 };
 
 interface WebBlanketState {
@@ -60,10 +97,13 @@ interface WebBlanketState {
   activateTab: (tabId: string) => Promise<void>;
   closeTab: (tabId: string) => Promise<void>;
   updateTab: (tabId: string, updates: Partial<WebBlanketTab>) => void; // local update
+  restoreSession: (tabs: WorkspaceWebSession["tabs"], activeTabId: string | null) => Promise<void>;
   
   // Navigation
   navigate: (tabId: string, url: string) => Promise<void>;
   syncTabState: (tabId: string) => Promise<void>;
+  handleTabEvent: (event: WebBlanketTabEvent) => void;
+  handleBridgeEvent: (event: WebBlanketBridgeEvent) => void;
   goBack: () => Promise<void>;
   goForward: () => Promise<void>;
   reload: () => Promise<void>;
@@ -95,6 +135,21 @@ const createDefaultFavorites = (): WebBlanketFavorite[] => [
   { id: uuidv4(), title: "My Drawer", url: "https://mydrawer.furkanksl.com", createdAt: Date.now(), updatedAt: Date.now() },
 ];
 
+// A native tab that is gone, or a container that isn't created yet, is expected
+// while tabs are being restored. Anything else is worth a warning.
+const isExpectedNativeError = (e: unknown) => {
+  const code = errorCode(e);
+  return code === "tabNotFound" || code === "webBlanketUnavailable";
+};
+
+const invokeNative = async (command: string, args?: Record<string, unknown>) => {
+  try {
+    await invoke(command, args);
+  } catch (e) {
+    if (!isExpectedNativeError(e)) console.warn(`${command} failed:`, errorMessage(e));
+  }
+};
+
 export const useWebBlanketStore = create<WebBlanketState>((set, get) => ({
   mode: "browse",
   enabled: false,
@@ -122,6 +177,15 @@ export const useWebBlanketStore = create<WebBlanketState>((set, get) => ({
           get().createTab(event.payload.url);
       }).catch(e => console.error("Failed to setup new window listener", e));
 
+      // Tab and bridge events are sent only to the window showing the tab
+      getCurrentWebviewWindow().listen<WebBlanketTabEvent>("web-blanket:tab", (event) => {
+          get().handleTabEvent(event.payload);
+      }).catch(e => console.error("Failed to setup tab event listener", e));
+
+      getCurrentWebviewWindow().listen<WebBlanketBridgeEvent>("web-blanket:bridge", (event) => {
+          get().handleBridgeEvent(event.payload);
+      }).catch(e => console.error("Failed to setup bridge listener", e));
+
       // Listen for menu shortcuts
       listen("web-blanket-new-tab", () => {
           get().createTab();
@@ -340,6 +404,45 @@ export const useWebBlanketStore = create<WebBlanketState>((set, get) => ({
     }
   },
 
+  restoreSession: async (savedTabs, savedActiveTabId) => {
+    const { tabs, enabled } = get();
+    await Promise.all(tabs.map(t =>
+        invoke("web_blanket_tab_close", { tabId: t.id })
+          .catch(e => console.warn("Native tab close failed:", t.id, e))
+    ));
+
+    const now = Date.now();
+    const newTabs: WebBlanketTab[] = savedTabs.map(t => ({
+      id: t.id,
+      url: t.url,
+      title: t.title || "New Tab",
+      createdAt: now,
+      lastActiveAt: now,
+      loading: false,
+      userAgent: t.userAgent,
+    }));
+    const activeTabId = newTabs.some(t => t.id === savedActiveTabId)
+        ? savedActiveTabId
+        : newTabs[newTabs.length - 1]?.id ?? null;
+
+    set({ tabs: newTabs, activeTabId });
+    await settingsRepo.set("web_blanket_tabs", newTabs);
+    await settingsRepo.set("web_blanket_active_tab_id", activeTabId);
+
+    if (!enabled) return;
+    for (const t of newTabs) {
+      await invoke("web_blanket_tab_create", { tabId: t.id, url: t.url || null })
+        .catch(e => console.error("Failed to restore tab:", t.id, e));
+      if (t.userAgent === "desktop") {
+        await invokeNative("web_blanket_set_user_agent", { tabId: t.id, mode: "desktop" });
+      }
+    }
+    if (activeTabId) {
+      await invoke("web_blanket_tab_activate", { tabId: activeTabId })
+        .catch(e => console.error("Failed to activate tab:", activeTabId, e));
+    }
+  },
+
   navigate: async (tabId, urlInput) => {
     const { ok, url } = normalizeUrl(urlInput);
     if (!ok) return;
@@ -357,7 +460,7 @@ export const useWebBlanketStore = create<WebBlanketState>((set, get) => ({
     try {
       await invoke("web_blanket_navigate", { tabId, url });
     } catch (e) {
-      console.warn("Native navigate failed:", e);
+      console.warn("Native navigate failed:", errorMessage(e));
       get().updateTab(tabId, { loading: false });
     }
   },
@@ -378,39 +481,98 @@ export const useWebBlanketStore = create<WebBlanketState>((set, get) => ({
               updates.url = state.url;
           }
           
-          const { tabs, updateTab } = get();
-          updateTab(tabId, updates);
-
-          // History Logic
-          const currentTab = tabs.find(t => t.id === tabId);
-          // Only add to history if:
-          // 1. Not loading
-          // 2. URL is valid and not about:blank
-          // 3. URL is different from last added history URL
-          if (!state.loading && state.url && state.url !== "about:blank" && currentTab && currentTab.lastHistoryUrl !== state.url) {
-              updateTab(tabId, { lastHistoryUrl: state.url });
-              historyService.addEntry(state.url, state.title || state.url);
-          }
-
+          get().updateTab(tabId, updates);
       } catch (e) {
-          // Tab might not exist native side or other error
+          if (!isExpectedNativeError(e)) console.warn("Failed to sync tab state:", tabId, errorMessage(e));
+      }
+  },
+
+  handleTabEvent: (event) => {
+      const { tabs, updateTab } = get();
+      const tab = tabs.find(t => t.id === event.tabId);
+      if (!tab) return;
+
+      switch (event.type) {
+          case "started":
+              updateTab(tab.id, { url: event.url, loading: true, progress: 0, loadError: undefined });
+              break;
+          case "committed":
+          case "urlChanged":
+              updateTab(tab.id, { url: event.url });
+              break;
+          case "finished":
+              updateTab(tab.id, { url: event.url, loading: false, progress: 1 });
+              // Only add real pages to history, once per URL
+              if (event.url && event.url !== "about:blank" && tab.lastHistoryUrl !== event.url) {
+                  updateTab(tab.id, { lastHistoryUrl: event.url });
+                  historyService.addEntry(event.url, tab.title || event.url);
+              }
+              break;
+          case "failed":
+              updateTab(tab.id, { loading: false, loadError: { code: event.code, message: event.message } });
+              break;
+          case "progress":
+              updateTab(tab.id, { progress: event.progress });
+              break;
+          case "title":
+              updateTab(tab.id, { title: event.title || "New Tab" });
+              break;
+          case "history":
+              updateTab(tab.id, { canGoBack: event.canGoBack, canGoForward: event.canGoForward });
+              break;
+      }
+  },
+
+  handleBridgeEvent: (event) => {
+      const site = event.origin.replace(/^https?:\/\//, "");
+
+      switch (event.type) {
+          case "permissionRequest": {
+              // Only the first answer counts; dismissing the toast denies this call only
+              let answered = false;
+              const answer = (allowed: boolean, remember: boolean) => {
+                  if (answered) return;
+                  answered = true;
+                  invoke("resolve_bridge_request", { requestId: event.requestId, allowed, remember }).catch(e => {
+                      if (errorCode(e) !== "bridgeRequestNotFound") console.error("Failed to answer bridge request:", errorMessage(e));
+                  });
+              };
+              toast(`${site} wants to ${BRIDGE_ACTION_LABELS[event.action]}`, {
+                  duration: Infinity,
+                  action: { label: "Allow", onClick: () => answer(true, true) },
+                  cancel: { label: "Deny", onClick: () => answer(false, true) },
+                  onDismiss: () => answer(false, false),
+              });
+              break;
+          }
+          case "copiedText":
+              toast.success(`Added to clipboard history from ${site}`);
+              break;
+          case "savedNote":
+              useTodoStore.getState().init();
+              toast.success(`Saved "${event.title}" to notes`);
+              break;
+          case "sendToChat":
+              useChatStore.getState().setInput(event.text);
+              useUIStore.getState().setActiveView("chat");
+              break;
       }
   },
 
   goBack: async () => {
-      try { await invoke("web_blanket_go_back"); } catch (e) {}
+      await invokeNative("web_blanket_go_back");
   },
   
   goForward: async () => {
-      try { await invoke("web_blanket_go_forward"); } catch (e) {}
+      await invokeNative("web_blanket_go_forward");
   },
   
   reload: async () => {
-      try { await invoke("web_blanket_reload"); } catch (e) {}
+      await invokeNative("web_blanket_reload");
   },
   
   stop: async () => {
-      try { await invoke("web_blanket_stop_loading"); } catch (e) {}
+      await invokeNative("web_blanket_stop_loading");
   },
 
   zoomIn: async () => {
@@ -420,7 +582,7 @@ export const useWebBlanketStore = create<WebBlanketState>((set, get) => ({
       if (tab) {
           const newZoom = (tab.zoom || 1) + 0.1;
           get().updateTab(activeTabId, { zoom: newZoom });
-          try { await invoke("web_blanket_zoom_in"); } catch (e) {}
+          await invokeNative("web_blanket_zoom_in");
       }
   },
 
@@ -431,7 +593,7 @@ export const useWebBlanketStore = create<WebBlanketState>((set, get) => ({
       if (tab) {
           const newZoom = Math.max(0.5, (tab.zoom || 1) - 0.1);
           get().updateTab(activeTabId, { zoom: newZoom });
-          try { await invoke("web_blanket_zoom_out"); } catch (e) {}
+          await invokeNative("web_blanket_zoom_out");
       }
   },
 
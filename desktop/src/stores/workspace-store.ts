@@ -0,0 +1,75 @@
+import { create } from 'zustand';
+import { invoke } from '@tauri-apps/api/core';
+import { useUIStore } from './ui-store';
+import { WindowInfo } from './layouts-store';
+
+export interface WorkspaceDrawerState {
+  edge: string;
+  side: number;
+  open: boolean;
+  activeView: string;
+}
+
+export interface WorkspaceWebSession {
+  tabs: { id: string; url: string; title: string; userAgent: "mobile" | "desktop" }[];
+  activeTabId: string | null;
+}
+
+export interface Workspace {
+  id: string;
+  name: string;
+  windows: WindowInfo[];
+  web: WorkspaceWebSession;
+  drawer: WorkspaceDrawerState;
+  createdAt: string;
+}
+
+interface WorkspaceState {
+  workspaces: Workspace[];
+  loadWorkspaces: () => Promise<void>;
+  captureWorkspace: (name: string) => Promise<void>;
+  restoreWorkspace: (id: string) => Promise<void>;
+  deleteWorkspace: (id: string) => Promise<void>;
+}
+
+export const useWorkspaceStore = create<WorkspaceState>((set, get) => ({
+  workspaces: [],
+
+  loadWorkspaces: async () => {
+    try {
+      const workspaces = await invoke<Workspace[]>('list_workspaces');
+      set({ workspaces });
+    } catch (error) {
+      console.error('Failed to load workspaces:', error);
+    }
+  },
+
+  captureWorkspace: async (name: string) => {
+    try {
+      const activeView = useUIStore.getState().activeView;
+      await invoke('capture_workspace', { name, activeView });
+      get().loadWorkspaces();
+    } catch (error) {
+      console.error('Failed to capture workspace:', error);
+    }
+  },
+
+  restoreWorkspace: async (id: string) => {
+    try {
+      await invoke('restore_workspace', { id });
+    } catch (error) {
+      console.error('Failed to restore workspace:', error);
+    }
+  },
+
+  deleteWorkspace: async (id: string) => {
+    try {
+      await invoke('delete_workspace', { id });
+      set(state => ({
+        workspaces: state.workspaces.filter(w => w.id !== id)
+      }));
+    } catch (error) {
+      console.error('Failed to delete workspace:', error);
+    }
+  }
+}));